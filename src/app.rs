@@ -1,6 +1,11 @@
 use crate::utils::commons::*;
+use crate::utils::registry::Registry;
+use crate::utils::workspace::{Workspace, WORKSPACE_KEY};
+use crate::windows::dropped_files::DroppedFiles;
+use crate::windows::expression_bar::ExpressionBar;
 use crate::windows::list_window::ListUi;
 use crate::windows::main_windows::MainWindows;
+use crate::windows::open_url::OpenUrl;
 
 use eframe::egui;
 use egui::menu;
@@ -19,11 +24,26 @@ extern "C" {
 
 // -------------------------------------------------------------------
 
+// The shortcuts undoing and redoing the last action (Cmd on Mac).
+const UNDO: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
+
 //FileApp struct
 #[derive(Default)]
 pub struct FileApp {
     list: ListUi,
     mainuis: MainWindows,
+    dropped: DroppedFiles,
+    open_url: OpenUrl,
+    expression: ExpressionBar,
+    // The MOCs known by the app, with the history of the actions done on them.
+    registry: Registry,
+    // The MOCs loaded from the file dialog, added to the registry at the next frame.
+    loaded: Loaded,
 }
 impl eframe::App for FileApp {
     //////////////////////
@@ -40,6 +60,8 @@ impl eframe::App for FileApp {
             frame.set_fullscreen(!frame.info().window_info.fullscreen);
         }
 
+        self.history_shortcuts(ctx);
+
         self.desktop_ui(ctx);
 
         // On web, the browser controls `pixels_per_point`.
@@ -47,8 +69,43 @@ impl eframe::App for FileApp {
             egui::gui_zoom::zoom_with_keyboard_shortcuts(ctx, frame.info().native_pixels_per_point);
         }
     }
+
+    // #Definition
+    //      A function saving the workspace (MOCs, names and open windows), called by eframe
+    //      at a given interval and when the app is closed.
+    // #Args
+    //  *    storage: the eframe storage, a file on native and the local storage on web
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        match Workspace::capture(
+            &self.registry,
+            self.list.open_mocs(),
+            self.mainuis.open_windows(),
+        ) {
+            Ok(workspace) => eframe::set_value(storage, WORKSPACE_KEY, &workspace),
+            Err(e) => log::error!("Workspace could not be saved: {e}"),
+        }
+    }
 }
 impl FileApp {
+    // #Definition
+    //      Creates the app, restoring the workspace of the previous session if one was saved.
+    // #Args
+    //  *    cc: the eframe creation context giving access to the storage
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(workspace) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Workspace>(storage, WORKSPACE_KEY))
+        {
+            if let Err(e) = workspace.restore(&mut app.registry) {
+                err(&e);
+            }
+            app.list.set_open_mocs(workspace.open_mocs);
+            app.mainuis.set_open_windows(workspace.open_windows);
+        }
+        app
+    }
+
     /////////////////////
     // Basic functions //
 
@@ -64,42 +121,113 @@ impl FileApp {
         menu::bar(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.menu_button("Files", |ui| {
-                    ui.menu_button("Load", |ui| {
+                    if ui.button("Open…").clicked() {
+                        ui.close_menu();
+                        let _ = load(&["fits", "fit", "json", "ascii", "txt"], None, &self.loaded)
+                            .map_err(|e| err(&e));
+                    }
+                    if ui.button("Open URL…").clicked() {
+                        ui.close_menu();
+                        self.open_url.open();
+                    }
+                    ui.menu_button("Open as", |ui| {
                         if ui.button("FITS").clicked() {
                             //Qty::Space here is a default it is not actually used
-                            assert!(load(&["fits"], MocQType::Space).is_ok());
+                            let _ = load(&["fits"], Some(MocQType::Space), &self.loaded)
+                                .map_err(|e| err(&e));
                         }
                         ui.menu_button("JSON", |ui| {
                             if ui.button("Space").clicked() {
-                                assert!(load(&["json"], MocQType::Space).is_ok());
+                                let _ = load(&["json"], Some(MocQType::Space), &self.loaded)
+                                    .map_err(|e| err(&e));
                             }
                             if ui.button("Time").clicked() {
-                                assert!(load(&["json"], MocQType::Time).is_ok());
+                                let _ = load(&["json"], Some(MocQType::Time), &self.loaded)
+                                    .map_err(|e| err(&e));
                             }
                             if ui.button("Spacetime").clicked() {
-                                assert!(load(&["json"], MocQType::TimeSpace).is_ok());
+                                let _ = load(&["json"], Some(MocQType::TimeSpace), &self.loaded)
+                                    .map_err(|e| err(&e));
+                            }
+                            if ui.button("Frequency").clicked() {
+                                let _ = load(&["json"], Some(MocQType::Frequency), &self.loaded)
+                                    .map_err(|e| err(&e));
                             }
                         });
                         ui.menu_button("ASCII", |ui| {
                             if ui.button("Space").clicked() {
-                                assert!(load(&["ascii", "txt"], MocQType::Space).is_ok());
+                                let _ =
+                                    load(&["ascii", "txt"], Some(MocQType::Space), &self.loaded)
+                                        .map_err(|e| err(&e));
                             }
                             if ui.button("Time").clicked() {
-                                assert!(load(&["ascii", "txt"], MocQType::Time).is_ok());
+                                let _ = load(&["ascii", "txt"], Some(MocQType::Time), &self.loaded)
+                                    .map_err(|e| err(&e));
                             }
                             if ui.button("Spacetime").clicked() {
-                                assert!(load(&["ascii", "txt"], MocQType::TimeSpace).is_ok());
+                                let _ = load(
+                                    &["ascii", "txt"],
+                                    Some(MocQType::TimeSpace),
+                                    &self.loaded,
+                                )
+                                .map_err(|e| err(&e));
+                            }
+                            if ui.button("Frequency").clicked() {
+                                let _ = load(
+                                    &["ascii", "txt"],
+                                    Some(MocQType::Frequency),
+                                    &self.loaded,
+                                )
+                                .map_err(|e| err(&e));
                             }
                         });
                     })
                 });
                 ui.separator();
+                ui.menu_button("Edit", |ui| {
+                    let undo = self.registry.undo_label();
+                    let button = egui::Button::new(match &undo {
+                        Some(label) => format!("Undo {label}"),
+                        None => String::from("Undo"),
+                    })
+                    .shortcut_text(ui.ctx().format_shortcut(&UNDO));
+                    if ui.add_enabled(undo.is_some(), button).clicked() {
+                        ui.close_menu();
+                        self.registry.undo();
+                    }
+                    let redo = self.registry.redo_label();
+                    let button = egui::Button::new(match &redo {
+                        Some(label) => format!("Redo {label}"),
+                        None => String::from("Redo"),
+                    })
+                    .shortcut_text(ui.ctx().format_shortcut(&REDO));
+                    if ui.add_enabled(redo.is_some(), button).clicked() {
+                        ui.close_menu();
+                        self.registry.redo();
+                    }
+                });
+                ui.separator();
                 ui.menu_button("Tools", |ui| {
                     self.mainui_list_ui(ui);
                 });
             });
         });
     }
+    // #Definition
+    //      Undoes (Ctrl+Z) or redoes (Ctrl+Shift+Z) the last action, unless a text field is
+    //      being edited as it has its own undo.
+    // #Args
+    //  *   ctx: the app's context
+    fn history_shortcuts(&mut self, ctx: &Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO)) {
+            self.registry.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO)) {
+            self.registry.undo();
+        }
+    }
     fn desktop_ui(&mut self, ctx: &Context) {
         egui::TopBottomPanel::top("wrap_app_top_bar").show(ctx, |ui| {
             egui::trace!(ui);
@@ -109,14 +237,36 @@ impl FileApp {
             });
         });
 
-        self.list.ui(ctx);
+        for moc in take_loaded(&self.loaded) {
+            if let Err(e) = self
+                .registry
+                .load(&moc.source, &moc.name, moc.id, moc.metadata)
+            {
+                err(&e);
+            }
+        }
+
+        self.expression.ui(ctx, &mut self.registry);
+
+        self.list.ui(ctx, &mut self.registry);
 
         self.show_windows(ctx);
+
+        self.dropped.ui(ctx, &mut self.registry);
+
+        if let Some((url, name, data)) = self.open_url.ui(ctx) {
+            if let Err(e) = self
+                .dropped
+                .add_content(&url, &name, data, &mut self.registry)
+            {
+                err(&e);
+            }
+        }
     }
 
     /// Show the open windows.
     fn show_windows(&mut self, ctx: &Context) {
-        self.mainuis.windows(ctx);
+        self.mainuis.windows(ctx, &mut self.registry);
     }
 
     fn mainui_list_ui(&mut self, ui: &mut egui::Ui) {
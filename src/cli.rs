@@ -0,0 +1,820 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+use structopt::StructOpt;
+
+use crate::controllers::{
+    creation::*,
+    filter::{
+        filter_csv, first_line, guess_column, guess_delimiter, is_header, split_line, Filter, Role,
+        DEC, JD, RA,
+    },
+    frame::Frame,
+    op1::{op1, Op1},
+    op2::{op2, Op2},
+    opn::{opn, OpN},
+    query::columns,
+};
+use crate::error::Error;
+use crate::utils::{
+    commons::{download, fmt_qty, freq_range_hz, type_reading},
+    compression::{decompress_reader, unpack},
+    detect::detect_reading,
+    provenance::{self, fits_metadata},
+    registry::Registry,
+};
+
+// The command line arguments.
+// Without any subcommand the GUI is launched, `cli` runs the app headless.
+#[derive(StructOpt)]
+#[structopt(name = "rust_moc_gui", about = "Create, combine and inspect MOCs.")]
+pub struct Args {
+    #[structopt(subcommand)]
+    pub mode: Option<Mode>,
+}
+
+#[derive(StructOpt)]
+pub enum Mode {
+    /// Runs the MOC operations without opening a window.
+    Cli(Command),
+}
+
+#[derive(StructOpt)]
+pub enum Command {
+    /// Loads MOC files and prints a summary line for each of them.
+    Load {
+        /// Files to load (fits, json, ascii or txt), http(s) URLs are downloaded.
+        #[structopt(parse(from_os_str), required = true)]
+        files: Vec<PathBuf>,
+        /// Quantity of json/ascii files: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+    },
+    /// Creates a new MOC and writes it to a file.
+    Create {
+        /// MOC maximum depth.
+        #[structopt(short, long)]
+        depth: u8,
+        /// Frame of the positions: icrs (default), fk5, galactic or ecliptic.
+        #[structopt(long)]
+        frame: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+        #[structopt(subcommand)]
+        typ: CreateCmd,
+    },
+    /// Performs an operation on one MOC and writes the result.
+    Op1 {
+        /// Operation: complement, degrade, extend, contract, extborder, intborder, split or splitindirect.
+        operation: String,
+        /// Input MOC file.
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// New depth, required by degrade.
+        #[structopt(long)]
+        depth: Option<u8>,
+        /// Quantity of a json/ascii input: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    /// Performs an operation between two MOCs and writes the result.
+    Op2 {
+        /// Operation: intersection, union, difference, minus, tfold or sfold.
+        operation: String,
+        /// First input MOC file.
+        #[structopt(parse(from_os_str))]
+        left: PathBuf,
+        /// Second input MOC file.
+        #[structopt(parse(from_os_str))]
+        right: PathBuf,
+        /// Quantity of json/ascii inputs: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    /// Performs an operation over any number of MOCs of the same type and writes the result.
+    Opn {
+        /// Operation: union, intersection or minus (successive, in the given order).
+        operation: String,
+        /// Input MOC files.
+        #[structopt(parse(from_os_str), required = true, min_values = 2)]
+        inputs: Vec<PathBuf>,
+        /// Quantity of json/ascii inputs: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    /// Prints information about MOC files.
+    Info {
+        /// Files to inspect, http(s) URLs are downloaded.
+        #[structopt(parse(from_os_str), required = true)]
+        files: Vec<PathBuf>,
+        /// Quantity of json/ascii files: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+    },
+    /// Filters the rows of a csv catalogue by a MOC and writes the rows kept.
+    Filter {
+        /// MOC file: an S-MOC (position), a T-MOC (time) or an ST-MOC (both).
+        #[structopt(parse(from_os_str))]
+        moc: PathBuf,
+        /// Csv catalogue, possibly gzip or bzip2 compressed. It is streamed, not loaded in memory.
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// Column of the RA (°), by name or number (from 1), guessed from the header if not given.
+        #[structopt(long)]
+        ra: Option<String>,
+        /// Column of the Dec (°), by name or number (from 1), guessed from the header if not given.
+        #[structopt(long)]
+        dec: Option<String>,
+        /// Column of the time (JD), by name or number (from 1), guessed from the header if not given.
+        #[structopt(long)]
+        jd: Option<String>,
+        /// Keeps the rows out of the MOC instead of the rows in it.
+        #[structopt(long)]
+        reject: bool,
+        /// Quantity of a json/ascii MOC: space, time or spacetime, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+        /// Output csv file.
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Converts a MOC file to another format.
+    Convert {
+        /// Input MOC file.
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// Quantity of a json/ascii input: space, time, spacetime or frequency, detected from the content if not given.
+        #[structopt(long)]
+        qty: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+}
+
+// The creation types, mirroring the "MOC creation" window.
+// Coordinates and radii are in degrees, files are csv files.
+#[derive(StructOpt)]
+pub enum CreateCmd {
+    Cone {
+        lon: f64,
+        lat: f64,
+        radius: f64,
+    },
+    Ring {
+        lon: f64,
+        lat: f64,
+        internal: f64,
+        external: f64,
+    },
+    EllipticalCone {
+        lon: f64,
+        lat: f64,
+        a: f64,
+        b: f64,
+        pa: f64,
+    },
+    Zone {
+        lon_min: f64,
+        lat_min: f64,
+        lon_max: f64,
+        lat_max: f64,
+    },
+    Box {
+        lon: f64,
+        lat: f64,
+        a: f64,
+        b: f64,
+        pa: f64,
+    },
+    Polygon {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(long)]
+        complement: bool,
+    },
+    Coo {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    SmallCones {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    LargeCones {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    DecimalJd {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    DecimalJdRange {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    FrequencyRange {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    WavelengthRange {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    ValuedCells {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(long, default_value = "0")]
+        from_threshold: f64,
+        #[structopt(long, default_value = "1")]
+        to_threshold: f64,
+        #[structopt(long)]
+        density: bool,
+        #[structopt(long)]
+        asc: bool,
+        #[structopt(long)]
+        not_strict: bool,
+        #[structopt(long)]
+        split: bool,
+        #[structopt(long)]
+        revese_recursive_descent: bool,
+    },
+}
+
+#[derive(StructOpt)]
+pub struct Output {
+    /// Output file, its extension gives the format if `--format` is not set.
+    #[structopt(short, long, parse(from_os_str))]
+    output: PathBuf,
+    /// Output format: fits, json or ascii.
+    #[structopt(long)]
+    format: Option<String>,
+}
+
+// #Definition
+//      run executes a command line command, loading the input MOCs in the store
+//      and going through the same controllers (and MOC registry) as the GUI.
+// #Args
+//  *   `cmd`: the command to execute
+// #Errors
+//      Any error coming from file reading/writing or from the MOC operations.
+pub fn run(cmd: Command) -> Result<(), Error> {
+    let mut registry = Registry::default();
+    match cmd {
+        Command::Load { files, qty } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            for path in files {
+                for id in load_files(&path, qty, &mut registry)? {
+                    println!(
+                        "{}\t{}",
+                        registry.name(id)?,
+                        fmt_qty(U64MocStore.get_qty_type(id)?)
+                    );
+                }
+            }
+            Ok(())
+        }
+        Command::Create {
+            depth,
+            frame,
+            output,
+            typ,
+        } => {
+            let frame = frame.as_deref().map(parse_frame).transpose()?;
+            let id = create(depth, frame.unwrap_or_default(), typ, &mut registry)?;
+            write_mocs(&[id], &output, &registry)
+        }
+        Command::Op1 {
+            operation,
+            input,
+            depth,
+            qty,
+            output,
+        } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            let id = load_file(&input, qty, &mut registry)?;
+            let op = parse_op1(&operation, depth)?;
+            let name = format!("{op}_{}", registry.name(id)?);
+            let ids = op1(&mut registry, id, op, &name)?;
+            write_mocs(&ids, &output, &registry)
+        }
+        Command::Op2 {
+            operation,
+            left,
+            right,
+            qty,
+            output,
+        } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            let op = parse_op2(&operation)?;
+            let mut l = load_file(&left, qty, &mut registry)?;
+            let mut r = load_file(&right, qty, &mut registry)?;
+            // Same as in the GUI, the ST-MOC of a fold has to be on the right.
+            if matches!(op, Op2::SFold | Op2::TFold)
+                && matches!(U64MocStore.get_qty_type(l), Ok(MocQType::TimeSpace))
+                && matches!(
+                    U64MocStore.get_qty_type(r),
+                    Ok(MocQType::Space | MocQType::Time)
+                )
+            {
+                std::mem::swap(&mut l, &mut r);
+            }
+            let name = format!("{op}_{}_{}", registry.name(l)?, registry.name(r)?);
+            let id = op2(&mut registry, l, r, op, &name)?;
+            write_mocs(&[id], &output, &registry)
+        }
+        Command::Opn {
+            operation,
+            inputs,
+            qty,
+            output,
+        } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            let ids = inputs
+                .iter()
+                .map(|path| load_file(path, qty, &mut registry))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let op = parse_opn(&operation)?;
+            let id = opn(
+                &mut registry,
+                &ids,
+                op,
+                &format!("{op}_of_{}_mocs", ids.len()),
+            )?;
+            write_mocs(&[id], &output, &registry)
+        }
+        Command::Info { files, qty } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            for path in files {
+                for id in load_files(&path, qty, &mut registry)? {
+                    print_info(id, &registry)?;
+                }
+            }
+            Ok(())
+        }
+        Command::Filter {
+            moc,
+            input,
+            ra,
+            dec,
+            jd,
+            reject,
+            qty,
+            output,
+        } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            let id = load_file(&moc, qty, &mut registry)?;
+            let line = first_line(decompress_reader(BufReader::new(fs::File::open(&input)?))?)?;
+            let delimiter = guess_delimiter(&line);
+            let has_headers = is_header(&line, delimiter);
+            let headers = if has_headers {
+                split_line(&line, delimiter)
+            } else {
+                Vec::default()
+            };
+            let columns = columns(U64MocStore.get_qty_type(id)?)?
+                .iter()
+                .map(|column| match *column {
+                    "ra" => find_column(&headers, ra.as_deref(), &RA),
+                    "dec" => find_column(&headers, dec.as_deref(), &DEC),
+                    _ => find_column(&headers, jd.as_deref(), &JD),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let filter = Filter {
+                delimiter,
+                has_headers,
+                columns,
+                keep_inside: !reject,
+            };
+            let stats = filter_csv(
+                id,
+                decompress_reader(BufReader::new(fs::File::open(&input)?))?,
+                BufWriter::new(fs::File::create(&output)?),
+                &filter,
+                |_| true,
+            )?;
+            println!(
+                "Rows read: {}, kept: {}, invalid: {}",
+                stats.read, stats.kept, stats.invalid
+            );
+            Ok(())
+        }
+        Command::Convert { input, qty, output } => {
+            let qty = qty.as_deref().map(parse_qty).transpose()?;
+            let id = load_file(&input, qty, &mut registry)?;
+            write_mocs(&[id], &output, &registry)
+        }
+    }
+}
+
+// #Definition
+//      create creates a MOC the same way the creation window does.
+// #Args
+//  *   `depth`: the MOC maximum depth
+//  *   `frame`: the frame of the positions
+//  *   `typ`: the creation type and its parameters
+//  *   `registry`: the MOC registry the created MOC is added to
+fn create(
+    depth: u8,
+    frame: Frame,
+    typ: CreateCmd,
+    registry: &mut Registry,
+) -> Result<usize, Error> {
+    let (name, id) = match typ {
+        CreateCmd::Cone { lon, lat, radius } => (
+            format!("Cone_of_rad_{radius}"),
+            from_cone(frame, lon, lat, radius, depth)?,
+        ),
+        CreateCmd::Ring {
+            lon,
+            lat,
+            internal,
+            external,
+        } => (
+            format!("ring_of_rad_{internal}_{external}"),
+            from_ring(frame, lon, lat, internal, external, depth)?,
+        ),
+        CreateCmd::EllipticalCone { lon, lat, a, b, pa } => (
+            format!("ElipCone_deg_{a}_{b}_{pa}"),
+            from_elliptical_cone(frame, lon, lat, a, b, pa, depth)?,
+        ),
+        CreateCmd::Zone {
+            lon_min,
+            lat_min,
+            lon_max,
+            lat_max,
+        } => (
+            format!("Zone_deg_{lon_min}_{lat_min}"),
+            from_zone(frame, lon_min, lat_min, lon_max, lat_max, depth)?,
+        ),
+        CreateCmd::Box { lon, lat, a, b, pa } => (
+            format!("Box_deg_{a}_{b}_{pa}"),
+            from_box(frame, lon, lat, a, b, pa, depth)?,
+        ),
+        CreateCmd::Polygon { file, complement } => (
+            format!("Polygon_{depth}"),
+            from_polygon(
+                depth,
+                vector_splitter(read_to_string(&file)?),
+                complement,
+                frame,
+            )?,
+        ),
+        CreateCmd::Coo { file } => (
+            format!("Coo_{depth}"),
+            from_coo(depth, vector_splitter(read_to_string(&file)?), frame)?,
+        ),
+        CreateCmd::SmallCones { file } => (
+            format!("SmallCone_{depth}"),
+            from_small_cones(depth, vector_splitter(read_to_string(&file)?), frame)?,
+        ),
+        CreateCmd::LargeCones { file } => (
+            format!("LargeCone_{depth}"),
+            from_large_cones(depth, vector_splitter(read_to_string(&file)?), frame)?,
+        ),
+        CreateCmd::DecimalJd { file } => (
+            format!("DecimalJd_{depth}"),
+            from_decimal_jd(depth, vector_splitter(read_to_string(&file)?))?,
+        ),
+        CreateCmd::DecimalJdRange { file } => (
+            format!("DecimalJdRange_{depth}"),
+            from_decimal_jd_range(depth, vector_splitter(read_to_string(&file)?))?,
+        ),
+        CreateCmd::FrequencyRange { file } => (
+            format!("FrequencyRange_{depth}"),
+            from_frequency_range(depth, vector_splitter(read_to_string(&file)?))?,
+        ),
+        CreateCmd::WavelengthRange { file } => (
+            format!("WavelengthRange_{depth}"),
+            from_wavelength_range(depth, vector_splitter(read_to_string(&file)?))?,
+        ),
+        CreateCmd::ValuedCells {
+            file,
+            from_threshold,
+            to_threshold,
+            density,
+            asc,
+            not_strict,
+            split,
+            revese_recursive_descent,
+        } => (
+            format!("ValuedC_{depth}"),
+            from_valued_cells(
+                depth,
+                density,
+                from_threshold,
+                to_threshold,
+                asc,
+                not_strict,
+                split,
+                revese_recursive_descent,
+                uniq_value_splitter(read_to_string(&file)?),
+            )?,
+        ),
+    };
+    registry.insert(&name, id, None, BTreeMap::new())?;
+    Ok(id)
+}
+
+// #Definition
+//      load_files reads a MOC file and adds it to the store, like the "Open" menu.
+//      Compressed files are decompressed, every MOC of a zip archive is loaded.
+// #Args
+//  *   `path`: the path of the file (or an http(s) URL), its extension gives the format when
+//              the quantity is given
+//  *   `qty`: the quantity of the MOC, unused with fits, detected with the format if None
+//  *   `registry`: the MOC registry the loaded MOCs are added to
+// #Errors
+//      Error if the file can't be read (or downloaded) or if a MOC can't be parsed.
+fn load_files(
+    path: &Path,
+    qty: Option<MocQType>,
+    registry: &mut Registry,
+) -> Result<Vec<usize>, Error> {
+    let (file_name, file_content) = match path.to_str() {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => download(url)?,
+        _ => {
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| Error::Parse("error while reading file name".to_string()))?;
+            (file_name.to_string(), fs::read(path)?)
+        }
+    };
+
+    let mut ids = Vec::default();
+    for (name, content) in unpack(&file_name, file_content)? {
+        let id = match qty {
+            None => detect_reading(&content).map_err(|e| match e {
+                Error::Ambiguous(msg) => {
+                    Error::Ambiguous(format!("{msg}, use --qty to give its type"))
+                }
+                e => e,
+            })?,
+            Some(qty) => {
+                let reading = match extension(Path::new(&name)).as_str() {
+                    "fits" => "fits",
+                    "json" => "json",
+                    "ascii" | "txt" => "ascii",
+                    ext => {
+                        return Err(Error::Parse(format!("Unsupported file extension: '{ext}'")))
+                    }
+                };
+                type_reading(reading, &qty, &content)?
+            }
+        };
+        registry.load(
+            &path.display().to_string(),
+            &name,
+            id,
+            fits_metadata(&content),
+        )?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+// #Definition
+//      load_file loads a file holding a single MOC, see load_files.
+// #Errors
+//      Error if the file can't be loaded or if it does not hold exactly one MOC.
+fn load_file(path: &Path, qty: Option<MocQType>, registry: &mut Registry) -> Result<usize, Error> {
+    match load_files(path, qty, registry)?.as_slice() {
+        [id] => Ok(*id),
+        ids => Err(Error::Parse(format!(
+            "'{}' holds {} MOCs, one is expected",
+            path.display(),
+            ids.len()
+        ))),
+    }
+}
+
+// #Definition
+//      write_mocs writes the given MOCs in the output file.
+//      If there are several MOCs (after a split), their index is appended to the file stem.
+// #Args
+//  *   `ids`: the ids of the MOCs to write
+//  *   `output`: the output file and format
+//  *   `registry`: the MOC registry, giving the provenance written in FITS
+fn write_mocs(ids: &[usize], output: &Output, registry: &Registry) -> Result<(), Error> {
+    let format = match &output.format {
+        Some(f) => f.to_lowercase(),
+        None => extension(&output.output),
+    };
+    for (i, id) in ids.iter().enumerate() {
+        let data = match format.as_str() {
+            "fits" | "" => provenance::to_fits_buff(*id, &registry.provenance(*id))?,
+            "json" => U64MocStore
+                .to_json_str(*id, None)?
+                .into_bytes()
+                .into_boxed_slice(),
+            "ascii" | "txt" => U64MocStore
+                .to_ascii_str(*id, None)?
+                .into_bytes()
+                .into_boxed_slice(),
+            f => return Err(Error::Parse(format!("Unsupported output format: '{f}'"))),
+        };
+        let path = if ids.len() > 1 {
+            let stem = output
+                .output
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("moc");
+            let ext = output
+                .output
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| format!(".{e}"))
+                .unwrap_or_default();
+            output.output.with_file_name(format!("{stem}_{i}{ext}"))
+        } else {
+            output.output.clone()
+        };
+        fs::write(&path, data)?;
+    }
+    Ok(())
+}
+
+// #Definition
+//      print_info prints the information shown in the info window of a MOC.
+fn print_info(id: usize, registry: &Registry) -> Result<(), Error> {
+    let qty = U64MocStore.get_qty_type(id)?;
+    println!("{}", registry.name(id)?);
+    println!("  MOC type: {}", fmt_qty(qty));
+    match qty {
+        MocQType::Space => {
+            println!("  Depth: {}", U64MocStore.get_smoc_depth(id)?);
+            println!("  Coverage: {}%", U64MocStore.get_coverage_percentage(id)?);
+        }
+        MocQType::Time => {
+            println!("  Depth: {}", U64MocStore.get_tmoc_depth(id)?);
+            if let (Some(min), Some(max)) = (
+                U64MocStore.get_1st_axis_min(id)?,
+                U64MocStore.get_1st_axis_max(id)?,
+            ) {
+                println!(
+                    "  JD range: [{}, {}]",
+                    min as f64 / JD_TO_USEC,
+                    max as f64 / JD_TO_USEC
+                );
+            }
+        }
+        MocQType::TimeSpace => {
+            let (t, s) = U64MocStore.get_stmoc_depths(id)?;
+            println!("  Depth T: {t}\n  Depth S: {s}");
+        }
+        MocQType::Frequency => {
+            println!("  Depth: {}", U64MocStore.get_fmoc_depth(id)?);
+            if let Some((min, max)) = freq_range_hz(id)? {
+                println!("  Frequency range: [{min:e}, {max:e}] Hz");
+            }
+        }
+    }
+    println!("  Number of ranges: {}", U64MocStore.get_n_ranges(id)?);
+    if let Some(entry) = registry.get(id) {
+        if let Some(source) = &entry.source {
+            println!("  Source: {source}");
+        }
+        for (key, value) in &entry.metadata {
+            println!("  {key}: {value}");
+        }
+    }
+    Ok(())
+}
+
+// #Definition
+//      find_column gives the column of a role in a csv catalogue: the given column, by name or
+//      number (from 1), or the column guessed from the header names.
+// #Args
+//  *   `headers`: the header names, empty if the catalogue has no header row
+//  *   `column`: the column given on the command line, if any
+//  *   `role`: the role of the column, giving its usual names
+// #Errors
+//      Error if the given column doesn't exist or if no column can be guessed.
+fn find_column(headers: &[String], column: Option<&str>, role: &Role) -> Result<usize, Error> {
+    let not_found = |what: &str| Error::NotFound(format!("{} column {what}", role.label));
+    match column {
+        Some(column) => match column.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n - 1),
+            _ => headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(column))
+                .ok_or_else(|| not_found(&format!("'{column}'"))),
+        },
+        None => guess_column(headers, role)
+            .ok_or_else(|| not_found("(give it with --ra, --dec or --jd)")),
+    }
+}
+
+fn parse_qty(qty: &str) -> Result<MocQType, Error> {
+    match qty.to_lowercase().as_str() {
+        "space" | "s" => Ok(MocQType::Space),
+        "time" | "t" => Ok(MocQType::Time),
+        "spacetime" | "timespace" | "st" => Ok(MocQType::TimeSpace),
+        "frequency" | "f" => Ok(MocQType::Frequency),
+        q => Err(Error::Parse(format!("Unknown MOC quantity: '{q}'"))),
+    }
+}
+
+fn parse_frame(frame: &str) -> Result<Frame, Error> {
+    Frame::ALL
+        .into_iter()
+        .find(|f| f.to_string().eq_ignore_ascii_case(frame))
+        .ok_or_else(|| Error::Parse(format!("Unknown frame: '{frame}'")))
+}
+
+fn parse_op1(op: &str, depth: Option<u8>) -> Result<Op1, Error> {
+    match op.to_lowercase().as_str() {
+        "complement" => Ok(Op1::Complement),
+        "degrade" => depth
+            .map(|new_depth| Op1::Degrade { new_depth })
+            .ok_or_else(|| Error::Parse(String::from("Degrade requires a new depth (--depth)"))),
+        "extend" => Ok(Op1::Extend),
+        "contract" => Ok(Op1::Contract),
+        "extborder" => Ok(Op1::ExtBorder),
+        "intborder" => Ok(Op1::IntBorder),
+        "split" => Ok(Op1::Split),
+        "splitindirect" => Ok(Op1::SplitIndirect),
+        o => Err(Error::Parse(format!("Unknown unitary operation: '{o}'"))),
+    }
+}
+
+fn parse_op2(op: &str) -> Result<Op2, Error> {
+    match op.to_lowercase().as_str() {
+        "intersection" => Ok(Op2::Intersection),
+        "union" => Ok(Op2::Union),
+        "difference" => Ok(Op2::Difference),
+        "minus" => Ok(Op2::Minus),
+        "tfold" => Ok(Op2::TFold),
+        "sfold" => Ok(Op2::SFold),
+        o => Err(Error::Parse(format!("Unknown operation: '{o}'"))),
+    }
+}
+
+fn parse_opn(op: &str) -> Result<OpN, Error> {
+    match op.to_lowercase().as_str() {
+        "union" => Ok(OpN::Union),
+        "intersection" => Ok(OpN::Intersection),
+        "minus" => Ok(OpN::Minus),
+        o => Err(Error::Parse(format!("Unknown operation: '{o}'"))),
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String, Error> {
+    Ok(fs::read_to_string(path)?)
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(path: &Path) -> Output {
+        Output {
+            output: path.to_path_buf(),
+            format: None,
+        }
+    }
+
+    #[test]
+    fn op2_keeps_the_order_of_two_st_mocs() {
+        let dir = std::env::temp_dir().join(format!("moc_gui_cli_op2_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b, out) = (dir.join("a.txt"), dir.join("b.txt"), dir.join("out.txt"));
+        fs::write(&a, "t3/0-1 s3/0-10").unwrap();
+        fs::write(&b, "t3/0 s3/0-5").unwrap();
+        run(Command::Op2 {
+            operation: "minus".to_string(),
+            left: a,
+            right: b,
+            qty: Some("spacetime".to_string()),
+            output: output(&out),
+        })
+        .unwrap();
+        let result = U64MocStore
+            .load_stmoc_from_ascii(&fs::read_to_string(&out).unwrap())
+            .unwrap();
+        let expected = U64MocStore
+            .load_stmoc_from_ascii("t3/0 s3/6-10 t3/1 s3/0-10")
+            .unwrap();
+        // b minus a would be empty.
+        assert!(U64MocStore.eq(result, expected).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+use crate::controllers::creation::JD_TO_USEC;
+use crate::controllers::op2::minus;
+use crate::controllers::stats::{area_sr, SQ_DEG_PER_SR};
+use crate::error::Error;
+use crate::utils::commons::fmt_qty;
+
+// The Comparison struct, how much two MOCs of the same type overlap. The measures are areas
+// in square degrees for S-MOCs and durations in days for T-MOCs.
+#[derive(Clone, Copy)]
+pub(crate) struct Comparison {
+    pub(crate) qty: MocQType,
+    pub(crate) left: f64,
+    pub(crate) right: f64,
+    pub(crate) intersection: f64,
+    pub(crate) equal: bool,
+    pub(crate) left_contains_right: bool,
+    pub(crate) right_contains_left: bool,
+}
+
+impl Comparison {
+    pub(crate) fn unit(&self) -> &'static str {
+        match self.qty {
+            MocQType::Time => "days",
+            _ => "deg²",
+        }
+    }
+
+    pub(crate) fn union(&self) -> f64 {
+        self.left + self.right - self.intersection
+    }
+
+    // The fraction of the left MOC covered by the right one, None if the left MOC is empty.
+    pub(crate) fn left_covered(&self) -> Option<f64> {
+        ratio(self.intersection, self.left)
+    }
+
+    // The fraction of the right MOC covered by the left one, None if the right MOC is empty.
+    pub(crate) fn right_covered(&self) -> Option<f64> {
+        ratio(self.intersection, self.right)
+    }
+
+    // The Jaccard index, the intersection over the union, None if both MOCs are empty.
+    pub(crate) fn jaccard(&self) -> Option<f64> {
+        ratio(self.intersection, self.union())
+    }
+}
+
+fn ratio(part: f64, whole: f64) -> Option<f64> {
+    (whole > 0.0).then(|| part / whole)
+}
+
+// #Definition
+//      compare measures the overlap of two S-MOCs or two T-MOCs from their intersection, and
+//      checks their equality and containment from their differences.
+// #Args
+//  *   `left`: the id of the first MOC in the store
+//  *   `right`: the id of the second MOC in the store
+// #Errors
+//      Error if a MOC is not in the store, or if the MOCs are not two S-MOCs or two T-MOCs.
+pub(crate) fn compare(left: usize, right: usize) -> Result<Comparison, Error> {
+    let qty = U64MocStore.get_qty_type(left)?;
+    let right_qty = U64MocStore.get_qty_type(right)?;
+    match (qty, right_qty) {
+        (MocQType::Space, MocQType::Space) | (MocQType::Time, MocQType::Time) => {}
+        _ => {
+            return Err(Error::UnsupportedQty(format!(
+                "Only two S-MOCs or two T-MOCs can be compared, not a {} and a {}",
+                fmt_qty(qty),
+                fmt_qty(right_qty)
+            )))
+        }
+    }
+    let intersection = with_temp(Ok(U64MocStore.intersection(left, right)?), |id| {
+        measure(id, qty)
+    })?;
+    let left_only = with_temp(minus(left, right), |id| Ok(U64MocStore.is_empty(id)?))?;
+    let right_only = with_temp(minus(right, left), |id| Ok(U64MocStore.is_empty(id)?))?;
+    Ok(Comparison {
+        qty,
+        left: measure(left, qty)?,
+        right: measure(right, qty)?,
+        intersection,
+        equal: left_only && right_only,
+        left_contains_right: right_only,
+        right_contains_left: left_only,
+    })
+}
+
+// #Definition
+//      measure gives the area of an S-MOC in square degrees or the duration of a T-MOC in days.
+fn measure(id: usize, qty: MocQType) -> Result<f64, Error> {
+    match qty {
+        MocQType::Time => Ok(U64MocStore.get_ranges_sum(id)? as f64 / JD_TO_USEC),
+        _ => Ok(area_sr(id)? * SQ_DEG_PER_SR),
+    }
+}
+
+// #Definition
+//      with_temp applies a function to a MOC created for it, the MOC is then dropped from the
+//      store whatever the outcome.
+// #Args
+//  *   `id`: the id of the created MOC, or why it could not be created
+//  *   `f`: the function
+fn with_temp<T>(
+    id: Result<usize, Error>,
+    f: impl FnOnce(usize) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let id = id?;
+    let result = f(id);
+    U64MocStore.drop(id)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disjoint_mocs_are_not_equal() {
+        let a = U64MocStore.load_smoc_from_ascii("3/0-20").unwrap();
+        let b = U64MocStore.load_smoc_from_ascii("3/100").unwrap();
+        let comparison = compare(a, b).unwrap();
+        assert_eq!(comparison.intersection, 0.0);
+        assert!(!comparison.equal);
+        assert!(!comparison.left_contains_right);
+        assert!(!comparison.right_contains_left);
+        assert_eq!(comparison.jaccard(), Some(0.0));
+        let c = U64MocStore.load_smoc_from_ascii("3/5-10").unwrap();
+        let comparison = compare(a, c).unwrap();
+        assert!(comparison.left_contains_right && !comparison.right_contains_left);
+        for id in [a, b, c] {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+}
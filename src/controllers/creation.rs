@@ -1,10 +1,23 @@
 use core::fmt;
+use std::ops::Range;
 
+use moc::moc::range::RangeMOC;
+use moc::qty::{Hpx, MocQty};
 use moc::storage::u64idx::U64MocStore;
 
+use crate::controllers::frame::Frame;
+use crate::error::Error;
 use crate::utils::commons::*;
 
-const JD_TO_USEC: f64 = (24_u64 * 60 * 60 * 1_000_000) as f64;
+pub(crate) const JD_TO_USEC: f64 = (24_u64 * 60 * 60 * 1_000_000) as f64;
+/// Speed of light in vacuum, in m/s.
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+/// Frequencies (in Hz) that can be stored in an F-MOC are in `[FREQ_MIN_HZ, FREQ_MAX_HZ[`.
+const FREQ_MIN_HZ: f64 = 5.048_709_793_414_476e-29;
+const FREQ_MAX_HZ: f64 = 5.846_006_549_323_611e48;
+/// Maximum length (in degrees) of the edges of the polygons approximating a zone of a frame
+/// other than ICRS, whose parallels are small circles.
+const ZONE_STEP_DEG: f64 = 0.25;
 
 #[derive(Copy, Clone, PartialOrd, Eq)]
 pub(crate) enum CreationType {
@@ -14,11 +27,13 @@ pub(crate) enum CreationType {
     DecimalJd,
     DecimalJdRange,
     EllipticalCone,
+    FrequencyRange,
     LargeCone,
     Polygon,
     Ring,
     SmallCone,
     ValuedCells,
+    WavelengthRange,
     Zone,
 }
 impl fmt::Display for CreationType {
@@ -36,6 +51,8 @@ impl fmt::Display for CreationType {
             Self::DecimalJd => write!(f, "DecimalJd"),
             Self::DecimalJdRange => write!(f, "DecimalJdRange"),
             Self::ValuedCells => write!(f, "ValuedCells"),
+            Self::FrequencyRange => write!(f, "FrequencyRange"),
+            Self::WavelengthRange => write!(f, "WavelengthRange"),
         }
     }
 }
@@ -55,6 +72,8 @@ impl PartialEq for CreationType {
                 | (Self::DecimalJd, Self::DecimalJd)
                 | (Self::DecimalJdRange, Self::DecimalJdRange)
                 | (Self::ValuedCells, Self::ValuedCells)
+                | (Self::FrequencyRange, Self::FrequencyRange)
+                | (Self::WavelengthRange, Self::WavelengthRange)
         )
     }
 }
@@ -64,17 +83,225 @@ impl Default for CreationType {
     }
 }
 
+/// Create a new MOC from the given cone.
+/// # Params
+/// * `frame`: the frame of the center, converted to ICRS
+/// * `lon_deg`, `lat_deg`: the center, in degrees
+/// * `radius_deg`: the radius, in degrees
+/// * `depth`: MOC maximum depth in `[0, 29]`
+pub fn from_cone(
+    frame: Frame,
+    lon_deg: f64,
+    lat_deg: f64,
+    radius_deg: f64,
+    depth: u8,
+) -> Result<usize, Error> {
+    let (lon_deg, lat_deg) = frame.to_icrs(lon_deg, lat_deg);
+    Ok(U64MocStore.from_cone(lon_deg, lat_deg, radius_deg, depth, 2)?)
+}
+
+/// Create a new MOC from the given ring.
+/// # Params
+/// * `frame`: the frame of the center, converted to ICRS
+/// * `lon_deg`, `lat_deg`: the center, in degrees
+/// * `internal_deg`, `external_deg`: the internal and external radii, in degrees
+/// * `depth`: MOC maximum depth in `[0, 29]`
+pub fn from_ring(
+    frame: Frame,
+    lon_deg: f64,
+    lat_deg: f64,
+    internal_deg: f64,
+    external_deg: f64,
+    depth: u8,
+) -> Result<usize, Error> {
+    let (lon_deg, lat_deg) = frame.to_icrs(lon_deg, lat_deg);
+    Ok(U64MocStore.from_ring(lon_deg, lat_deg, internal_deg, external_deg, depth, 2)?)
+}
+
+/// Create a new MOC from the given elliptical cone.
+/// # Params
+/// * `frame`: the frame of the center and of the position angle, converted to ICRS
+/// * `lon_deg`, `lat_deg`: the center, in degrees
+/// * `a_deg`, `b_deg`: the semi-major and semi-minor axes, in degrees
+/// * `pa_deg`: the position angle of the semi-major axis (east-of-north), in degrees
+/// * `depth`: MOC maximum depth in `[0, 29]`
+pub fn from_elliptical_cone(
+    frame: Frame,
+    lon_deg: f64,
+    lat_deg: f64,
+    a_deg: f64,
+    b_deg: f64,
+    pa_deg: f64,
+    depth: u8,
+) -> Result<usize, Error> {
+    // An ellipse is unchanged by a rotation of half a turn.
+    let pa = frame
+        .pa_to_icrs(lon_deg, lat_deg, pa_deg)
+        .rem_euclid(180.0)
+        .to_radians();
+    let (lon_deg, lat_deg) = frame.to_icrs(lon_deg, lat_deg);
+    let (lon, lat) = (lon_deg2rad(lon_deg)?, lat_deg2rad(lat_deg)?);
+    let (a, b) = (a_deg.to_radians(), b_deg.to_radians());
+    if depth > Hpx::<u64>::MAX_DEPTH {
+        Err(Error::Parse(format!(
+            "Depth must be in [0, {}]",
+            Hpx::<u64>::MAX_DEPTH
+        )))
+    } else if a <= 0.0 || HALF_PI <= a {
+        Err(Error::Parse(String::from(
+            "Semi-major axis must be in ]0, pi/2[",
+        )))
+    } else if b <= 0.0 || a <= b {
+        Err(Error::Parse(String::from(
+            "Semi-minor axis must be in ]0, a[",
+        )))
+    } else {
+        // The store rejects position angles in [pi/2, pi[ (which are valid), the MOC is built
+        // and inserted directly.
+        let dd = 2.min(Hpx::<u64>::MAX_DEPTH - depth);
+        let moc = RangeMOC::from_elliptical_cone(lon, lat, a, b, pa, depth, dd);
+        Ok(U64MocStore.insert_smoc(moc)?)
+    }
+}
+
+/// Create a new MOC from the given box.
+/// # Params
+/// * `frame`: the frame of the center and of the position angle, converted to ICRS
+/// * `lon_deg`, `lat_deg`: the center, in degrees
+/// * `a_deg`, `b_deg`: the semi-major and semi-minor axes, in degrees
+/// * `pa_deg`: the position angle of the semi-major axis (east-of-north), in degrees
+/// * `depth`: MOC maximum depth in `[0, 29]`
+pub fn from_box(
+    frame: Frame,
+    lon_deg: f64,
+    lat_deg: f64,
+    a_deg: f64,
+    b_deg: f64,
+    pa_deg: f64,
+    depth: u8,
+) -> Result<usize, Error> {
+    // A box is unchanged by a rotation of half a turn.
+    let pa_deg = frame.pa_to_icrs(lon_deg, lat_deg, pa_deg).rem_euclid(180.0);
+    let (lon_deg, lat_deg) = frame.to_icrs(lon_deg, lat_deg);
+    Ok(U64MocStore.from_box(lon_deg, lat_deg, a_deg, b_deg, pa_deg, depth)?)
+}
+
+/// Create a new MOC from the given zone, i.e. longitude and latitude ranges.
+/// In a frame other than ICRS, the zone is not an ICRS zone: it is made of polygons at most
+/// 90° wide, whose parallels are approximated by edges of at most `ZONE_STEP_DEG`.
+/// Whatever the frame, the zone crosses the primary meridian if `lon_deg_max < lon_deg_min`.
+/// # Params
+/// * `frame`: the frame of the zone
+/// * `lon_deg_min`, `lat_deg_min`: the minimal longitude and latitude, in degrees
+/// * `lon_deg_max`, `lat_deg_max`: the maximal longitude and latitude, in degrees
+/// * `depth`: MOC maximum depth in `[0, 29]`
+pub fn from_zone(
+    frame: Frame,
+    lon_deg_min: f64,
+    lat_deg_min: f64,
+    lon_deg_max: f64,
+    lat_deg_max: f64,
+    depth: u8,
+) -> Result<usize, Error> {
+    if frame == Frame::Icrs {
+        return Ok(U64MocStore.from_zone(
+            lon_deg_min,
+            lat_deg_min,
+            lon_deg_max,
+            lat_deg_max,
+            depth,
+        )?);
+    }
+    // As in ICRS, a maximal longitude smaller than the minimal one is a zone crossing the
+    // primary meridian.
+    let lon_deg_max = if lon_deg_max < lon_deg_min {
+        lon_deg_max + 360.0
+    } else {
+        lon_deg_max
+    };
+    if lon_deg_max == lon_deg_min || lat_deg_max <= lat_deg_min {
+        return Err(Error::Parse(String::from(
+            "The zone longitudes must differ and its maximal latitude exceed the minimal one",
+        )));
+    }
+    let n_parts = ((lon_deg_max - lon_deg_min) / 90.0).ceil() as usize;
+    let width = (lon_deg_max - lon_deg_min) / n_parts as f64;
+    let mut ids = Vec::with_capacity(n_parts);
+    for i in 0..n_parts {
+        let lon_min = lon_deg_min + i as f64 * width;
+        let vertices = zone_contour(lon_min, lat_deg_min, lon_min + width, lat_deg_max);
+        match U64MocStore.from_polygon(
+            vertices
+                .into_iter()
+                .map(|(lon, lat)| frame.to_icrs(lon, lat)),
+            false,
+            depth,
+        ) {
+            Ok(id) => ids.push(id),
+            Err(e) => {
+                drop_all(&ids)?;
+                return Err(e.into());
+            }
+        }
+    }
+    let id = U64MocStore.multi_union(&ids);
+    drop_all(&ids)?;
+    Ok(id?)
+}
+
+// #Definition
+//      zone_contour gives the vertices of the contour of a zone, in degrees, counterclockwise:
+//      its southern parallel, its eastern meridian, its northern parallel and its western
+//      meridian, each edge being split in segments of at most ZONE_STEP_DEG. A pole is a
+//      single vertex.
+fn zone_contour(lon_min: f64, lat_min: f64, lon_max: f64, lat_max: f64) -> Vec<(f64, f64)> {
+    let edge = |from: (f64, f64), to: (f64, f64)| {
+        let len = (to.0 - from.0).abs().max((to.1 - from.1).abs());
+        let n = ((len / ZONE_STEP_DEG).ceil() as usize).max(1);
+        (0..n).map(move |i| {
+            let t = i as f64 / n as f64;
+            (from.0 + t * (to.0 - from.0), from.1 + t * (to.1 - from.1))
+        })
+    };
+    let mut vertices: Vec<(f64, f64)> = Vec::default();
+    for (from, to) in [
+        ((lon_min, lat_min), (lon_max, lat_min)),
+        ((lon_max, lat_min), (lon_max, lat_max)),
+        ((lon_max, lat_max), (lon_min, lat_max)),
+        ((lon_min, lat_max), (lon_min, lat_min)),
+    ] {
+        for (lon, lat) in edge(from, to) {
+            let at_pole = lat.abs() >= 90.0;
+            if !(at_pole && vertices.last().is_some_and(|v| v.1 == lat)) {
+                vertices.push((lon, lat));
+            }
+        }
+    }
+    vertices
+}
+
+fn drop_all(ids: &[usize]) -> Result<(), Error> {
+    for id in ids {
+        U64MocStore.drop(*id)?;
+    }
+    Ok(())
+}
+
 /// Create a new MOC from the given polygon vertices.
 /// # Params
 /// * `name`: the name to be given to the MOC
 /// * `depth`: MOC maximum depth in `[0, 29]`
 /// * `vertices_deg`: vertices coordinates in degrees `[lon_v1, lat_v1, lon_v2, lat_v2, ..., lon_vn, lat_vn]`
 /// * `complement`: reverse the default inside/outside of the polygon
-pub fn from_polygon(depth: u8, content: String, complement: bool) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
+/// * `frame`: the frame of the vertices, converted to ICRS
+pub fn from_polygon(
+    depth: u8,
+    v: Vec<f64>,
+    complement: bool,
+    frame: Frame,
+) -> Result<usize, Error> {
     // An other solution would be to go unsafe to transmute in Box<[[f64; 2]]> ...
-    U64MocStore.from_polygon(
+    Ok(U64MocStore.from_polygon(
         v.iter()
             .step_by(2)
             .zip(v.iter().skip(1).step_by(2))
@@ -82,25 +309,24 @@ pub fn from_polygon(depth: u8, content: String, complement: bool) -> Result<usiz
                 let lon = lon_deg2rad(*lon_deg).ok();
                 let lat = lat_deg2rad(*lat_deg).ok();
                 match (lon, lat) {
-                    (Some(lon), Some(lat)) => Some((lon, lat)),
+                    (Some(_), Some(_)) => Some(frame.to_icrs(*lon_deg, *lat_deg)),
                     _ => None,
                 }
             }),
         complement,
         depth,
-    )
+    )?)
 }
 
-/// Create a new MOC from the given list of coordinates (assumed to be equatorial)
+/// Create a new MOC from the given list of coordinates
 /// # Params
 /// * `name`: the name to be given to the MOC
 /// * `depth`: MOC maximum depth in `[0, 29]`
 /// * `coos_deg`: list of coordinates in degrees `[lon_1, lat_1, lon_2, lat_2, ..., lon_n, lat_n]`
-pub fn from_coo(depth: u8, content: String) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
+/// * `frame`: the frame of the coordinates, converted to ICRS
+pub fn from_coo(depth: u8, v: Vec<f64>, frame: Frame) -> Result<usize, Error> {
     // An other solution would be to go unsafe to transmute coos_deg in Box<[[f64; 2]]> ...
-    U64MocStore.from_coo(
+    Ok(U64MocStore.from_coo(
         depth,
         v.iter()
             .step_by(2)
@@ -109,11 +335,11 @@ pub fn from_coo(depth: u8, content: String) -> Result<usize, String> {
                 let lon = lon_deg2rad(*lon_deg).ok();
                 let lat = lat_deg2rad(*lat_deg).ok();
                 match (lon, lat) {
-                    (Some(lon), Some(lat)) => Some((lon, lat)),
+                    (Some(_), Some(_)) => Some(frame.to_icrs(*lon_deg, *lat_deg)),
                     _ => None,
                 }
             }),
-    )
+    )?)
 }
 
 /// Create a new MOC from the given list of cone centers and radii
@@ -123,10 +349,9 @@ pub fn from_coo(depth: u8, content: String) -> Result<usize, String> {
 /// * `depth`: MOC maximum depth in `[0, 29]`
 /// * `coos_and_radius_deg`: list of coordinates adn radii in degrees
 ///   `[lon_1, lat_1, rad_1, lon_2, lat_2, rad_2, ..., lon_n, lat_n, rad_n]`
-pub fn from_small_cones(depth: u8, content: String) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
-    U64MocStore.from_small_cones(
+/// * `frame`: the frame of the cone centers, converted to ICRS
+pub fn from_small_cones(depth: u8, v: Vec<f64>, frame: Frame) -> Result<usize, Error> {
+    Ok(U64MocStore.from_small_cones(
         depth,
         2,
         v.iter()
@@ -137,11 +362,11 @@ pub fn from_small_cones(depth: u8, content: String) -> Result<usize, String> {
                 let lon = lon_deg2rad(*lon_deg).ok();
                 let lat = lat_deg2rad(*lat_deg).ok();
                 match (lon, lat) {
-                    (Some(lon), Some(lat)) => Some(((lon, lat), (*radius_deg).to_radians())),
+                    (Some(_), Some(_)) => Some((frame.to_icrs(*lon_deg, *lat_deg), *radius_deg)),
                     _ => None,
                 }
             }),
-    )
+    )?)
 }
 
 /// Create a new MOC from the given list of cone centers and radii
@@ -151,10 +376,9 @@ pub fn from_small_cones(depth: u8, content: String) -> Result<usize, String> {
 /// * `depth`: MOC maximum depth in `[0, 29]`
 /// * `coos_and_radius_deg`: list of coordinates adn radii in degrees
 ///   `[lon_1, lat_1, rad_1, lon_2, lat_2, rad_2, ..., lon_n, lat_n, rad_n]`
-pub fn from_large_cones(depth: u8, content: String) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
-    U64MocStore.from_large_cones(
+/// * `frame`: the frame of the cone centers, converted to ICRS
+pub fn from_large_cones(depth: u8, v: Vec<f64>, frame: Frame) -> Result<usize, Error> {
+    Ok(U64MocStore.from_large_cones(
         depth,
         2,
         v.iter()
@@ -165,11 +389,11 @@ pub fn from_large_cones(depth: u8, content: String) -> Result<usize, String> {
                 let lon = lon_deg2rad(*lon_deg).ok();
                 let lat = lat_deg2rad(*lat_deg).ok();
                 match (lon, lat) {
-                    (Some(lon), Some(lat)) => Some(((lon, lat), (*radius_deg).to_radians())),
+                    (Some(_), Some(_)) => Some((frame.to_icrs(*lon_deg, *lat_deg), *radius_deg)),
                     _ => None,
                 }
             }),
-    )
+    )?)
 }
 
 /// Create a new T-MOC from the given list of decimal Julian Days (JD) times.
@@ -185,22 +409,83 @@ pub fn from_large_cones(depth: u8, content: String) -> Result<usize, String> {
 /// The other approach is to use a couple of `f64`: one for the integer part of the JD, the
 /// other for the fractional part of the JD.
 /// We will add such a method later if required by users.
-pub fn from_decimal_jd(depth: u8, content: String) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
-    U64MocStore.from_decimal_jd_values(depth, v.iter().map(|jd| (jd * JD_TO_USEC)))
+pub fn from_decimal_jd(depth: u8, v: Vec<f64>) -> Result<usize, Error> {
+    Ok(U64MocStore.from_decimal_jd_values(depth, v.into_iter())?)
 }
 
-pub fn from_decimal_jd_range(depth: u8, content: String) -> Result<usize, String> {
-    let v = vector_splitter(content);
-
-    U64MocStore.from_decimal_jd_ranges(
+pub fn from_decimal_jd_range(depth: u8, v: Vec<f64>) -> Result<usize, Error> {
+    Ok(U64MocStore.from_decimal_jd_ranges(
         depth,
         v.iter()
             .step_by(2)
             .zip(v.iter().skip(1).step_by(2))
-            .map(|(jd_min, jd_max)| (jd_min * JD_TO_USEC)..(jd_max * JD_TO_USEC)),
-    )
+            .map(|(jd_min, jd_max)| *jd_min..*jd_max),
+    )?)
+}
+
+/// Create a new ST-MOC from the given list of decimal Julian Days (JD) times and positions.
+/// # Params
+/// * `time_depth`: T-MOC maximum depth in `[0, 61]`
+/// * `space_depth`: S-MOC maximum depth in `[0, 29]`
+/// * `v`: times and coordinates in degrees `[jd_1, lon_1, lat_1, ..., jd_n, lon_n, lat_n]`
+pub fn from_decimal_jd_coo(time_depth: u8, space_depth: u8, v: Vec<f64>) -> Result<usize, Error> {
+    let (mut times, mut lons, mut lats) = (Vec::default(), Vec::default(), Vec::default());
+    for jd_lon_lat in v.chunks_exact(3) {
+        if let (Ok(lon), Ok(lat)) = (lon_deg2rad(jd_lon_lat[1]), lat_deg2rad(jd_lon_lat[2])) {
+            times.push((jd_lon_lat[0] * JD_TO_USEC) as u64);
+            lons.push(lon);
+            lats.push(lat);
+        }
+    }
+    Ok(U64MocStore.create_from_times_positions(times, lons, lats, time_depth, space_depth)?)
+}
+
+/// Create a new F-MOC from the given list of frequency ranges.
+/// # Params
+/// * `depth`: F-MOC maximum depth in `[0, 59]`
+/// * `v`: frequency ranges in Hz `[f_min_1, f_max_1, f_min_2, f_max_2, ..., f_min_n, f_max_n]`
+pub fn from_frequency_range(depth: u8, v: Vec<f64>) -> Result<usize, Error> {
+    let ranges = v
+        .iter()
+        .step_by(2)
+        .zip(v.iter().skip(1).step_by(2))
+        .map(|(f_min, f_max)| freq_range(*f_min, *f_max))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(U64MocStore.from_hz_ranges(depth, ranges.into_iter())?)
+}
+
+/// Create a new F-MOC from the given list of wavelength ranges.
+/// # Params
+/// * `depth`: F-MOC maximum depth in `[0, 59]`
+/// * `v`: wavelength ranges in meters `[l_min_1, l_max_1, l_min_2, l_max_2, ..., l_min_n, l_max_n]`
+pub fn from_wavelength_range(depth: u8, v: Vec<f64>) -> Result<usize, Error> {
+    // The largest wavelength gives the lowest frequency.
+    let ranges = v
+        .iter()
+        .step_by(2)
+        .zip(v.iter().skip(1).step_by(2))
+        .map(|(l_min, l_max)| freq_range(SPEED_OF_LIGHT / *l_max, SPEED_OF_LIGHT / *l_min))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(U64MocStore.from_hz_ranges(depth, ranges.into_iter())?)
+}
+
+// #Definition
+//      freq_range checks that a frequency range (in Hz) can be stored in an F-MOC.
+// #Errors
+//      Error if a bound is out of the F-MOC frequency range or if the bounds are reversed.
+fn freq_range(f_min: f64, f_max: f64) -> Result<Range<f64>, Error> {
+    let valid = FREQ_MIN_HZ..FREQ_MAX_HZ;
+    if !valid.contains(&f_min) || !valid.contains(&f_max) {
+        Err(Error::Parse(format!(
+            "Frequencies must be in [{FREQ_MIN_HZ:e}, {FREQ_MAX_HZ:e}[ Hz, got [{f_min:e}, {f_max:e}]"
+        )))
+    } else if f_min > f_max {
+        Err(Error::Parse(format!(
+            "Range lower bound {f_min} is greater than its upper bound {f_max}"
+        )))
+    } else {
+        Ok(f_min..f_max)
+    }
 }
 
 /// Create a new S-MOC from the given lists of UNIQ and Values.
@@ -214,8 +499,7 @@ pub fn from_decimal_jd_range(depth: u8, content: String) -> Result<usize, String
 /// * `not_strict`: Cells overlapping with the upper or the lower cumulative bounds are not rejected (often = false).
 /// * `split`: Split recursively the cells overlapping the upper or the lower cumulative bounds (often = false).
 /// * `revese_recursive_descent`: Perform the recursive descent from the highest to the lowest sub-cell, only with option 'split' (set both flags to be compatibile with Aladin)
-/// * `uniqs`: array of uniq HEALPix cells
-/// * `values`: array of values associated to the HEALPix cells
+/// * `uniq_vals`: array of uniq HEALPix cells with the values associated to them
 pub fn from_valued_cells(
     depth: u8,
     density: bool,
@@ -225,20 +509,9 @@ pub fn from_valued_cells(
     not_strict: bool,
     split: bool,
     revese_recursive_descent: bool,
-    content: String,
-) -> Result<usize, String> {
-    let mut v: Vec<f64> = Vec::default();
-
-    let f: Vec<&str> = content.split(|c| c == ',' || c == '\n').collect();
-    // Split on line returns too
-    let mut tmp: Vec<f64> = f.iter().filter_map(|f| (*f).parse::<f64>().ok()).collect();
-    v.append(&mut tmp);
-
-    let uniq_vals = v
-        .iter()
-        .zip(v.iter().step_by(2))
-        .map(|(uniq, val)| (*uniq as u64, *val));
-    U64MocStore.from_valued_cells(
+    uniq_vals: Vec<(u64, f64)>,
+) -> Result<usize, Error> {
+    Ok(U64MocStore.from_valued_cells(
         depth,
         density,
         from_threshold,
@@ -247,29 +520,34 @@ pub fn from_valued_cells(
         not_strict,
         split,
         revese_recursive_descent,
-        uniq_vals,
-    )
+        uniq_vals.into_iter(),
+    )?)
 }
 
-pub(crate) fn lon_deg2rad(lon_deg: f64) -> Result<f64, String> {
+pub(crate) fn lon_deg2rad(lon_deg: f64) -> Result<f64, Error> {
     let lon = lon_deg.to_radians();
     if !(0.0..TWICE_PI).contains(&lon) {
-        Err("Longitude must be in [0, 2pi[".to_string())
+        Err(Error::Parse("Longitude must be in [0, 2pi[".to_string()))
     } else {
         Ok(lon)
     }
 }
 
-pub(crate) fn lat_deg2rad(lat_deg: f64) -> Result<f64, String> {
+pub(crate) fn lat_deg2rad(lat_deg: f64) -> Result<f64, Error> {
     let lat = lat_deg.to_radians();
     if !(-HALF_PI..HALF_PI).contains(&lat) {
-        Err("Latitude must be in [-pi/2, pi/2]".to_string())
+        Err(Error::Parse(
+            "Latitude must be in [-pi/2, pi/2]".to_string(),
+        ))
     } else {
         Ok(lat)
     }
 }
 
-fn vector_splitter(content: String) -> Vec<f64> {
+// #Definition
+//      vector_splitter gives the numbers of a csv content, whatever the number of columns
+//      (values are split on commas and line returns, anything else is skipped).
+pub(crate) fn vector_splitter(content: String) -> Vec<f64> {
     let mut v: Vec<f64> = Vec::default();
 
     let f: Vec<&str> = content.split(|c| c == ',' || c == '\n').collect();
@@ -279,3 +557,68 @@ fn vector_splitter(content: String) -> Vec<f64> {
 
     v
 }
+
+// #Definition
+//      uniq_value_splitter gives the (UNIQ, value) pairs of a csv content made of 2 columns,
+//      the UNIQs are parsed as integers so that they are not rounded.
+pub(crate) fn uniq_value_splitter(content: String) -> Vec<(u64, f64)> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once(','))
+        .filter_map(|(uniq, val)| Some((uniq.trim().parse().ok()?, val.trim().parse().ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::stats::{area_sr, SQ_DEG_PER_SR};
+
+    // The store takes degrees, a 1° cone covers about π deg².
+    #[test]
+    fn cone_area_is_in_square_degrees() {
+        let expected = std::f64::consts::PI;
+        for id in [
+            from_cone(Frame::Icrs, 10.0, 20.0, 1.0, 12).unwrap(),
+            from_small_cones(12, vec![10.0, 20.0, 1.0], Frame::Icrs).unwrap(),
+            from_large_cones(12, vec![10.0, 20.0, 1.0], Frame::Icrs).unwrap(),
+        ] {
+            let area = area_sr(id).unwrap() * SQ_DEG_PER_SR;
+            assert!((area - expected).abs() < 0.05 * expected, "{area} deg²");
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+
+    // A zone crossing the primary meridian covers the same area in any frame.
+    #[test]
+    fn zone_crosses_primary_meridian_in_any_frame() {
+        let area = |id: usize| {
+            let area = area_sr(id).unwrap() * SQ_DEG_PER_SR;
+            U64MocStore.drop(id).unwrap();
+            area
+        };
+        let icrs = area(from_zone(Frame::Icrs, 350.0, -10.0, 10.0, 10.0, 10).unwrap());
+        let galactic = area(from_zone(Frame::Galactic, 350.0, -10.0, 10.0, 10.0, 10).unwrap());
+        let shifted = area(from_zone(Frame::Galactic, -10.0, -10.0, 10.0, 10.0, 10).unwrap());
+        assert!(
+            (galactic - icrs).abs() < 0.01 * icrs,
+            "{galactic} {icrs} deg²"
+        );
+        assert!(
+            (galactic - shifted).abs() < 1e-9 * shifted,
+            "{galactic} {shifted} deg²"
+        );
+        assert!(from_zone(Frame::Galactic, 10.0, -10.0, 10.0, 10.0, 10).is_err());
+    }
+
+    // The store takes decimal JDs, the range is stored in microseconds since JD 0.
+    #[test]
+    fn tmoc_range_is_in_jd() {
+        let id = from_decimal_jd_range(61, vec![2_451_545.0, 2_451_546.5]).unwrap();
+        let min = U64MocStore.get_1st_axis_min(id).unwrap().unwrap() as f64 / JD_TO_USEC;
+        let max = U64MocStore.get_1st_axis_max(id).unwrap().unwrap() as f64 / JD_TO_USEC;
+        assert!((min - 2_451_545.0).abs() < 1e-6, "{min}");
+        assert!((max - 2_451_546.5).abs() < 1e-6, "{max}");
+        U64MocStore.drop(id).unwrap();
+    }
+}
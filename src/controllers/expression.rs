@@ -0,0 +1,568 @@
+use std::collections::BTreeMap;
+
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+use crate::controllers::{op1::Op1, op2::Op2};
+use crate::error::Error;
+use crate::utils::{
+    commons::{fmt_qty, same_qty},
+    registry::Registry,
+};
+
+// The syntax of the expressions, shown as help in the expression bar.
+pub(crate) const SYNTAX: &str = "MOC names combined with:
+  a | b   union
+  a & b   intersection
+  a - b   minus
+  a ^ b   difference (symmetric)
+  !a      complement
+  ( )     grouping
+Functions: complement(a), degrade(a, depth), extend(a), contract(a),
+  extborder(a), intborder(a), union(a, b), intersection(a, b),
+  difference(a, b), minus(a, b), sfold(s, st), tfold(t, st)
+'&' binds tighter than '|', '-' and '^', which are evaluated from left to right.
+Names with spaces or special characters are quoted: \"my moc (1)\".";
+
+// The characters ending a MOC name which is not quoted.
+const SPECIAL_CHARS: &str = "()!&|-^,\"'";
+
+// A token of an expression.
+#[derive(Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Quoted(String),
+    Open,
+    Close,
+    Comma,
+    Not,
+    And,
+    Or,
+    Minus,
+    Xor,
+}
+
+// The Expr enum, the syntax tree of an expression.
+enum Expr {
+    Moc(String),
+    Op1(Op1, Box<Expr>),
+    Op2(Op2, Box<Expr>, Box<Expr>),
+}
+
+// #Definition
+//      evaluate evaluates an expression over the names of the MOCs of the registry, e.g.
+//      "(a | b) & !c - degrade(d, 8)", and adds the resulting MOC to the store.
+//      The quantities of the MOCs are checked before any operation, the intermediate MOCs are
+//      dropped from the store. The expression is recorded in the history.
+// #Args
+//  *   `registry`: the registry giving the MOCs by name, the result is added to it
+//  *   `expression`: the expression
+//  *   `res_name`: the name given to the result, the expression itself if empty
+// #Errors
+//      Error if the expression is not valid, if a MOC name is unknown or if an operation is not
+//      available for the quantity of its MOCs.
+pub(crate) fn evaluate(
+    registry: &mut Registry,
+    expression: &str,
+    res_name: &str,
+) -> Result<usize, Error> {
+    let expr = parse(expression)?;
+    if let Expr::Moc(name) = &expr {
+        return Err(Error::Parse(format!(
+            "The expression only names '{name}', an operation is expected"
+        )));
+    }
+    let resolve = |name: &str| {
+        registry
+            .find(name)
+            .ok_or_else(|| Error::NotFound(format!("MOC '{name}'")))
+    };
+    expr.qty(&resolve)?;
+
+    let mut temporaries = Vec::default();
+    let result = expr.eval(&resolve, &mut temporaries);
+    for id in temporaries {
+        if result.as_ref().ok() != Some(&id) {
+            U64MocStore.drop(id)?;
+        }
+    }
+    let id = result?;
+
+    let expression = expression.trim();
+    let name = if res_name.is_empty() {
+        expression
+    } else {
+        res_name
+    };
+    let mut inputs = Vec::default();
+    expr.inputs(&resolve, &mut inputs)?;
+    registry.insert(name, id, None, BTreeMap::new())?;
+    registry.added("Expression", &inputs, expression, &[id])?;
+    Ok(id)
+}
+
+impl Expr {
+    // #Definition
+    //      qty checks the quantities of the MOCs of the expression and gives the quantity of
+    //      its result.
+    // #Args
+    //  *   `resolve`: gives the id of a MOC from its name
+    // #Errors
+    //      Error if a MOC name is unknown or if an operation is not available for the quantity
+    //      of its MOCs.
+    fn qty(&self, resolve: &dyn Fn(&str) -> Result<usize, Error>) -> Result<MocQType, Error> {
+        match self {
+            Expr::Moc(name) => Ok(U64MocStore.get_qty_type(resolve(name)?)?),
+            Expr::Op1(op, e) => {
+                let qty = e.qty(resolve)?;
+                let available = match op {
+                    Op1::Complement | Op1::Degrade { .. } => !matches!(qty, MocQType::TimeSpace),
+                    Op1::Extend | Op1::Contract => {
+                        matches!(qty, MocQType::Space | MocQType::Frequency)
+                    }
+                    _ => matches!(qty, MocQType::Space),
+                };
+                if available {
+                    Ok(qty)
+                } else {
+                    Err(Error::UnsupportedQty(format!(
+                        "{op} is not available for {} MOCs",
+                        fmt_qty(qty)
+                    )))
+                }
+            }
+            Expr::Op2(op, l, r) => {
+                let (left, right) = (l.qty(resolve)?, r.qty(resolve)?);
+                match (op, &left, &right) {
+                    (Op2::SFold, MocQType::Space, MocQType::TimeSpace) => Ok(MocQType::Time),
+                    (Op2::TFold, MocQType::Time, MocQType::TimeSpace) => Ok(MocQType::Space),
+                    (Op2::SFold | Op2::TFold, _, _) => Err(Error::UnsupportedQty(format!(
+                        "{op} is not available for a {} MOC with a {} MOC",
+                        fmt_qty(left),
+                        fmt_qty(right)
+                    ))),
+                    _ if same_qty(left, right) => Ok(left),
+                    _ => Err(Error::UnsupportedQty(format!(
+                        "{op} of a {} MOC with a {} MOC, both MOCs must be of the same type",
+                        fmt_qty(left),
+                        fmt_qty(right)
+                    ))),
+                }
+            }
+        }
+    }
+
+    // #Definition
+    //      eval performs the operations of the expression.
+    // #Args
+    //  *   `resolve`: gives the id of a MOC from its name
+    //  *   `temporaries`: where to put the ids of the MOCs created by the operations
+    // #Errors
+    //      Errors come from the store operations.
+    fn eval(
+        &self,
+        resolve: &dyn Fn(&str) -> Result<usize, Error>,
+        temporaries: &mut Vec<usize>,
+    ) -> Result<usize, Error> {
+        let id = match self {
+            Expr::Moc(name) => return resolve(name),
+            Expr::Op1(op, e) => {
+                let id = e.eval(resolve, temporaries)?;
+                op.apply(id)?
+                    .first()
+                    .copied()
+                    .ok_or_else(|| Error::NotFound(format!("Result of {op}")))?
+            }
+            Expr::Op2(op, l, r) => {
+                let left = l.eval(resolve, temporaries)?;
+                let right = r.eval(resolve, temporaries)?;
+                op.apply(left, right)?
+            }
+        };
+        temporaries.push(id);
+        Ok(id)
+    }
+
+    // #Definition
+    //      inputs gives the MOCs used by the expression, in order and without duplicates.
+    fn inputs(
+        &self,
+        resolve: &dyn Fn(&str) -> Result<usize, Error>,
+        ids: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        match self {
+            Expr::Moc(name) => {
+                let id = resolve(name)?;
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            Expr::Op1(_, e) => e.inputs(resolve, ids)?,
+            Expr::Op2(_, l, r) => {
+                l.inputs(resolve, ids)?;
+                r.inputs(resolve, ids)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// #Definition
+//      parse parses an expression into its syntax tree.
+// #Errors
+//      Error if the expression is not valid.
+fn parse(expression: &str) -> Result<Expr, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        pos: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Err(Error::Parse(String::from("The expression is empty")));
+    }
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(Error::Parse(format!(
+            "Unexpected {} in the expression",
+            describe(token)
+        ))),
+    }
+}
+
+// #Definition
+//      tokenize splits an expression into tokens.
+// #Errors
+//      Error if a quote is not closed.
+fn tokenize(expression: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::default();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '!' => Token::Not,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '-' => Token::Minus,
+            '^' => Token::Xor,
+            '"' | '\'' => {
+                let mut name = String::default();
+                loop {
+                    match chars.next() {
+                        Some(n) if n == c => break,
+                        Some(n) => name.push(n),
+                        None => {
+                            return Err(Error::Parse(format!("Missing closing {c} after {name}")))
+                        }
+                    }
+                }
+                Token::Quoted(name)
+            }
+            c => {
+                let mut name = String::from(c);
+                while let Some(n) =
+                    chars.next_if(|n| !n.is_whitespace() && !SPECIAL_CHARS.contains(*n))
+                {
+                    name.push(n);
+                }
+                Token::Name(name)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// A token as shown in the error messages.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Name(name) | Token::Quoted(name) => format!("'{name}'"),
+        Token::Open => String::from("'('"),
+        Token::Close => String::from("')'"),
+        Token::Comma => String::from("','"),
+        Token::Not => String::from("'!'"),
+        Token::And => String::from("'&'"),
+        Token::Or => String::from("'|'"),
+        Token::Minus => String::from("'-'"),
+        Token::Xor => String::from("'^'"),
+    }
+}
+
+// A recursive descent parser over the tokens of an expression.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Error> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(Error::Parse(format!(
+                "Expected {} but found {}",
+                describe(&expected),
+                describe(&token)
+            ))),
+            None => Err(Error::Parse(format!(
+                "Expected {} at the end of the expression",
+                describe(&expected)
+            ))),
+        }
+    }
+
+    // expr := and (('|' | '-' | '^') and)*
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut left = self.and()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Or) => Op2::Union,
+                Some(Token::Minus) => Op2::Minus,
+                Some(Token::Xor) => Op2::Difference,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Expr::Op2(op, Box::new(left), Box::new(self.and()?));
+        }
+    }
+
+    // and := unary ('&' unary)*
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut left = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::Op2(Op2::Intersection, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    // unary := '!' unary | primary
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Op1(Op1::Complement, Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    // primary := '(' expr ')' | function '(' args ')' | name
+    fn primary(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.expr()?;
+                self.expect(Token::Close)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Open) => {
+                self.pos += 1;
+                let expr = self.function(&name)?;
+                self.expect(Token::Close)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) | Some(Token::Quoted(name)) => Ok(Expr::Moc(name)),
+            Some(token) => Err(Error::Parse(format!(
+                "Expected a MOC name but found {}",
+                describe(&token)
+            ))),
+            None => Err(Error::Parse(String::from(
+                "Expected a MOC name at the end of the expression",
+            ))),
+        }
+    }
+
+    // The arguments of a function, after its opening parenthesis.
+    fn function(&mut self, name: &str) -> Result<Expr, Error> {
+        let op1 = match name.to_lowercase().as_str() {
+            "complement" => Some(Op1::Complement),
+            "extend" => Some(Op1::Extend),
+            "contract" => Some(Op1::Contract),
+            "extborder" => Some(Op1::ExtBorder),
+            "intborder" => Some(Op1::IntBorder),
+            _ => None,
+        };
+        if let Some(op) = op1 {
+            return Ok(Expr::Op1(op, Box::new(self.expr()?)));
+        }
+        let op2 = match name.to_lowercase().as_str() {
+            "union" => Op2::Union,
+            "intersection" => Op2::Intersection,
+            "difference" => Op2::Difference,
+            "minus" => Op2::Minus,
+            "sfold" => Op2::SFold,
+            "tfold" => Op2::TFold,
+            "degrade" => {
+                let expr = self.expr()?;
+                self.expect(Token::Comma)?;
+                let new_depth = match self.next() {
+                    Some(Token::Name(depth)) => depth.parse::<u8>().ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    Error::Parse(String::from("degrade expects a depth, e.g. degrade(a, 8)"))
+                })?;
+                return Ok(Expr::Op1(Op1::Degrade { new_depth }, Box::new(expr)));
+            }
+            _ => return Err(Error::Parse(format!("Unknown function '{name}'"))),
+        };
+        let left = self.expr()?;
+        self.expect(Token::Comma)?;
+        let right = self.expr()?;
+        Ok(Expr::Op2(op2, Box::new(left), Box::new(right)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The syntax tree of an expression as a formula, e.g. "Union(a, b)".
+    fn tree(expression: &str) -> String {
+        fn show(expr: &Expr) -> String {
+            match expr {
+                Expr::Moc(name) => name.clone(),
+                Expr::Op1(Op1::Degrade { new_depth }, e) => {
+                    format!("Degrade({}, {new_depth})", show(e))
+                }
+                Expr::Op1(op, e) => format!("{op}({})", show(e)),
+                Expr::Op2(op, l, r) => format!("{op}({}, {})", show(l), show(r)),
+            }
+        }
+        match parse(expression) {
+            Ok(expr) => show(&expr),
+            Err(e) => panic!("{expression}: {e}"),
+        }
+    }
+
+    fn parse_error(expression: &str) -> String {
+        match parse(expression) {
+            Err(Error::Parse(msg)) => msg,
+            Err(e) => panic!("{expression}: not a parse error: {e}"),
+            Ok(_) => panic!("{expression}: parsed"),
+        }
+    }
+
+    // A registry with the S-MOCs "a", "b" and "my moc (1)", the T-MOC "t" and the ST-MOC "st".
+    fn registry() -> Registry {
+        let mut registry = Registry::default();
+        let mocs = [
+            ("a", U64MocStore.load_smoc_from_ascii("3/0-10")),
+            ("b", U64MocStore.load_smoc_from_ascii("3/5-20")),
+            ("my moc (1)", U64MocStore.load_smoc_from_ascii("3/100")),
+            ("t", U64MocStore.load_tmoc_from_ascii("10/5-8")),
+            ("st", U64MocStore.load_stmoc_from_ascii("t10/5 s3/0-7")),
+        ];
+        for (name, id) in mocs {
+            registry.add("Test", "", name, id.unwrap()).unwrap();
+        }
+        registry
+    }
+
+    fn drop_all(registry: &Registry) {
+        for id in registry.ids() {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(tree("a | b & c"), "Union(a, Intersection(b, c))");
+        assert_eq!(tree("a & b | c"), "Union(Intersection(a, b), c)");
+        // '|', '-' and '^' are evaluated from left to right.
+        assert_eq!(tree("a - b | c"), "Union(Minus(a, b), c)");
+        assert_eq!(
+            tree("a | b - c ^ d"),
+            "Difference(Minus(Union(a, b), c), d)"
+        );
+        assert_eq!(tree("!a & b"), "Intersection(Complement(a), b)");
+        assert_eq!(tree("!!a"), "Complement(Complement(a))");
+    }
+
+    #[test]
+    fn parentheses_and_functions() {
+        assert_eq!(tree("a - (b | c)"), "Minus(a, Union(b, c))");
+        assert_eq!(tree("(a | b) & c"), "Intersection(Union(a, b), c)");
+        assert_eq!(tree("!(a & b)"), "Complement(Intersection(a, b))");
+        assert_eq!(
+            tree("degrade(a | b, 8) & EXTEND(c)"),
+            "Intersection(Degrade(Union(a, b), 8), Extend(c))"
+        );
+        assert_eq!(tree("sfold(s, st)"), "SFold(s, st)");
+        assert_eq!(tree("\"my moc (1)\" | 'b-c'"), "Union(my moc (1), b-c)");
+    }
+
+    #[test]
+    fn parser_errors() {
+        assert_eq!(parse_error("  "), "The expression is empty");
+        assert!(parse_error("a |").contains("end of the expression"));
+        assert!(parse_error("(a | b").contains("Expected ')'"));
+        assert!(parse_error("a b").contains("Unexpected 'b'"));
+        assert!(parse_error("a | )").contains("found ')'"));
+        assert!(parse_error("\"a | b").contains("Missing closing"));
+        assert!(parse_error("union(a)").contains("Expected ','"));
+        assert!(parse_error("foo(a)").contains("Unknown function 'foo'"));
+        assert!(parse_error("degrade(a, x)").contains("degrade expects a depth"));
+    }
+
+    #[test]
+    fn unknown_names() {
+        let mut registry = registry();
+        let result = evaluate(&mut registry, "a | c", "");
+        assert!(matches!(result, Err(Error::NotFound(ref what)) if what == "MOC 'c'"));
+        assert!(matches!(
+            evaluate(&mut registry, "a", ""),
+            Err(Error::Parse(_))
+        ));
+        assert_eq!(registry.len(), 5);
+        drop_all(&registry);
+    }
+
+    #[test]
+    fn mixed_types() {
+        let mut registry = registry();
+        for expression in ["a | t", "a & st", "!st", "extend(t)", "tfold(a, st)"] {
+            assert!(
+                matches!(
+                    evaluate(&mut registry, expression, ""),
+                    Err(Error::UnsupportedQty(_))
+                ),
+                "{expression}"
+            );
+        }
+        assert_eq!(registry.len(), 5);
+        let id = evaluate(&mut registry, "sfold(a, st)", "").unwrap();
+        assert!(matches!(U64MocStore.get_qty_type(id), Ok(MocQType::Time)));
+        drop_all(&registry);
+    }
+
+    #[test]
+    fn evaluation() {
+        let mut registry = registry();
+        let ascii = |id: usize| U64MocStore.to_ascii_str(id, None).unwrap();
+        for (expression, expected) in [
+            ("(a | b) - \"my moc (1)\" - b", "3/0-4"),
+            ("a ^ b", "3/0-4 11-20"),
+            ("!(a | b) & \"my moc (1)\"", "3/100"),
+        ] {
+            let id = evaluate(&mut registry, expression, "res").unwrap();
+            let expected = U64MocStore.load_smoc_from_ascii(expected).unwrap();
+            assert_eq!(ascii(id), ascii(expected), "{expression}");
+            U64MocStore.drop(expected).unwrap();
+        }
+        assert_eq!(registry.name(registry.ids()[5]).unwrap(), "res");
+        assert_eq!(registry.name(registry.ids()[6]).unwrap(), "res(1)");
+        // Without a name, the result is named after the expression.
+        let id = evaluate(&mut registry, " a & b ", "").unwrap();
+        assert_eq!(registry.name(id).unwrap(), "a & b");
+        assert_eq!(registry.provenance(id).expression(), "(a & b)");
+        drop_all(&registry);
+    }
+}
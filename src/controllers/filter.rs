@@ -0,0 +1,250 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use csv::{ByteRecord, ReaderBuilder, Trim, WriterBuilder};
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+use crate::controllers::query::{columns, contains, parse_point, Point};
+use crate::error::Error;
+
+// The number of rows tested against the MOC at once, only these rows are held in memory.
+const CHUNK_ROWS: usize = 10_000;
+
+// The delimiters of a csv file that can be picked, with their name.
+pub(crate) const DELIMITERS: [(u8, &str); 5] = [
+    (b',', "Comma"),
+    (b';', "Semicolon"),
+    (b'\t', "Tab"),
+    (b' ', "Space"),
+    (b'|', "Pipe"),
+];
+
+// A column of a csv file, with the header names it is usually found under.
+pub(crate) struct Role {
+    pub(crate) label: &'static str,
+    pub(crate) names: &'static [&'static str],
+}
+pub(crate) const RA: Role = Role {
+    label: "RA (°)",
+    names: &[
+        "ra",
+        "raj2000",
+        "_raj2000",
+        "ra_icrs",
+        "ra_deg",
+        "lon",
+        "ra_center",
+    ],
+};
+pub(crate) const DEC: Role = Role {
+    label: "Dec (°)",
+    names: &[
+        "dec",
+        "de",
+        "dej2000",
+        "_dej2000",
+        "de_icrs",
+        "dec_deg",
+        "lat",
+        "dec_center",
+    ],
+};
+pub(crate) const JD: Role = Role {
+    label: "JD",
+    names: &["jd", "time", "epoch", "t"],
+};
+
+// The Filter struct, how the rows of a csv catalogue are filtered by a MOC.
+#[derive(Clone)]
+pub(crate) struct Filter {
+    pub(crate) delimiter: u8,
+    // Whether the first row is a header row, it is then written as it is.
+    pub(crate) has_headers: bool,
+    // The columns of the values the MOC type needs, in the order of query::columns
+    // (e.g. the RA and Dec columns for an S-MOC).
+    pub(crate) columns: Vec<usize>,
+    // Whether the rows in the MOC are kept, or the ones out of it.
+    pub(crate) keep_inside: bool,
+}
+
+// The FilterStats struct, the numbers of rows of a catalogue read and kept so far.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FilterStats {
+    pub(crate) read: usize,
+    pub(crate) kept: usize,
+    // The rows whose values can't be read, they are never kept.
+    pub(crate) invalid: usize,
+}
+
+// #Definition
+//      filter_csv streams the rows of a csv catalogue through a MOC and writes the rows kept,
+//      in the same format. The rows are read and tested by chunks, so that the catalogue is
+//      never held in memory. Comment lines (starting with '#') are left out.
+// #Args
+//  *   `id`: the id of the S-, T- or ST-MOC in the store
+//  *   `input`: the catalogue
+//  *   `output`: where to write the rows kept
+//  *   `filter`: the columns to read and the rows to keep
+//  *   `progress`: called after each chunk with the numbers of rows so far, the filtering
+//                  is cancelled if it returns false
+// #Returns
+//      The numbers of rows read, kept and invalid.
+// #Errors
+//      Error if the MOC can't be queried, if the number of columns doesn't match its type, if
+//      the catalogue can't be read or written or if the filtering is cancelled.
+pub(crate) fn filter_csv<R: Read, W: Write>(
+    id: usize,
+    input: R,
+    output: W,
+    filter: &Filter,
+    mut progress: impl FnMut(&FilterStats) -> bool,
+) -> Result<FilterStats, Error> {
+    let qty = U64MocStore.get_qty_type(id)?;
+    let names = columns(qty)?;
+    if filter.columns.len() != names.len() {
+        return Err(Error::Parse(format!(
+            "{} columns expected ({})",
+            names.len(),
+            names.join(", ")
+        )));
+    }
+    let mut reader = ReaderBuilder::new()
+        .delimiter(filter.delimiter)
+        .has_headers(filter.has_headers)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .from_reader(input);
+    let mut writer = WriterBuilder::new()
+        .delimiter(filter.delimiter)
+        .flexible(true)
+        .from_writer(output);
+    let headers = reader.byte_headers()?;
+    if filter.has_headers && !headers.is_empty() {
+        writer.write_byte_record(headers)?;
+    }
+
+    let mut stats = FilterStats::default();
+    let mut records: Vec<ByteRecord> = Vec::with_capacity(CHUNK_ROWS);
+    let mut points: Vec<Point> = Vec::with_capacity(CHUNK_ROWS);
+    let mut record = ByteRecord::new();
+    loop {
+        let more = reader.read_byte_record(&mut record)?;
+        if more {
+            stats.read += 1;
+            match read_point(&record, filter, qty) {
+                Some(point) => {
+                    records.push(record.clone());
+                    points.push(point);
+                }
+                None => stats.invalid += 1,
+            }
+        }
+        if points.len() == CHUNK_ROWS || !more {
+            let inside = contains(id, &points)?;
+            for (record, inside) in records.drain(..).zip(inside) {
+                if inside == filter.keep_inside {
+                    writer.write_byte_record(&record)?;
+                    stats.kept += 1;
+                }
+            }
+            points.clear();
+            if !progress(&stats) {
+                return Err(Error::Cancelled);
+            }
+        }
+        if !more {
+            break;
+        }
+    }
+    writer.flush()?;
+    Ok(stats)
+}
+
+// #Definition
+//      read_point reads the point of a row, None if a value is missing or is not valid.
+fn read_point(record: &ByteRecord, filter: &Filter, qty: MocQType) -> Option<Point> {
+    let values = filter
+        .columns
+        .iter()
+        .map(|c| record.get(*c).and_then(|v| std::str::from_utf8(v).ok()))
+        .collect::<Option<Vec<&str>>>()?;
+    parse_point(qty, &values).ok()
+}
+
+// #Definition
+//      first_line reads the first line of a csv file which is neither empty nor a comment,
+//      without reading the rest of the file.
+// #Args
+//  *   `input`: the (decompressed) file content
+// #Errors
+//      Error if the file can't be read or is not valid UTF-8.
+pub(crate) fn first_line<R: Read>(input: R) -> Result<String, Error> {
+    for line in BufReader::new(input).lines() {
+        let line = line?;
+        if !line.trim().is_empty() && !line.starts_with('#') {
+            return Ok(line);
+        }
+    }
+    Ok(String::default())
+}
+
+// #Definition
+//      split_line gives the fields of a csv line, e.g. the header names of the first line.
+pub(crate) fn split_line(line: &str, delimiter: u8) -> Vec<String> {
+    ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .trim(Trim::All)
+        .from_reader(line.as_bytes())
+        .records()
+        .next()
+        .and_then(Result::ok)
+        .map(|r| r.iter().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+// #Definition
+//      guess_delimiter gives the delimiter found the most in the first line of a csv file, a
+//      comma if none is found (e.g. a single column) or if several are found as often.
+pub(crate) fn guess_delimiter(first_line: &str) -> u8 {
+    let counts = DELIMITERS.map(|(d, _)| (d, first_line.bytes().filter(|b| *b == d).count()));
+    let max = counts.iter().map(|(_, n)| *n).max().unwrap_or_default();
+    match counts.iter().filter(|(_, n)| *n == max).collect::<Vec<_>>()[..] {
+        [(d, _)] if max > 0 => *d,
+        _ => b',',
+    }
+}
+
+// #Definition
+//      is_header tells whether the first line of a csv file is a header row, i.e. whether one
+//      of its fields is not a number.
+pub(crate) fn is_header(first_line: &str, delimiter: u8) -> bool {
+    first_line
+        .split(char::from(delimiter))
+        .any(|f| !f.trim().is_empty() && f.trim().parse::<f64>().is_err())
+}
+
+// #Definition
+//      guess_column gives the column whose header is one of the role's usual names, if any.
+// #Args
+//  *   `headers`: the header names of the csv file
+//  *   `role`: the role of the wanted column
+pub(crate) fn guess_column(headers: &[String], role: &Role) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| role.names.contains(&h.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimiter_defaults_to_comma() {
+        assert_eq!(guess_delimiter("ra;dec;jd"), b';');
+        assert_eq!(guess_delimiter("ra\tdec"), b'\t');
+        // A single column, and as many pipes as spaces.
+        assert_eq!(guess_delimiter("ra"), b',');
+        assert_eq!(guess_delimiter(""), b',');
+        assert_eq!(guess_delimiter("ra |dec"), b',');
+    }
+}
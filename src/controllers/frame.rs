@@ -0,0 +1,205 @@
+use core::fmt;
+
+// The offsets of the FK5 (J2000) pole and equinox from ICRS, in mas, giving the frame bias.
+const FK5_ETA_MAS: f64 = -19.9;
+const FK5_XI_MAS: f64 = 9.1;
+const FK5_DA_MAS: f64 = -22.9;
+// The rotation from ICRS to galactic coordinates (Hipparcos, vol. 1, 1.5.3).
+const GALACTIC: [[f64; 3]; 3] = [
+    [
+        -0.054_875_560_416_215_4,
+        -0.873_437_090_234_885,
+        -0.483_835_015_548_713_2,
+    ],
+    [
+        0.494_109_427_875_583_7,
+        -0.444_829_629_960_011_2,
+        0.746_982_244_497_219,
+    ],
+    [
+        -0.867_666_149_019_004_7,
+        -0.198_076_373_431_201_5,
+        0.455_983_776_175_066_9,
+    ],
+];
+// The obliquity of the J2000 mean ecliptic (IAU 2006), 84381.406".
+const OBLIQUITY_DEG: f64 = 84_381.406 / 3600.0;
+
+// The Frame enum, the celestial frames the positions of a MOC creation can be given in.
+// MOCs are always built in ICRS, the positions are converted beforehand.
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub(crate) enum Frame {
+    #[default]
+    Icrs,
+    Fk5,
+    Galactic,
+    // The J2000 mean ecliptic and equinox, the J2000 mean equator being approximated by FK5.
+    Ecliptic,
+}
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Icrs => write!(f, "ICRS"),
+            Self::Fk5 => write!(f, "FK5"),
+            Self::Galactic => write!(f, "Galactic"),
+            Self::Ecliptic => write!(f, "Ecliptic"),
+        }
+    }
+}
+
+impl Frame {
+    pub(crate) const ALL: [Frame; 4] = [Frame::Icrs, Frame::Fk5, Frame::Galactic, Frame::Ecliptic];
+
+    // #Definition
+    //      matrix gives the rotation from ICRS to the frame, None for ICRS.
+    fn matrix(self) -> Option<[[f64; 3]; 3]> {
+        match self {
+            Self::Icrs => None,
+            Self::Fk5 => Some(fk5()),
+            Self::Galactic => Some(GALACTIC),
+            Self::Ecliptic => Some(mul(&rot_x(OBLIQUITY_DEG.to_radians()), &fk5())),
+        }
+    }
+
+    // #Definition
+    //      to_icrs converts a position of the frame to ICRS.
+    // #Args
+    //  *   `lon_deg`: the longitude in the frame, in degrees
+    //  *   `lat_deg`: the latitude in the frame, in degrees
+    // #Returns
+    //      The ICRS RA in [0, 360[ and Dec, in degrees.
+    pub(crate) fn to_icrs(self, lon_deg: f64, lat_deg: f64) -> (f64, f64) {
+        match self.matrix() {
+            None => (lon_deg, lat_deg),
+            // The inverse of a rotation is its transpose.
+            Some(m) => to_lon_lat(tmul_vec(&m, &to_xyz(lon_deg, lat_deg))),
+        }
+    }
+
+    // #Definition
+    //      pa_to_icrs converts a position angle (east of north) at a position of the frame to
+    //      ICRS, i.e. adds the angle between the ICRS north and the north of the frame.
+    // #Args
+    //  *   `lon_deg`: the longitude in the frame, in degrees
+    //  *   `lat_deg`: the latitude in the frame, in degrees
+    //  *   `pa_deg`: the position angle in the frame, in degrees
+    // #Returns
+    //      The position angle in ICRS, in degrees in [0, 360[.
+    pub(crate) fn pa_to_icrs(self, lon_deg: f64, lat_deg: f64, pa_deg: f64) -> f64 {
+        let Some(m) = self.matrix() else {
+            return pa_deg;
+        };
+        let (ra, dec) = self.to_icrs(lon_deg, lat_deg);
+        let (pole_ra, pole_dec) = to_lon_lat(tmul_vec(&m, &[0.0, 0.0, 1.0]));
+        let (ra, dec, pole_ra, pole_dec) = (
+            ra.to_radians(),
+            dec.to_radians(),
+            pole_ra.to_radians(),
+            pole_dec.to_radians(),
+        );
+        let pole_pa = (pole_dec.cos() * (pole_ra - ra).sin())
+            .atan2(dec.cos() * pole_dec.sin() - dec.sin() * pole_dec.cos() * (pole_ra - ra).cos());
+        (pa_deg + pole_pa.to_degrees()).rem_euclid(360.0)
+    }
+}
+
+fn to_xyz(lon_deg: f64, lat_deg: f64) -> [f64; 3] {
+    let (lon, lat) = (lon_deg.to_radians(), lat_deg.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn to_lon_lat([x, y, z]: [f64; 3]) -> (f64, f64) {
+    let lon = y.atan2(x).to_degrees().rem_euclid(360.0);
+    let lat = z.atan2((x * x + y * y).sqrt()).to_degrees();
+    // rem_euclid may round a tiny negative longitude up to 360.
+    (if lon >= 360.0 { 0.0 } else { lon }, lat)
+}
+
+// The product of the transpose of m by v.
+fn tmul_vec(m: &[[f64; 3]; 3], v: &[f64; 3]) -> [f64; 3] {
+    let mut r = [0.0; 3];
+    for (i, ri) in r.iter_mut().enumerate() {
+        *ri = (0..3).map(|k| m[k][i] * v[k]).sum();
+    }
+    r
+}
+
+fn mul(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut r = [[0.0; 3]; 3];
+    for (i, row) in r.iter_mut().enumerate() {
+        for (j, rij) in row.iter_mut().enumerate() {
+            *rij = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    r
+}
+
+// The rotation of the axes by an angle (in radians) around x.
+fn rot_x(angle: f64) -> [[f64; 3]; 3] {
+    let (s, c) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+// #Definition
+//      fk5 gives the rotation from ICRS to FK5 (J2000), R_x(-eta) R_y(xi) R_z(da) to first
+//      order: the angles being of about 1e-7 rad, the error is below 1e-13.
+fn fk5() -> [[f64; 3]; 3] {
+    let mas = |v: f64| (v / 3_600_000.0).to_radians();
+    let (x, y, z) = (-mas(FK5_ETA_MAS), mas(FK5_XI_MAS), mas(FK5_DA_MAS));
+    [[1.0, z, -y], [-z, 1.0, x], [y, -x, 1.0]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The angular distance between two positions, in degrees.
+    fn distance_deg((lon1, lat1): (f64, f64), (lon2, lat2): (f64, f64)) -> f64 {
+        // From the chord, acos not being precise for close positions.
+        let [x1, y1, z1] = to_xyz(lon1, lat1);
+        let [x2, y2, z2] = to_xyz(lon2, lat2);
+        let chord = ((x1 - x2).powi(2) + (y1 - y2).powi(2) + (z1 - z2).powi(2)).sqrt();
+        2.0 * (chord / 2.0).min(1.0).asin().to_degrees()
+    }
+
+    #[test]
+    fn known_positions_in_icrs() {
+        // The galactic centre and pole, the ecliptic pole and the FK5 pole, less than 0.1"
+        // from the ICRS one.
+        for (frame, lon_lat, icrs, tolerance) in [
+            (Frame::Galactic, (0.0, 0.0), (266.404_99, -28.936_17), 1e-4),
+            (Frame::Galactic, (0.0, 90.0), (192.859_48, 27.128_25), 1e-4),
+            (
+                Frame::Ecliptic,
+                (0.0, 90.0),
+                (270.0, 90.0 - OBLIQUITY_DEG),
+                1e-4,
+            ),
+            (Frame::Fk5, (0.0, 90.0), (0.0, 90.0), 0.1 / 3600.0),
+            (Frame::Icrs, (12.5, -45.0), (12.5, -45.0), 0.0),
+        ] {
+            let to_icrs = frame.to_icrs(lon_lat.0, lon_lat.1);
+            assert!(
+                distance_deg(to_icrs, icrs) <= tolerance,
+                "{frame}: {to_icrs:?}"
+            );
+        }
+    }
+
+    // From ICRS to the frame and back, the matrices being rotations.
+    #[test]
+    fn round_trip() {
+        for frame in Frame::ALL {
+            let m = frame
+                .matrix()
+                .unwrap_or([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+            for (ra, dec) in [(0.0, 0.0), (123.4, 56.7), (266.4, -28.9), (359.9, -89.0)] {
+                let [x, y, z] = to_xyz(ra, dec);
+                let in_frame = m.map(|row| row[0] * x + row[1] * y + row[2] * z);
+                let (lon, lat) = to_lon_lat(in_frame);
+                let back = frame.to_icrs(lon, lat);
+                assert!(distance_deg(back, (ra, dec)) < 1e-9, "{frame}: {back:?}");
+            }
+        }
+    }
+}
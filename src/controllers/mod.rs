@@ -1,3 +1,11 @@
+pub(crate) mod compare;
+pub(crate) mod creation;
+pub(crate) mod expression;
+pub(crate) mod filter;
+pub(crate) mod frame;
 pub(crate) mod op1;
 pub(crate) mod op2;
-pub(crate) mod creation;
\ No newline at end of file
+pub(crate) mod opn;
+pub(crate) mod query;
+pub(crate) mod stats;
+pub(crate) mod stmoc;
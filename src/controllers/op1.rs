@@ -1,6 +1,8 @@
 use core::fmt;
+use std::collections::BTreeMap;
 
-use crate::utils::namestore::add;
+use crate::error::Error;
+use crate::utils::registry::Registry;
 use moc::storage::u64idx::{common::MocQType, U64MocStore};
 
 // The OP1 type
@@ -57,117 +59,119 @@ impl PartialEq for Op1 {
 }
 
 impl Op1 {
+    // #Definition
+    //      apply performs the operation on the given MOC, the resulting MOCs are only in the
+    //      MOC store (not named, not recorded in the history).
+    // #Args
+    //  *   `id`: the MOC on which to perform the op
+    // #Errors
+    //      Error if a timespace string is used as TimeSpace MOCs cannot be operated on alone.
+    //      Errors come from the store operation.
+    pub(crate) fn apply(self, id: usize) -> Result<Vec<usize>, Error> {
+        match U64MocStore.get_qty_type(id)? {
+            MocQType::Space => self.perform_op_on_smoc(id),
+            MocQType::Time => self.perform_op_on_tmoc(id),
+            MocQType::TimeSpace => Err(Error::UnsupportedQty(String::from(
+                "Operations are not implemented for ST-MOCs.",
+            ))),
+            MocQType::Frequency => self.perform_op_on_fmoc(id),
+        }
+    }
     // #Definition
     //      perform_op_on_smoc, it does exactly as it says, it performs an operation on a given SMOC
     // #Args
     //  *   `id`: the MOC on which to perform the op
-    //  *   `n`: the name given to the new MOC once added to the store
     // #Errors
-    //      Errors can come if "add" does not work, in which case the MOC is not added to the store.
-    fn perform_op_on_smoc(self, id: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    //      Errors come from the store operation.
+    fn perform_op_on_smoc(self, id: usize) -> Result<Vec<usize>, Error> {
         match self {
-            Op1::Complement => {
-                if let Ok(index) = U64MocStore.complement(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Degrade { new_depth } => {
-                if let Ok(index) = U64MocStore.degrade(id, new_depth) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Extend => {
-                if let Ok(index) = U64MocStore.extend(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Contract => {
-                if let Ok(index) = U64MocStore.contract(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::ExtBorder => {
-                if let Ok(index) = U64MocStore.ext_border(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::IntBorder => {
-                if let Ok(index) = U64MocStore.int_border(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Split => {
-                for i in U64MocStore.split(id)? {
-                    add(&format!("{i}_{name}"), i)?;
-                }
-                Ok(())
-            }
-            Op1::SplitIndirect => {
-                for i in U64MocStore.split(id)? {
-                    add(&format!("{i}_{name}"), i)?;
-                }
-                Ok(())
-            }
+            Op1::Complement => Ok(vec![U64MocStore.complement(id)?]),
+            Op1::Degrade { new_depth } => Ok(vec![U64MocStore.degrade(id, new_depth)?]),
+            Op1::Extend => Ok(vec![U64MocStore.extend(id)?]),
+            Op1::Contract => Ok(vec![U64MocStore.contract(id)?]),
+            Op1::ExtBorder => Ok(vec![U64MocStore.ext_border(id)?]),
+            Op1::IntBorder => Ok(vec![U64MocStore.int_border(id)?]),
+            Op1::Split => Ok(U64MocStore.split(id)?),
+            Op1::SplitIndirect => Ok(U64MocStore.split_indirect(id)?),
         }
     }
     // #Definition
     //      Same as perform_op_on_smoc, but for TMOCs
     //      The only difference is that only complement and degrade work for this type of MOC
-    fn perform_op_on_tmoc(self, id: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    fn perform_op_on_tmoc(self, id: usize) -> Result<Vec<usize>, Error> {
         match self {
-            Op1::Complement => {
-                if let Ok(index) = U64MocStore.complement(id) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Degrade { new_depth } => {
-                if let Ok(index) = U64MocStore.degrade(id, new_depth) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op1::Extend => Err(String::from(
+            Op1::Complement => Ok(vec![U64MocStore.complement(id)?]),
+            Op1::Degrade { new_depth } => Ok(vec![U64MocStore.degrade(id, new_depth)?]),
+            Op1::Extend => Err(Error::UnsupportedQty(String::from(
                 "Extend border not implemented (yet) for T-MOCs.",
-            )),
-            Op1::Contract => Err(String::from(
+            ))),
+            Op1::Contract => Err(Error::UnsupportedQty(String::from(
                 "Contract border not implemented (yet) for T-MOCs.",
-            )),
-            Op1::ExtBorder => Err(String::from(
+            ))),
+            Op1::ExtBorder => Err(Error::UnsupportedQty(String::from(
                 "External border not implemented (yet) for T-MOCs.",
-            )),
-            Op1::IntBorder => Err(String::from(
+            ))),
+            Op1::IntBorder => Err(Error::UnsupportedQty(String::from(
                 "Internal border not implemented (yet) for T-MOCs.",
-            )),
-            Op1::Split | Op1::SplitIndirect => {
-                Err(String::from("Split not implemented for T-MOCs."))
-            }
+            ))),
+            Op1::Split | Op1::SplitIndirect => Err(Error::UnsupportedQty(String::from(
+                "Split not implemented for T-MOCs.",
+            ))),
+        }
+    }
+    // #Definition
+    //      Same as perform_op_on_smoc, but for FMOCs
+    //      Borders and splits are not available for this type of MOC
+    fn perform_op_on_fmoc(self, id: usize) -> Result<Vec<usize>, Error> {
+        match self {
+            Op1::Complement => Ok(vec![U64MocStore.complement(id)?]),
+            Op1::Degrade { new_depth } => Ok(vec![U64MocStore.degrade(id, new_depth)?]),
+            Op1::Extend => Ok(vec![U64MocStore.extend(id)?]),
+            Op1::Contract => Ok(vec![U64MocStore.contract(id)?]),
+            Op1::ExtBorder => Err(Error::UnsupportedQty(String::from(
+                "External border not implemented (yet) for F-MOCs.",
+            ))),
+            Op1::IntBorder => Err(Error::UnsupportedQty(String::from(
+                "Internal border not implemented (yet) for F-MOCs.",
+            ))),
+            Op1::Split | Op1::SplitIndirect => Err(Error::UnsupportedQty(String::from(
+                "Split not implemented for F-MOCs.",
+            ))),
         }
     }
 }
 
 // #Definition
 //      op1 performs the given operation on the given MOC and store the resulting MOC in the store.
+//      It returns the ids of the resulting MOCs (several ones in case of a split), the operation
+//      is recorded in the history.
 // #Args
+//  *   `registry`: the registry the results are added to
 //  *   `id`: the MOC's id in the store
 //  *   `op`: the operation that needs to be applied to the MOC
 //  *   `res_name`: The name given to the result
 // #Errors
 //      Error if a timespace string is used as TimeSpace MOCs cannot be operated on alone.
-pub(crate) fn op1(id: usize, op: Op1, res_name: &str) -> Result<(), String> {
-    let moc = U64MocStore.get_qty_type(id)?;
-    match moc {
-        MocQType::Space => op.perform_op_on_smoc(id, res_name),
-        MocQType::Time => op.perform_op_on_tmoc(id, res_name),
-        MocQType::TimeSpace => Err(String::from("Operations are not implemented for ST-MOCs.")),
-        MocQType::Frequency => unreachable!(),
+pub(crate) fn op1(
+    registry: &mut Registry,
+    id: usize,
+    op: Op1,
+    res_name: &str,
+) -> Result<Vec<usize>, Error> {
+    let indices = op.apply(id)?;
+    if matches!(op, Op1::Split | Op1::SplitIndirect) {
+        for i in indices.iter() {
+            registry.insert(&format!("{i}_{res_name}"), *i, None, BTreeMap::new())?;
+        }
+    } else {
+        for i in indices.iter() {
+            registry.insert(res_name, *i, None, BTreeMap::new())?;
+        }
     }
+    let params = match op {
+        Op1::Degrade { new_depth } => format!("depth {new_depth}"),
+        _ => String::default(),
+    };
+    registry.added(&op.to_string(), &[id], &params, &indices)?;
+    Ok(indices)
 }
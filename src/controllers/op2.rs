@@ -1,6 +1,8 @@
 use core::fmt;
+use std::collections::BTreeMap;
 
-use crate::utils::namestore::add;
+use crate::error::Error;
+use crate::utils::registry::Registry;
 use moc::storage::u64idx::U64MocStore;
 
 // The OP2 type
@@ -52,199 +54,246 @@ impl PartialEq for Op2 {
 }
 
 impl Op2 {
+    // #Definition
+    //      apply performs the operation on the given MOCs, the resulting MOC is only in the MOC
+    //      store (not named, not recorded in the history).
+    // #Args
+    //  *   `left`: the first MOC
+    //  *   `right`: the second MOC
+    // #Errors
+    //      Error if MOCs are not the same OR a TimeSpace and a Space/Time MOC.
+    //      Error if the MOC type is not found.
+    pub(crate) fn apply(self, left: usize, right: usize) -> Result<usize, Error> {
+        if let (Ok(left_qty), Ok(right_qty)) = (
+            U64MocStore.get_qty_type(left),
+            U64MocStore.get_qty_type(right),
+        ) {
+            match (left_qty, right_qty) {
+                (
+                    moc::storage::u64idx::common::MocQType::Space,
+                    moc::storage::u64idx::common::MocQType::Space,
+                ) => self.perform_op_on_smoc(left, right),
+                (
+                    moc::storage::u64idx::common::MocQType::Space,
+                    moc::storage::u64idx::common::MocQType::TimeSpace,
+                ) => self.perform_space_fold(left, right),
+                (
+                    moc::storage::u64idx::common::MocQType::Time,
+                    moc::storage::u64idx::common::MocQType::Time,
+                ) => self.perform_op_on_tmoc(left, right),
+                (
+                    moc::storage::u64idx::common::MocQType::Time,
+                    moc::storage::u64idx::common::MocQType::TimeSpace,
+                ) => self.perform_time_fold(left, right),
+                (
+                    moc::storage::u64idx::common::MocQType::TimeSpace,
+                    moc::storage::u64idx::common::MocQType::TimeSpace,
+                ) => self.perform_op_on_stmoc(left, right),
+                (
+                    moc::storage::u64idx::common::MocQType::Frequency,
+                    moc::storage::u64idx::common::MocQType::Frequency,
+                ) => self.perform_op_on_fmoc(left, right),
+                _ => Err(Error::UnsupportedQty(String::from(
+                    "Both type of both MOCs must be the same, except in fold operations",
+                ))),
+            }
+        } else {
+            Err(Error::NotFound(String::from("MOC QTY type")))
+        }
+    }
     // #Definition
     //      perform_op_on_smoc, it does exactly as it says, it performs an operation on a given SMOC
     // #Args
     //  *   `left`: the first selected MOC
     //  *   `right`: the second MOC on which the op will be performed, using both MOCs
-    //  *   `n`: the name given to the new MOC once added to the store
     // #Errors
-    //      Errors can come if "add" does not work, in which case the MOC is not added to the store.
-    fn perform_op_on_smoc(self, left: usize, right: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    //      Errors come from the store operation.
+    fn perform_op_on_smoc(self, left: usize, right: usize) -> Result<usize, Error> {
         match self {
-            Op2::Intersection => {
-                if let Ok(index) = U64MocStore.intersection(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Union => {
-                if let Ok(index) = U64MocStore.union(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Difference => {
-                if let Ok(index) = U64MocStore.difference(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Minus => {
-                if let Ok(index) = U64MocStore.minus(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::TFold => Err(String::from(
+            Op2::Intersection => Ok(U64MocStore.intersection(left, right)?),
+            Op2::Union => Ok(U64MocStore.union(left, right)?),
+            Op2::Difference => Ok(U64MocStore.symmetric_difference(left, right)?),
+            Op2::Minus => minus(left, right),
+            Op2::TFold => Err(Error::UnsupportedQty(String::from(
                 "TimeFold operation not available on 2 S-MOCs.",
-            )),
-            Op2::SFold => Err(String::from(
+            ))),
+            Op2::SFold => Err(Error::UnsupportedQty(String::from(
                 "SpaceFold operation not available on 2 S-MOCs.",
-            )),
+            ))),
         }
     }
     // #Definition
     //      Same as perform_op_on_smoc, but for TMOCs
-    fn perform_op_on_tmoc(self, left: usize, right: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    fn perform_op_on_tmoc(self, left: usize, right: usize) -> Result<usize, Error> {
         match self {
-            Op2::Intersection => {
-                if let Ok(index) = U64MocStore.intersection(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Union => {
-                if let Ok(index) = U64MocStore.union(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Difference => {
-                if let Ok(index) = U64MocStore.difference(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Minus => {
-                if let Ok(index) = U64MocStore.minus(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::TFold => Err(String::from(
+            Op2::Intersection => Ok(U64MocStore.intersection(left, right)?),
+            Op2::Union => Ok(U64MocStore.union(left, right)?),
+            Op2::Difference => Ok(U64MocStore.symmetric_difference(left, right)?),
+            Op2::Minus => minus(left, right),
+            Op2::TFold => Err(Error::UnsupportedQty(String::from(
                 "TimeFold operation not available on 2 T-MOCs.",
-            )),
-            Op2::SFold => Err(String::from(
+            ))),
+            Op2::SFold => Err(Error::UnsupportedQty(String::from(
                 "SpaceFold operation not available on 2 T-MOCs.",
-            )),
+            ))),
+        }
+    }
+    // #Definition
+    //      Same as perform_op_on_smoc, but for FMOCs
+    fn perform_op_on_fmoc(self, left: usize, right: usize) -> Result<usize, Error> {
+        match self {
+            Op2::Intersection => Ok(U64MocStore.intersection(left, right)?),
+            Op2::Union => Ok(U64MocStore.union(left, right)?),
+            Op2::Difference => Ok(U64MocStore.symmetric_difference(left, right)?),
+            Op2::Minus => minus(left, right),
+            Op2::TFold => Err(Error::UnsupportedQty(String::from(
+                "TimeFold operation not available on 2 F-MOCs.",
+            ))),
+            Op2::SFold => Err(Error::UnsupportedQty(String::from(
+                "SpaceFold operation not available on 2 F-MOCs.",
+            ))),
         }
     }
     // #Definition
     //      Same as perform_op_on_smoc, but for STMOCs
     //      The difference is that difference does not work for STMOCs
-    fn perform_op_on_stmoc(self, left: usize, right: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    fn perform_op_on_stmoc(self, left: usize, right: usize) -> Result<usize, Error> {
         match self {
-            Op2::Intersection => {
-                if let Ok(index) = U64MocStore.intersection(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Union => {
-                if let Ok(index) = U64MocStore.union(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::Difference => {
-                Err(String::from(
-                    "Difference (or xor) not implemented for ST-MOCs.",
-                ))
-            }
-            Op2::Minus => {
-                if let Ok(index) = U64MocStore.minus(left, right) {
-                    add(&name, index)?;
-                }
-                Ok(())
-            }
-            Op2::TFold => {
-                Err(String::from(
-                    "TimeFold operation not available on 2 ST-MOCs.",
-                ))
-            }
-            Op2::SFold => {
-                Err(String::from(
-                    "SpaceFold operation not available on 2 ST-MOCs.",
-                ))
-            }
+            Op2::Intersection => Ok(U64MocStore.intersection(left, right)?),
+            Op2::Union => Ok(U64MocStore.union(left, right)?),
+            Op2::Difference => Err(Error::UnsupportedQty(String::from(
+                "Difference (or xor) not implemented for ST-MOCs.",
+            ))),
+            Op2::Minus => minus(left, right),
+            Op2::TFold => Err(Error::UnsupportedQty(String::from(
+                "TimeFold operation not available on 2 ST-MOCs.",
+            ))),
+            Op2::SFold => Err(Error::UnsupportedQty(String::from(
+                "SpaceFold operation not available on 2 ST-MOCs.",
+            ))),
         }
     }
     // #Definition
     //      Same as perform_op_on_smoc, but it only works for space_fold
-    fn perform_space_fold(self, left: usize, right: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    fn perform_space_fold(self, left: usize, right: usize) -> Result<usize, Error> {
         if !matches!(self, Op2::SFold) {
-            Err(String::from(
+            Err(Error::UnsupportedQty(String::from(
                 "Operation SpaceFold expected on S-MOC with ST-MOC.",
-            ))
+            )))
         } else {
-            if let Ok(index) = U64MocStore.space_fold(left, right) {
-                add(&name, index)?;
-            }
-            Ok(())
+            Ok(U64MocStore.space_fold(left, right)?)
         }
     }
     // #Definition
     //      Same as perform_op_on_smoc, but it only works for time_fold
-    fn perform_time_fold(self, left: usize, right: usize, n: &str) -> Result<(), String> {
-        let name = n.to_string();
+    fn perform_time_fold(self, left: usize, right: usize) -> Result<usize, Error> {
         if !matches!(self, Op2::TFold) {
-            Err(String::from(
+            Err(Error::UnsupportedQty(String::from(
                 "Operation TimeFold expected on T-MOC with ST-MOC.",
-            ))
+            )))
         } else {
-            if let Ok(index) = U64MocStore.time_fold(left, right) {
-                add(&name, index)?;
-            }
-            Ok(())
+            Ok(U64MocStore.time_fold(left, right)?)
         }
     }
 }
 
 // #Definition
-//      op1 performs the given operation on the given MOC and store the resulting MOC in the store.
+//      minus gives the MOC of what is in the left MOC and not in the right one.
+//      The MOC library gives an empty MOC when all the ranges of a MOC come before all the
+//      ranges of the other, the left MOC is then copied (its union with the empty MOC).
+// #Args
+//  *   `left`: the first MOC
+//  *   `right`: the MOC removed from the first one
+// #Errors
+//      Errors come from the store operations.
+pub(crate) fn minus(left: usize, right: usize) -> Result<usize, Error> {
+    let id = U64MocStore.minus(left, right)?;
+    if !U64MocStore.is_empty(id)? || U64MocStore.is_empty(left)? {
+        return Ok(id);
+    }
+    // The result is only right if the left MOC is in the right one.
+    let intersection = U64MocStore.intersection(left, right)?;
+    let disjoint = U64MocStore.is_empty(intersection);
+    U64MocStore.drop(intersection)?;
+    if !disjoint? {
+        return Ok(id);
+    }
+    let copy = U64MocStore.union(left, id);
+    U64MocStore.drop(id)?;
+    Ok(copy?)
+}
+
+// #Definition
+//      op2 performs the given operation on the given MOCs and store the resulting MOC in the store.
+//      It returns the id of the resulting MOC, the operation is recorded in the history.
 // #Args
-//  *   `id`: the MOC's id in the store
+//  *   `registry`: the registry the result is added to
+//  *   `left_id`: the first MOC's id in the store
+//  *   `right_id`: the second MOC's id in the store
 //  *   `op`: the operation that needs to be applied to the MOC
 //  *   `res_name`: The name given to the result
 // #Errors
 //      Error if MOCs are not the same OR a TimeSpace and a Space/Time MOC.
 //      Error if the MOC type is not found.
-pub(crate) fn op2(left_id: usize, right_id: usize, op: Op2, res_name: &str) -> Result<(), String> {
-    if let (Ok(left), Ok(right)) = (
-        U64MocStore.get_qty_type(left_id),
-        U64MocStore.get_qty_type(right_id),
-    ) {
-        match (left, right) {
-            (
-                moc::storage::u64idx::common::MocQType::Space,
-                moc::storage::u64idx::common::MocQType::Space,
-            ) => op.perform_op_on_smoc(left_id, right_id, res_name),
-            (
-                moc::storage::u64idx::common::MocQType::Space,
-                moc::storage::u64idx::common::MocQType::TimeSpace,
-            ) => op.perform_space_fold(left_id, right_id, res_name),
-            (
-                moc::storage::u64idx::common::MocQType::Time,
-                moc::storage::u64idx::common::MocQType::Time,
-            ) => op.perform_op_on_tmoc(left_id, right_id, res_name),
-            (
-                moc::storage::u64idx::common::MocQType::Time,
-                moc::storage::u64idx::common::MocQType::TimeSpace,
-            ) => op.perform_time_fold(left_id, right_id, res_name),
-            (
-                moc::storage::u64idx::common::MocQType::TimeSpace,
-                moc::storage::u64idx::common::MocQType::TimeSpace,
-            ) => op.perform_op_on_stmoc(left_id, right_id, res_name),
-            _ => Err(String::from(
-                "Both type of both MOCs must be the same, except in fold operations",
-            )),
+pub(crate) fn op2(
+    registry: &mut Registry,
+    left_id: usize,
+    right_id: usize,
+    op: Op2,
+    res_name: &str,
+) -> Result<usize, Error> {
+    let index = op.apply(left_id, right_id)?;
+    registry.insert(res_name, index, None, BTreeMap::new())?;
+    registry.added(&op.to_string(), &[left_id, right_id], "", &[index])?;
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii(id: usize) -> String {
+        U64MocStore.to_ascii_str(id, None).unwrap()
+    }
+
+    #[test]
+    fn minus_of_disjoint_mocs() {
+        let a = U64MocStore.load_smoc_from_ascii("3/0-20").unwrap();
+        let b = U64MocStore.load_smoc_from_ascii("3/100").unwrap();
+        let c = U64MocStore.load_smoc_from_ascii("3/10-30").unwrap();
+        for (l, r, expected) in [
+            (a, b, "3/0-20"),
+            (b, a, "3/100"),
+            (a, c, "3/0-9"),
+            (a, a, "3/"),
+        ] {
+            let id = Op2::Minus.apply(l, r).unwrap();
+            let expected = U64MocStore.load_smoc_from_ascii(expected).unwrap();
+            assert_eq!(ascii(id), ascii(expected));
+            U64MocStore.drop(id).unwrap();
+            U64MocStore.drop(expected).unwrap();
+        }
+        let t = U64MocStore.load_tmoc_from_ascii("10/5-8").unwrap();
+        let u = U64MocStore.load_tmoc_from_ascii("10/900").unwrap();
+        let id = Op2::Minus.apply(u, t).unwrap();
+        assert_eq!(ascii(id), ascii(u));
+        for id in [a, b, c, t, u, id] {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+
+    #[test]
+    fn difference_is_symmetric() {
+        let a = U64MocStore.load_smoc_from_ascii("3/0-20").unwrap();
+        for (r, expected) in [("3/10-30", "3/0-9 21-30"), ("3/100", "3/0-20 100")] {
+            let r = U64MocStore.load_smoc_from_ascii(r).unwrap();
+            let id = Op2::Difference.apply(a, r).unwrap();
+            let expected = U64MocStore.load_smoc_from_ascii(expected).unwrap();
+            assert_eq!(ascii(id), ascii(expected));
+            for id in [r, id, expected] {
+                U64MocStore.drop(id).unwrap();
+            }
         }
-    } else {
-        Err(String::from("Could not get moc QTY type"))
+        U64MocStore.drop(a).unwrap();
     }
 }
@@ -0,0 +1,198 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use crate::controllers::op2::minus;
+use crate::error::Error;
+use crate::utils::{commons::same_qty, registry::Registry};
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+// The OPN type, operations on an arbitrary number of MOCs of the same type
+#[derive(Copy, Clone, Eq, PartialEq, Default)]
+pub(crate) enum OpN {
+    #[default]
+    Union,
+    Intersection,
+    Minus,
+}
+impl fmt::Display for OpN {
+    // #Definition
+    //      fmt formats an OPN to a string
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Union => write!(f, "Union"),
+            Self::Intersection => write!(f, "Intersection"),
+            Self::Minus => write!(f, "Minus"),
+        }
+    }
+}
+
+impl OpN {
+    // #Definition
+    //      perform_op performs the operation on the given MOCs and returns the id of the result.
+    //      Union and intersection are done in one pass by the store (except for ST-MOCs),
+    //      minus is done successively: ((first - second) - third) - ...
+    // #Args
+    //  *   `ids`: the MOCs on which to perform the op, in order
+    //  *   `qty`: the type of the MOCs
+    fn perform_op(self, ids: &[usize], qty: MocQType) -> Result<usize, Error> {
+        match (self, qty) {
+            (OpN::Union, MocQType::TimeSpace) => fold(ids, |l, r| Ok(U64MocStore.union(l, r)?)),
+            (OpN::Union, _) => Ok(U64MocStore.multi_union(ids)?),
+            (OpN::Intersection, MocQType::TimeSpace) => {
+                fold(ids, |l, r| Ok(U64MocStore.intersection(l, r)?))
+            }
+            (OpN::Intersection, _) => Ok(U64MocStore.multi_intersection(ids)?),
+            (OpN::Minus, _) => fold(ids, minus),
+        }
+    }
+}
+
+// #Definition
+//      fold successively applies a binary operation from the first MOC to the last one.
+//      The intermediate MOCs are dropped from the store, only the last result is kept.
+// #Args
+//  *   `ids`: the MOCs on which to perform the op, in order
+//  *   `op`: the binary operation
+fn fold<F>(ids: &[usize], op: F) -> Result<usize, Error>
+where
+    F: Fn(usize, usize) -> Result<usize, Error>,
+{
+    let (first, others) = ids
+        .split_first()
+        .ok_or_else(|| Error::NotFound(String::from("MOC to operate on")))?;
+    let mut acc = *first;
+    for id in others {
+        let res = op(acc, *id);
+        if acc != *first {
+            U64MocStore.drop(acc)?;
+        }
+        acc = res?;
+    }
+    Ok(acc)
+}
+
+// #Definition
+//      opn performs the given operation on the given MOCs and stores the resulting MOC in the store.
+//      It returns the id of the resulting MOC, the operation is recorded in the history.
+// #Args
+//  *   `registry`: the registry the result is added to
+//  *   `ids`: the MOCs' ids in the store, the order matters for minus
+//  *   `op`: the operation that needs to be applied to the MOCs
+//  *   `res_name`: The name given to the result
+// #Errors
+//      Error if there are less than 2 MOCs or if they are not all of the same type.
+//      Error if the operation is not available for the type of the MOCs.
+pub(crate) fn opn(
+    registry: &mut Registry,
+    ids: &[usize],
+    op: OpN,
+    res_name: &str,
+) -> Result<usize, Error> {
+    if ids.len() < 2 {
+        return Err(Error::Parse(String::from(
+            "At least 2 MOCs are needed for an operation",
+        )));
+    }
+    let qty = U64MocStore.get_qty_type(ids[0])?;
+    for id in &ids[1..] {
+        if !same_qty(U64MocStore.get_qty_type(*id)?, qty) {
+            return Err(Error::UnsupportedQty(String::from(
+                "All the MOCs must be of the same type",
+            )));
+        }
+    }
+    let index = op.perform_op(ids, qty)?;
+    registry.insert(res_name, index, None, BTreeMap::new())?;
+    registry.added(&op.to_string(), ids, "", &[index])?;
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii(id: usize) -> String {
+        U64MocStore
+            .to_ascii_str(id, None)
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    // The ASCII of a MOC as written by the store.
+    fn ascii_of(load: fn(&str) -> usize, moc: &str) -> String {
+        let id = load(moc);
+        let res = ascii(id);
+        U64MocStore.drop(id).unwrap();
+        res
+    }
+
+    // #Definition
+    //      result applies an operation on MOCs given in ASCII and gives the result in ASCII.
+    fn result(op: OpN, mocs: &[&str], load: fn(&str) -> usize) -> String {
+        let ids: Vec<usize> = mocs.iter().map(|moc| load(moc)).collect();
+        let mut registry = Registry::default();
+        let id = opn(&mut registry, &ids, op, "result").unwrap();
+        let res = ascii(id);
+        for id in ids.into_iter().chain([id]) {
+            U64MocStore.drop(id).unwrap();
+        }
+        res
+    }
+
+    fn smoc(ascii: &str) -> usize {
+        U64MocStore.load_smoc_from_ascii(ascii).unwrap()
+    }
+
+    fn stmoc(ascii: &str) -> usize {
+        U64MocStore.load_stmoc_from_ascii(ascii).unwrap()
+    }
+
+    #[test]
+    fn union_and_intersection_of_three_mocs() {
+        let mocs = ["3/0-20", "3/10-30", "3/15-40"];
+        assert_eq!(result(OpN::Union, &mocs, smoc), ascii_of(smoc, "3/0-40"));
+        assert_eq!(
+            result(OpN::Intersection, &mocs, smoc),
+            ascii_of(smoc, "3/15-20")
+        );
+        // ST-MOCs are folded two by two.
+        let mocs = ["t10/1-3 s3/0-20", "t10/2-5 s3/10-30", "t10/3-9 s3/15-40"];
+        assert_eq!(
+            result(OpN::Intersection, &mocs, stmoc),
+            ascii_of(stmoc, "t10/3 s3/15-20")
+        );
+    }
+
+    // Minus is a left fold: (a - b) - c.
+    #[test]
+    fn minus_is_done_from_left_to_right() {
+        assert_eq!(
+            result(OpN::Minus, &["3/0-40", "3/10-20", "3/30-35"], smoc),
+            ascii_of(smoc, "3/0-9 21-29 36-40")
+        );
+        assert_eq!(
+            result(OpN::Minus, &["3/10-20", "3/0-40", "3/30-35"], smoc),
+            ascii_of(smoc, "3/")
+        );
+    }
+
+    #[test]
+    fn operands_are_checked() {
+        let mut registry = Registry::default();
+        let s = smoc("3/0-20");
+        let t = U64MocStore.load_tmoc_from_ascii("10/5-8").unwrap();
+        assert!(matches!(
+            opn(&mut registry, &[s], OpN::Union, "result"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            opn(&mut registry, &[s, s, t], OpN::Union, "result"),
+            Err(Error::UnsupportedQty(_))
+        ));
+        assert!(registry.is_empty());
+        for id in [s, t] {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+}
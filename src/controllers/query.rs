@@ -0,0 +1,248 @@
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+use crate::error::Error;
+use crate::utils::commons::fmt_qty;
+
+// A position and/or a time tested against a MOC, the position in degrees and the time in
+// decimal JD.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Point {
+    pub(crate) pos: Option<(f64, f64)>,
+    pub(crate) jd: Option<f64>,
+}
+
+// #Definition
+//      columns gives the values a point needs to be tested against a MOC of the given type.
+// #Errors
+//      Error if the MOC type can't be queried (F-MOC).
+pub(crate) fn columns(qty: MocQType) -> Result<&'static [&'static str], Error> {
+    match qty {
+        MocQType::Space => Ok(&["ra", "dec"]),
+        MocQType::Time => Ok(&["jd"]),
+        MocQType::TimeSpace => Ok(&["ra", "dec", "jd"]),
+        MocQType::Frequency => Err(Error::UnsupportedQty(format!(
+            "Points can't be tested against a {}",
+            fmt_qty(qty)
+        ))),
+    }
+}
+
+// #Definition
+//      parse_point reads a point from its values, in the order given by columns. The right
+//      ascension is brought into [0, 360[, whatever the type of the MOC.
+// #Args
+//  *   `qty`: the type of the MOC the point is tested against
+//  *   `values`: the values, e.g. ["83.63", "22.01"] for an S-MOC
+// #Errors
+//      Error if a value is missing or is not a number, or if the declination is not in
+//      [-90, 90].
+pub(crate) fn parse_point(qty: MocQType, values: &[&str]) -> Result<Point, Error> {
+    let columns = columns(qty)?;
+    if values.len() < columns.len() {
+        return Err(Error::Parse(format!(
+            "{} values expected ({}), {} given",
+            columns.len(),
+            columns.join(", "),
+            values.len()
+        )));
+    }
+    let mut numbers = Vec::with_capacity(columns.len());
+    for (column, value) in columns.iter().zip(values) {
+        let number: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| Error::Parse(format!("'{}' is not a valid {column}", value.trim())))?;
+        numbers.push(number);
+    }
+    let point = match numbers[..] {
+        [ra, dec, jd] => Point {
+            pos: Some((ra, dec)),
+            jd: Some(jd),
+        },
+        [ra, dec] => Point {
+            pos: Some((ra, dec)),
+            jd: None,
+        },
+        [jd] => Point {
+            pos: None,
+            jd: Some(jd),
+        },
+        _ => Point::default(),
+    };
+    match point.pos {
+        Some((_, dec)) if !(-90.0..=90.0).contains(&dec) => Err(Error::Parse(format!(
+            "The declination {dec} is not in [-90, 90]"
+        ))),
+        Some((ra, dec)) => Ok(Point {
+            pos: Some((wrap_ra(ra), dec)),
+            ..point
+        }),
+        None => Ok(point),
+    }
+}
+
+// A right ascension in degrees brought into [0, 360[.
+fn wrap_ra(ra: f64) -> f64 {
+    let ra = ra.rem_euclid(360.0);
+    // rem_euclid may round a tiny negative angle up to 360.
+    if ra >= 360.0 {
+        0.0
+    } else {
+        ra
+    }
+}
+
+// #Definition
+//      contains tells whether each point is in the MOC: its position for an S-MOC, its time
+//      for a T-MOC and both for an ST-MOC.
+// #Args
+//  *   `id`: the id of the MOC in the store
+//  *   `points`: the points, read by parse_point for the type of the MOC
+// #Returns
+//      One boolean per point.
+// #Errors
+//      Error if the MOC is not in the store or can't be queried (F-MOC).
+pub(crate) fn contains(id: usize, points: &[Point]) -> Result<Vec<bool>, Error> {
+    let qty = U64MocStore.get_qty_type(id)?;
+    let pos = |p: &Point| p.pos.unwrap_or((f64::NAN, f64::NAN));
+    let jd = |p: &Point| p.jd.unwrap_or_default();
+    let inside = match qty {
+        MocQType::Space => U64MocStore.filter_pos(id, points.iter().map(pos), |b| b)?,
+        MocQType::Time => U64MocStore.filter_time_approx(id, points.iter().map(jd), |b| b)?,
+        // Unlike filter_pos, filter_timepos expects the coordinates in radians.
+        MocQType::TimeSpace => U64MocStore.filter_timepos_approx(
+            id,
+            points.iter().map(|p| {
+                let (ra, dec) = pos(p);
+                (jd(p), (ra.to_radians(), dec.to_radians()))
+            }),
+            |b| b,
+        )?,
+        MocQType::Frequency => return columns(qty).map(|_| Vec::default()),
+    };
+    Ok(inside)
+}
+
+// A row of a list of points: its line in the list, its values and the point read from them
+// or why it can't be read.
+#[derive(Clone)]
+pub(crate) struct Row {
+    pub(crate) line: usize,
+    pub(crate) values: Vec<String>,
+    pub(crate) point: Result<Point, String>,
+}
+
+// #Definition
+//      parse_list reads a list of points, one per line, the values being separated by commas,
+//      semicolons, tabs or spaces. Empty lines and comments (starting with '#') are skipped, as
+//      well as a first line which is not made of numbers (a header).
+// #Args
+//  *   `qty`: the type of the MOC the points are tested against
+//  *   `text`: the list, e.g. "83.63, 22.01\n10.68, 41.27" for an S-MOC
+// #Errors
+//      Error if the MOC type can't be queried (F-MOC).
+pub(crate) fn parse_list(qty: MocQType, text: &str) -> Result<Vec<Row>, Error> {
+    columns(qty)?;
+    let mut rows: Vec<Row> = Vec::default();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let values: Vec<&str> = line
+            .split([',', ';', '\t', ' '])
+            .filter(|v| !v.is_empty())
+            .collect();
+        if rows.is_empty() && values.iter().any(|v| v.parse::<f64>().is_err()) {
+            continue;
+        }
+        rows.push(Row {
+            line: i + 1,
+            values: values.iter().map(|v| v.to_string()).collect(),
+            point: parse_point(qty, &values).map_err(|e| e.to_string()),
+        });
+    }
+    Ok(rows)
+}
+
+// #Definition
+//      query_list tests the points of a list against a MOC.
+// #Args
+//  *   `id`: the id of the MOC in the store
+//  *   `rows`: the list, read by parse_list for the type of the MOC
+// #Returns
+//      Whether each point is in the MOC, None for the rows that could not be read.
+// #Errors
+//      Error if the MOC is not in the store or can't be queried (F-MOC).
+pub(crate) fn query_list(id: usize, rows: &[Row]) -> Result<Vec<Option<bool>>, Error> {
+    let points: Vec<Point> = rows
+        .iter()
+        .filter_map(|r| r.point.as_ref().ok().copied())
+        .collect();
+    let mut inside = contains(id, &points)?.into_iter();
+    Ok(rows
+        .iter()
+        .map(|r| match r.point {
+            Ok(_) => inside.next(),
+            Err(_) => None,
+        })
+        .collect())
+}
+
+// #Definition
+//      to_csv writes the results of a list in CSV: the line, the values of the point and
+//      whether it is in the MOC ("true", "false" or why the row could not be read).
+// #Args
+//  *   `qty`: the type of the MOC the points were tested against
+//  *   `rows`: the list
+//  *   `results`: the results of query_list
+// #Errors
+//      Error if the MOC type can't be queried (F-MOC) or if the CSV can't be written.
+pub(crate) fn to_csv(
+    qty: MocQType,
+    rows: &[Row],
+    results: &[Option<bool>],
+) -> Result<Vec<u8>, Error> {
+    let columns = columns(qty)?;
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let header = ["line"].iter().chain(columns).chain(&["inside"]);
+    writer.write_record(header)?;
+    for (row, result) in rows.iter().zip(results) {
+        let mut record = vec![row.line.to_string()];
+        record.extend(row.values.iter().take(columns.len()).cloned());
+        record.resize(columns.len() + 1, String::default());
+        record.push(match (result, &row.point) {
+            (Some(inside), _) => inside.to_string(),
+            (None, Err(e)) => e.clone(),
+            (None, Ok(_)) => String::default(),
+        });
+        writer.write_record(&record)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| Error::Parse(format!("CSV could not be written: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::creation::from_decimal_jd_coo;
+
+    #[test]
+    fn ra_is_wrapped_for_every_moc_type() {
+        let smoc = U64MocStore.from_cone(0.5, 10.0, 1.0, 10, 2).unwrap();
+        let stmoc = from_decimal_jd_coo(20, 10, vec![2_451_545.0, 0.5, 10.0]).unwrap();
+        for ra in ["0.5", "360.5", "-359.5", "720.5"] {
+            let point = parse_point(MocQType::Space, &[ra, "10"]).unwrap();
+            assert_eq!(point.pos, Some((0.5, 10.0)), "{ra}");
+            assert_eq!(contains(smoc, &[point]).unwrap(), vec![true], "{ra}");
+            let point = parse_point(MocQType::TimeSpace, &[ra, "10", "2451545"]).unwrap();
+            assert_eq!(contains(stmoc, &[point]).unwrap(), vec![true], "{ra}");
+        }
+        let point = parse_point(MocQType::Space, &["-1e-20", "0"]).unwrap();
+        assert_eq!(point.pos, Some((0.0, 0.0)));
+        assert!(parse_point(MocQType::Space, &["10", "91"]).is_err());
+        U64MocStore.drop(smoc).unwrap();
+        U64MocStore.drop(stmoc).unwrap();
+    }
+}
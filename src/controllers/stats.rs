@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+
+use moc::storage::u64idx::U64MocStore;
+
+use crate::error::Error;
+use crate::utils::provenance::{self, Provenance};
+
+// The number of cells of the whole sky at the maximum depth of an S-MOC (29), 12 * 4^29.
+const N_CELLS_MAX_DEPTH: f64 = (12_u64 << 58) as f64;
+pub(crate) const SQ_DEG_PER_SR: f64 = (180.0 / PI) * (180.0 / PI);
+
+// The SmocStats struct, figures giving the size and the complexity of an S-MOC.
+#[derive(Clone, PartialEq, Default)]
+pub(crate) struct SmocStats {
+    pub(crate) depth: u8,
+    // The area covered, in steradians.
+    pub(crate) area_sr: f64,
+    pub(crate) n_ranges: u32,
+    // The number of cells at each depth, from 0 to the depth of the MOC.
+    pub(crate) cells_per_depth: Vec<usize>,
+    // The size in bytes of the MOC written in each format, as downloaded.
+    pub(crate) sizes: Vec<(&'static str, usize)>,
+}
+
+impl SmocStats {
+    pub(crate) fn area_deg2(&self) -> f64 {
+        self.area_sr * SQ_DEG_PER_SR
+    }
+
+    pub(crate) fn area_arcmin2(&self) -> f64 {
+        self.area_deg2() * 3600.0
+    }
+
+    pub(crate) fn n_cells(&self) -> usize {
+        self.cells_per_depth.iter().sum()
+    }
+}
+
+// #Definition
+//      area_sr gives the area covered by an S-MOC, in steradians.
+// #Errors
+//      Error if the MOC is not an S-MOC of the store.
+pub(crate) fn area_sr(id: usize) -> Result<f64, Error> {
+    Ok(4.0 * PI * U64MocStore.get_ranges_sum(id)? as f64 / N_CELLS_MAX_DEPTH)
+}
+
+// #Definition
+//      smoc_stats computes the figures of an S-MOC: its area, its number of ranges, its number
+//      of cells per depth and its size in FITS (with its provenance), ASCII and JSON.
+// #Args
+//  *   `id`: the S-MOC's id in the store
+//  *   `provenance`: the provenance written in the FITS file
+// #Errors
+//      Error if the MOC is not an S-MOC of the store or can't be serialized.
+pub(crate) fn smoc_stats(id: usize, provenance: &Provenance) -> Result<SmocStats, Error> {
+    let depth = U64MocStore.get_smoc_depth(id)?;
+    let area_sr = area_sr(id)?;
+    let n_ranges = U64MocStore.get_n_ranges(id)?;
+    let mut cells_per_depth = vec![0; depth as usize + 1];
+    for uniq in U64MocStore.to_uniq_hpx(id)? {
+        let (d, _) = healpix::nested::from_uniq_ivoa(uniq);
+        if let Some(n) = cells_per_depth.get_mut(d as usize) {
+            *n += 1;
+        }
+    }
+    let sizes = vec![
+        ("FITS", provenance::to_fits_buff(id, provenance)?.len()),
+        ("ASCII", U64MocStore.to_ascii_str(id, None)?.len()),
+        ("JSON", U64MocStore.to_json_str(id, None)?.len()),
+    ];
+    Ok(SmocStats {
+        depth,
+        area_sr,
+        n_ranges,
+        cells_per_depth,
+        sizes,
+    })
+}
+
+// #Definition
+//      fmt_size formats a size in bytes with a binary unit, e.g. "2.5 KiB".
+pub(crate) fn fmt_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
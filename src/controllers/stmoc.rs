@@ -0,0 +1,50 @@
+use std::ops::Range;
+
+use crate::controllers::creation::JD_TO_USEC;
+use crate::error::Error;
+use moc::moc::range::RangeMOC;
+use moc::qty::Hpx;
+use moc::storage::u64idx::U64MocStore;
+
+// #Definition
+//      time_coverage gives the time ranges (in microseconds since JD=0) during which the
+//      ST-MOC covers some part of the sky, i.e. the space fold of the whole sky.
+// #Args
+//  *   `id`: the ST-MOC's id in the store
+// #Errors
+//      Error if the MOC is not an ST-MOC of the store.
+pub(crate) fn time_coverage(id: usize) -> Result<Vec<Range<u64>>, Error> {
+    let empty = U64MocStore.new_empty_smoc(0)?;
+    let all_sky = U64MocStore.not(empty);
+    U64MocStore.drop(empty)?;
+    let all_sky = all_sky?;
+    let tmoc = U64MocStore.space_fold(all_sky, id);
+    U64MocStore.drop(all_sky)?;
+    let tmoc = tmoc?;
+    let ranges = U64MocStore.to_ranges(tmoc);
+    U64MocStore.drop(tmoc)?;
+    Ok(ranges?)
+}
+
+// #Definition
+//      space_coverage gives the sky observed during the given time range, i.e. the time fold of
+//      the ST-MOC with a T-MOC made of this range (at the time depth of the ST-MOC).
+// #Args
+//  *   `id`: the ST-MOC's id in the store
+//  *   `jd_range`: the time range, in decimal JD
+// #Errors
+//      Error if the MOC is not an ST-MOC of the store.
+pub(crate) fn space_coverage(
+    id: usize,
+    jd_range: Range<f64>,
+) -> Result<RangeMOC<u64, Hpx<u64>>, Error> {
+    let (t_depth, _) = U64MocStore.get_stmoc_depths(id)?;
+    let usec_range = (jd_range.start * JD_TO_USEC) as u64..(jd_range.end * JD_TO_USEC) as u64;
+    let tmoc = U64MocStore.from_microsec_ranges_since_jd0(t_depth, std::iter::once(usec_range))?;
+    let smoc = U64MocStore.time_fold(tmoc, id);
+    U64MocStore.drop(tmoc)?;
+    let smoc = smoc?;
+    let copy = U64MocStore.get_smoc_copy(smoc);
+    U64MocStore.drop(smoc)?;
+    Ok(copy?)
+}
@@ -0,0 +1,84 @@
+use std::{fmt, sync::PoisonError};
+
+// The Error enum, the crate-wide error type.
+// Errors are surfaced to the user (error dialog or label) instead of aborting the app.
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be opened, read or written.
+    Io(std::io::Error),
+    /// A file could not be downloaded.
+    Http(String),
+    /// A file content or a user input could not be parsed.
+    Parse(String),
+    /// The type of a MOC can't be told from its content.
+    Ambiguous(String),
+    /// The operation is not available for the MOC quantity (or pair of quantities).
+    UnsupportedQty(String),
+    /// A store lock is poisoned, a thread panicked while writing in it.
+    StoreLock,
+    /// The user cancelled the action (e.g. closed a file dialog).
+    Cancelled,
+    /// Something could not be found, e.g. a MOC id in the registry.
+    NotFound(String),
+    /// An error coming from the MOC library.
+    Moc(String),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Http(msg) => write!(f, "Download error: {msg}"),
+            Self::Parse(msg) => write!(f, "Parse error: {msg}"),
+            Self::Ambiguous(msg) => write!(f, "Ambiguous MOC type: {msg}"),
+            Self::UnsupportedQty(msg) => write!(f, "{msg}"),
+            Self::StoreLock => write!(f, "Store lock poisoned"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::NotFound(what) => write!(f, "{what} not found"),
+            Self::Moc(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => Self::Io(e),
+            e => Self::Parse(format!("Invalid zip archive: {e}")),
+        }
+    }
+}
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        if !e.is_io_error() {
+            return Self::Parse(format!("Invalid csv: {e}"));
+        }
+        match e.into_kind() {
+            csv::ErrorKind::Io(e) => Self::Io(e),
+            kind => Self::Parse(format!("Invalid csv: {kind:?}")),
+        }
+    }
+}
+// The MOC library returns its errors as strings.
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Self::Moc(msg)
+    }
+}
+impl<T> From<PoisonError<T>> for Error {
+    fn from(_: PoisonError<T>) -> Self {
+        Self::StoreLock
+    }
+}
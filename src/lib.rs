@@ -1,7 +1,10 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 pub mod app;
-pub mod utils;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 pub mod controllers;
+pub mod error;
+pub mod utils;
 pub mod windows;
-pub use app::FileApp;
\ No newline at end of file
+pub use app::FileApp;
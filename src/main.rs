@@ -4,8 +4,26 @@
 // When compiling natively.
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    use rust_moc_gui::cli::{run, Args, Mode};
+    use structopt::StructOpt;
+
+    // The release builds have no console of their own on Windows, the output of the command
+    // line (including the help and the argument errors) goes to the console it is run from.
+    #[cfg(windows)]
+    if std::env::args_os().len() > 1 {
+        attach_parent_console();
+    }
     tracing_subscriber::fmt::init();
 
+    // `rust_moc_gui cli ...` runs headless, without any subcommand the GUI is launched.
+    if let Some(Mode::Cli(cmd)) = Args::from_args().mode {
+        if let Err(e) = run(cmd) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let options = eframe::NativeOptions {
         default_theme: eframe::Theme::Light,
         ..Default::default()
@@ -13,10 +31,26 @@ fn main() {
     let _ = eframe::run_native(
         "MOC GUI",
         options,
-        Box::new(|_| Box::new(rust_moc_gui::FileApp::default())),
+        Box::new(|cc| Box::new(rust_moc_gui::FileApp::new(cc))),
     ); // This is supposed to work, if it does not, then there is a greater error, please open an issue.
 }
 
+// #Definition
+//      attach_parent_console attaches the process to the console of its parent, if it has one,
+//      so that stdout and stderr are shown in it.
+#[cfg(windows)]
+fn attach_parent_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // Fails if the process already has a console (debug builds) or the parent has none.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 // when compiling to web using trunk.
 #[cfg(target_arch = "wasm32")]
 fn main() {
@@ -36,7 +70,7 @@ fn main() {
         eframe::start_web(
             "moccanvas", // hardcode it
             web_options,
-            Box::new(|_| Box::new(rust_moc_gui::FileApp::default())),
+            Box::new(|cc| Box::new(rust_moc_gui::FileApp::new(cc))),
         )
         .await
         .expect("failed to start eframe");
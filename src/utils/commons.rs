@@ -1,6 +1,14 @@
-use crate::utils::namestore::add;
+use crate::error::Error;
+use crate::utils::compression::{decompress, unpack, COMPRESSED_EXTENSIONS};
+use crate::utils::detect::detect_reading;
+use crate::utils::provenance::fits_metadata;
+use moc::qty::Frequency;
 use moc::storage::u64idx::{common::MocQType, U64MocStore};
-use std::{str::from_utf8_unchecked, collections::BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::from_utf8,
+    sync::{Arc, Mutex},
+};
 
 #[cfg(target_arch = "wasm32")]
 use js_sys::{Array, Uint8Array};
@@ -9,7 +17,11 @@ use rfd::AsyncFileDialog;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsCast;
 #[cfg(target_arch = "wasm32")]
-use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+use wasm_bindgen_futures::JsFuture;
+#[cfg(target_arch = "wasm32")]
+use web_sys::{
+    Blob, BlobPropertyBag, HtmlAnchorElement, Request, RequestInit, RequestMode, Response, Url,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
@@ -19,13 +31,6 @@ use std::{
     io::{Read, Write},
 };
 
-// #Definition
-//      desc
-// #Args
-//  *   `arg`: desc
-// #Errors
-//      error
-
 pub(crate) const HALF_PI: f64 = 0.5 * std::f64::consts::PI;
 pub(crate) const TWICE_PI: f64 = 2.0 * std::f64::consts::PI;
 
@@ -34,41 +39,36 @@ pub(crate) const TWICE_PI: f64 = 2.0 * std::f64::consts::PI;
 
 // #Definition
 //      type_reading reads a file and loads a MOC from it.
-//      3 file types can be opened : fits, json and txt (or ASCII).
+//      3 file types can be opened : fits, json and txt (or ASCII), possibly gzip or bzip2
+//      compressed.
 // #Args
 //  *   `rtype`: the string sent that matches the extension of a given file
 //  *   `moct`: the type of moc that was sent, unused with fits
 //  *   `data`: the data contained in the file in a &[u8] form
 // #Errors
-//      Errors from this function are unreachable, except for the unsafe calls which can return an error message.
-//      Errors are not supposed to happen.
-pub(crate) fn type_reading(rtype: &str, moct: &MocQType, data: &[u8]) -> Result<usize, String> {
-    match rtype {
-        "fits" => U64MocStore.load_from_fits(data),
+//      Error if the file type is unknown or if a json/ASCII file is not valid UTF-8.
+//      Error if the data can't be decompressed.
+//      Error if the MOC can't be parsed from the data.
+pub(crate) fn type_reading(rtype: &str, moct: &MocQType, data: &[u8]) -> Result<usize, Error> {
+    let data = &*decompress(data)?;
+    let text = || from_utf8(data).map_err(|e| Error::Parse(format!("Invalid UTF-8 content: {e}")));
+    let id = match rtype {
+        "fits" => U64MocStore.load_from_fits(data)?,
         "json" => match moct {
-            MocQType::Space => {
-                U64MocStore.load_smoc_from_json(unsafe { from_utf8_unchecked(data) })
-            }
-            MocQType::Time => U64MocStore.load_tmoc_from_json(unsafe { from_utf8_unchecked(data) }),
-            MocQType::TimeSpace => {
-                U64MocStore.load_stmoc_from_json(unsafe { from_utf8_unchecked(data) })
-            }
-            MocQType::Frequency => unreachable!(),
+            MocQType::Space => U64MocStore.load_smoc_from_json(text()?)?,
+            MocQType::Time => U64MocStore.load_tmoc_from_json(text()?)?,
+            MocQType::TimeSpace => U64MocStore.load_stmoc_from_json(text()?)?,
+            MocQType::Frequency => U64MocStore.load_fmoc_from_json(text()?)?,
         },
         "txt" | "ascii" => match moct {
-            MocQType::Space => {
-                U64MocStore.load_smoc_from_ascii(unsafe { from_utf8_unchecked(data) })
-            }
-            MocQType::Time => {
-                U64MocStore.load_tmoc_from_ascii(unsafe { from_utf8_unchecked(data) })
-            }
-            MocQType::TimeSpace => {
-                U64MocStore.load_stmoc_from_ascii(unsafe { from_utf8_unchecked(data) })
-            }
-            MocQType::Frequency => unreachable!(),
+            MocQType::Space => U64MocStore.load_smoc_from_ascii(text()?)?,
+            MocQType::Time => U64MocStore.load_tmoc_from_ascii(text()?)?,
+            MocQType::TimeSpace => U64MocStore.load_stmoc_from_ascii(text()?)?,
+            MocQType::Frequency => U64MocStore.load_fmoc_from_ascii(text()?)?,
         },
-        _ => unreachable!(), // since file_input.set_attribute("accept", ".fits, .json, .ascii, .txt");
-    }
+        _ => return Err(Error::Parse(format!("Unsupported file type: '{rtype}'"))),
+    };
+    Ok(id)
 }
 
 // #Definition
@@ -82,33 +82,21 @@ pub(crate) fn type_reading(rtype: &str, moct: &MocQType, data: &[u8]) -> Result<
 // #Errors
 //      If the file is unable to be written we return an error.
 //      If the file can't be created we return an error.
-//      Path = none is an error coming from the use of rfd.
+//      Path = none means the user closed the rfd dialog, Error::Cancelled is returned.
 #[cfg(not(target_arch = "wasm32"))]
-pub fn to_file(name: &str, ext: &str, _mime: &str, data: Box<[u8]>) -> Result<(), String> {
+pub fn to_file(name: &str, ext: &str, _mime: &str, data: Box<[u8]>) -> Result<(), Error> {
     let path = rfd::FileDialog::new()
         .set_directory("../")
         .set_file_name(&(name.to_owned() + ext))
-        .save_file();
-    if let Some(path) = path {
-        let file = File::create(path);
-        match file {
-            Ok(_) => {
-                if file.unwrap().write_all(&data).is_err() {
-                    return Err("Error while reading file".to_string());
-                }
-            }
-            Err(_) => return Err("Error during file creation".to_string()),
-        };
-    } else {
-        // path is equal to none
-        return Err("Canceled".to_string());
-    }
+        .save_file()
+        .ok_or(Error::Cancelled)?;
+    File::create(path)?.write_all(&data)?;
 
     Ok(())
 }
 // Same as above but for WASM32 target
 #[cfg(target_arch = "wasm32")]
-pub fn to_file(name: &str, ext: &str, mime: &str, data: Box<[u8]>) -> Result<(), String> {
+pub fn to_file(name: &str, ext: &str, mime: &str, data: Box<[u8]>) -> Result<(), Error> {
     // Set filename
     let mut filename = String::from(name);
     if !filename.ends_with(ext) {
@@ -122,96 +110,103 @@ pub fn to_file(name: &str, ext: &str, mime: &str, data: Box<[u8]>) -> Result<(),
     blob_prop.type_(mime);
 
     let blob = Blob::new_with_u8_array_sequence_and_options(&bytes, &blob_prop)
-        .map_err(|e| e.as_string())
-        .unwrap();
+        .map_err(|_| web_err("Blob creation has failed"))?;
 
     // Generate the URL with the attached data
-    let url = Url::create_object_url_with_blob(&blob)
-        .map_err(|e| e.as_string())
-        .unwrap();
+    let url =
+        Url::create_object_url_with_blob(&blob).map_err(|_| web_err("URL creation has failed"))?;
 
     // Create a temporary download link
-    let window = web_sys::window().expect("no global `window` exists");
-    let document = window.document().expect("should have a document on window");
-    let body = document.body().expect("document should have a body");
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| web_err("No document on window"))?;
+    let body = document
+        .body()
+        .ok_or_else(|| web_err("Document has no body"))?;
     let anchor: HtmlAnchorElement = document
         .create_element("a")
-        .unwrap()
+        .map_err(|_| web_err("Anchor creation has failed"))?
         .dyn_into()
-        .map_err(|e| e.as_string())
-        .unwrap();
+        .map_err(|_| web_err("Anchor creation has failed"))?;
     anchor.set_href(&url);
     anchor.set_download(&filename);
     body.append_child(&anchor)
-        .map_err(|_| "Body child appending has failed".to_string())?;
+        .map_err(|_| web_err("Body child appending has failed"))?;
     // Simulate a click
     anchor.click();
     // Clean
     body.remove_child(&anchor)
-        .map_err(|_| "Body child removing has failed".to_string())?;
-    Url::revoke_object_url(&url).map_err(|_| "URL revoking object url has failed".to_string())?;
+        .map_err(|_| web_err("Body child removing has failed"))?;
+    Url::revoke_object_url(&url).map_err(|_| web_err("URL revoking object url has failed"))?;
     Ok(())
 }
 
+// A MOC loaded from a picked file, waiting to be added to the registry by the app.
+pub(crate) struct LoadedMoc {
+    // The path of the file (its name on web).
+    pub(crate) source: String,
+    pub(crate) name: String,
+    // The id of the MOC in the store.
+    pub(crate) id: usize,
+    // The keywords of the FITS header, see fits_metadata.
+    pub(crate) metadata: BTreeMap<String, String>,
+}
+// The MOCs loaded from the files picked in the "Open" dialog, shared with the async dialog on
+// web: the app adds them to the registry once loaded.
+pub(crate) type Loaded = Arc<Mutex<Vec<LoadedMoc>>>;
+
 // #Definition
-//      load loads a file and uses type_reading to make that data into a usable MOC object.
-//      It then adds it to the MOC store.
+//      load loads files and uses type_reading to make that data into a usable MOC object.
+//      It then adds it to the MOC store and puts it in `loaded`.
+//      Compressed files are decompressed, every MOC of a zip archive is loaded.
 // #Args
 //  *   `rtype`: the type of the diffrent files that are being imported
-//  *   `moct`: the moc qty type
+//  *   `moct`: the moc qty type, None to detect the format and the qty from the content
+//  *   `loaded`: where to put the loaded MOCs
 // #Errors
 //      Error if file can't be opened
 //      Error if file name can't be read correctly
 //      Error if file can't be read correctly
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn load(rtype: &[&str], moct: MocQType) -> Result<(), String> {
-    let reading = if rtype.contains(&"fits") {
-        "fits"
-    } else if rtype.contains(&"json") {
-        "json"
-    } else if rtype.contains(&"ascii") {
-        "ascii"
-    } else {
-        unreachable!()
-    };
+pub(crate) fn load(rtype: &[&str], moct: Option<MocQType>, loaded: &Loaded) -> Result<(), Error> {
+    let reading = reading_type(rtype)?;
 
-    if let Some(handle) = FileDialog::new().add_filter("MOCs", rtype).pick_files() {
-        for path in handle {
-            let mut file = File::open(&path).map_err(|_| "Error while opening file".to_string())?;
-            //Reads name and adds it to be shown to user
-            let file_name = path
-                .file_name()
-                .ok_or_else(|| "error while reading file name".to_string())?
-                .to_str()
-                .ok_or_else(|| "error while reading file name".to_string())?;
-            //Reads file contents and adds it to the data
-            let mut file_content = Vec::default();
-            file.read_to_end(&mut file_content)
-                .map_err(|e| format!("Error while reading file: {e}"))?;
-
-            if let Ok(id) = type_reading(reading, &moct, file_content.as_slice()) {
-                add(file_name, id)?;
-            }
+    let handle = FileDialog::new()
+        .add_filter("MOCs", &with_compressed(rtype))
+        .pick_files()
+        .ok_or(Error::Cancelled)?;
+    for path in handle {
+        let mut file = File::open(&path)?;
+        //Reads name and adds it to be shown to user
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| Error::Parse("error while reading file name".to_string()))?;
+        //Reads file contents and adds it to the data
+        let mut file_content = Vec::default();
+        file.read_to_end(&mut file_content)?;
+
+        for (name, content) in unpack(file_name, file_content)? {
+            let id = read_moc(reading, moct, &content)?;
+            loaded.lock()?.push(LoadedMoc {
+                source: path.display().to_string(),
+                name,
+                id,
+                metadata: fits_metadata(&content),
+            });
         }
     }
     Ok(())
 }
 // Same as above but for WASM32 target
 #[cfg(target_arch = "wasm32")]
-pub(crate) fn load(rtype: &[&str], moct: MocQType) -> Result<(), String> {
+pub(crate) fn load(rtype: &[&str], moct: Option<MocQType>, loaded: &Loaded) -> Result<(), Error> {
     let task = AsyncFileDialog::new()
-        .add_filter("MOCs", rtype)
+        .add_filter("MOCs", &with_compressed(rtype))
         .pick_files();
 
-    let reading = if rtype.contains(&"fits") {
-        "fits"
-    } else if rtype.contains(&"json") {
-        "json"
-    } else if rtype.contains(&"ascii") {
-        "ascii"
-    } else {
-        unreachable!()
-    };
+    let reading = reading_type(rtype)?;
+    let loaded = loaded.clone();
 
     execute(async move {
         let handle = task.await;
@@ -223,14 +218,279 @@ pub(crate) fn load(rtype: &[&str], moct: MocQType) -> Result<(), String> {
                 let file_name = path.file_name();
                 //Reads file contents and adds it to the data
                 let file_content = path.read().await;
-                if let Ok(id) = type_reading(reading, &moct, file_content.as_slice()) {
-                    add(&file_name, id);
+                let files = match unpack(&file_name, file_content) {
+                    Ok(files) => files,
+                    Err(e) => {
+                        err(&e);
+                        continue;
+                    }
+                };
+                for (name, content) in files {
+                    let moc = read_moc(reading, moct, &content).map(|id| LoadedMoc {
+                        source: file_name.clone(),
+                        name,
+                        id,
+                        metadata: fits_metadata(&content),
+                    });
+                    match (moc, loaded.lock()) {
+                        (Ok(moc), Ok(mut loaded)) => loaded.push(moc),
+                        (Err(e), _) => err(&e),
+                        (_, Err(e)) => err(&Error::from(e)),
+                    }
                 }
             }
         }
     });
     Ok(())
 }
+
+// #Definition
+//      take_loaded takes the MOCs put in `loaded` by load.
+pub(crate) fn take_loaded(loaded: &Loaded) -> Vec<LoadedMoc> {
+    loaded
+        .lock()
+        .map(|mut loaded| std::mem::take(&mut *loaded))
+        .unwrap_or_default()
+}
+
+// #Definition
+//      read_moc loads a MOC from a file content, with the given qty or, if None, detecting
+//      the format and the qty from the content.
+fn read_moc(reading: &str, moct: Option<MocQType>, data: &[u8]) -> Result<usize, Error> {
+    match moct {
+        Some(moct) => type_reading(reading, &moct, data),
+        None => detect_reading(data).map_err(|e| match e {
+            Error::Ambiguous(msg) => {
+                Error::Ambiguous(format!("{msg}, use \"Files > Open as\" to give its type"))
+            }
+            e => e,
+        }),
+    }
+}
+// #Definition
+//      with_compressed gives the accepted extensions along with the compressed ones.
+fn with_compressed<'a>(rtype: &[&'a str]) -> Vec<&'a str> {
+    rtype.iter().copied().chain(COMPRESSED_EXTENSIONS).collect()
+}
+// #Definition
+//      reading_type gives the type of reading to do from the accepted extensions.
+fn reading_type(rtype: &[&str]) -> Result<&'static str, Error> {
+    if rtype.contains(&"fits") {
+        Ok("fits")
+    } else if rtype.contains(&"json") {
+        Ok("json")
+    } else if rtype.contains(&"ascii") {
+        Ok("ascii")
+    } else {
+        Err(Error::Parse(format!("Unsupported file types: {rtype:?}")))
+    }
+}
+
+// A file picked from a file dialog (its name and its content), shared with the async dialog
+// on web: the UI takes it once it has been read.
+pub(crate) type PickedFile = Arc<Mutex<Option<(String, Vec<u8>)>>>;
+
+// #Definition
+//      pick_file opens a file dialog and puts the name and the content of the picked file in
+//      `picked`, nothing happens if the dialog is closed.
+// #Args
+//  *   `filter`: the name of the file dialog filter
+//  *   `extensions`: the extensions accepted by the filter
+//  *   `picked`: where to put the picked file
+// #Errors
+//      Error if the file can't be read.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn pick_file(
+    filter: &str,
+    extensions: &[&str],
+    picked: &PickedFile,
+) -> Result<(), Error> {
+    let Some(path) = FileDialog::new().add_filter(filter, extensions).pick_file() else {
+        return Ok(());
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| Error::Parse("error while reading file name".to_string()))?
+        .to_string();
+    let file_content = std::fs::read(&path)?;
+    *picked.lock()? = Some((file_name, file_content));
+    Ok(())
+}
+// Same as above but for WASM32 target, the file is put in `picked` once read.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn pick_file(
+    filter: &str,
+    extensions: &[&str],
+    picked: &PickedFile,
+) -> Result<(), Error> {
+    let task = AsyncFileDialog::new()
+        .add_filter(filter, extensions)
+        .pick_file();
+    let picked = picked.clone();
+    execute(async move {
+        if let Some(file) = task.await {
+            let file_content = file.read().await;
+            match picked.lock() {
+                Ok(mut picked) => *picked = Some((file.file_name(), file_content)),
+                Err(e) => err(&Error::from(e)),
+            }
+        }
+    });
+    Ok(())
+}
+
+// #Definition
+//      take_picked takes the file put in `picked` by pick_file, if any.
+pub(crate) fn take_picked(picked: &PickedFile) -> Option<(String, Vec<u8>)> {
+    picked.lock().ok().and_then(|mut picked| picked.take())
+}
+
+// #Definition
+//      pick_path opens a file dialog and gives the path of the picked file without reading it,
+//      e.g. for a catalogue too large to be held in memory.
+// #Returns
+//      None if the dialog is closed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn pick_path(filter: &str, extensions: &[&str]) -> Option<std::path::PathBuf> {
+    FileDialog::new().add_filter(filter, extensions).pick_file()
+}
+
+// #Definition
+//      save_path opens a file dialog and gives the path of the file to write, see pick_path.
+// #Returns
+//      None if the dialog is closed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save_path(name: &str) -> Option<std::path::PathBuf> {
+    FileDialog::new()
+        .set_directory("../")
+        .set_file_name(name)
+        .save_file()
+}
+
+// #Definition
+//      dropped_file_content gives the name and the content of a file dropped on the app.
+//      On native the file is read from its path, on web its content is given by the browser.
+// #Args
+//  *   `file`: the dropped file
+// #Errors
+//      Error if the file can't be read.
+pub(crate) fn dropped_file_content(file: &egui::DroppedFile) -> Result<(String, Vec<u8>), Error> {
+    let name = match &file.path {
+        Some(path) if file.name.is_empty() => path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| Error::Parse("error while reading file name".to_string()))?
+            .to_string(),
+        _ => file.name.clone(),
+    };
+    let data = match (&file.bytes, &file.path) {
+        (Some(bytes), _) => bytes.to_vec(),
+        (None, Some(path)) => std::fs::read(path)?,
+        (None, None) => return Err(Error::NotFound(format!("Content of '{name}'"))),
+    };
+    Ok((name, data))
+}
+
+// A file downloaded from a URL (its name and its content) or the download error, shared with
+// the download thread (native) or task (web): the UI takes it once the download is over.
+pub(crate) type Fetched = Arc<Mutex<Option<Result<(String, Vec<u8>), Error>>>>;
+
+// #Definition
+//      fetch_url downloads a file in the background and puts its name and content, or the
+//      error, in `fetched`. The app is repainted once the download is over.
+// #Args
+//  *   `ctx`: the app's context
+//  *   `url`: the URL of the file
+//  *   `fetched`: where to put the downloaded file
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn fetch_url(ctx: &egui::Context, url: &str, fetched: &Fetched) {
+    let (ctx, url, fetched) = (ctx.clone(), url.to_string(), fetched.clone());
+    std::thread::spawn(move || {
+        let result = download(&url);
+        if let Ok(mut fetched) = fetched.lock() {
+            *fetched = Some(result);
+        }
+        ctx.request_repaint();
+    });
+}
+// Same as above but for WASM32 target, the file is fetched by the browser.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn fetch_url(ctx: &egui::Context, url: &str, fetched: &Fetched) {
+    let (ctx, url, fetched) = (ctx.clone(), url.to_string(), fetched.clone());
+    execute(async move {
+        let result = download(&url).await;
+        if let Ok(mut fetched) = fetched.lock() {
+            *fetched = Some(result);
+        }
+        ctx.request_repaint();
+    });
+}
+
+// #Definition
+//      download downloads a file with an HTTP GET request.
+// #Args
+//  *   `url`: the URL of the file
+// #Errors
+//      Error if the request fails or if the server answers with an error status.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn download(url: &str) -> Result<(String, Vec<u8>), Error> {
+    let response = ureq::get(url).call().map_err(|e| match e {
+        ureq::Error::Status(code, response) => {
+            Error::Http(format!("{url}: {code} {}", response.status_text()))
+        }
+        ureq::Error::Transport(e) => Error::Http(e.to_string()),
+    })?;
+    let mut data = Vec::default();
+    response.into_reader().read_to_end(&mut data)?;
+    Ok((url_file_name(url), data))
+}
+// Same as above but for WASM32 target, using the fetch API of the browser.
+#[cfg(target_arch = "wasm32")]
+async fn download(url: &str) -> Result<(String, Vec<u8>), Error> {
+    let http_err = |msg: &str| Error::Http(format!("{url}: {msg}"));
+    let mut init = RequestInit::new();
+    init.method("GET").mode(RequestMode::Cors);
+    let request =
+        Request::new_with_str_and_init(url, &init).map_err(|_| http_err("invalid request"))?;
+    let window = web_sys::window().ok_or_else(|| web_err("No window"))?;
+    let response: Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|_| http_err("network error (the server may not allow cross-origin requests)"))?
+        .dyn_into()
+        .map_err(|_| http_err("invalid response"))?;
+    if !response.ok() {
+        return Err(http_err(&format!(
+            "{} {}",
+            response.status(),
+            response.status_text()
+        )));
+    }
+    let buffer = response
+        .array_buffer()
+        .map_err(|_| http_err("unreadable response"))?;
+    let buffer = JsFuture::from(buffer)
+        .await
+        .map_err(|_| http_err("unreadable response"))?;
+    Ok((url_file_name(url), Uint8Array::new(&buffer).to_vec()))
+}
+
+// #Definition
+//      url_file_name gives the name of a downloaded file: the last segment of the URL path,
+//      or the host if the path is empty.
+// #Args
+//  *   `url`: the URL of the file
+pub(crate) fn url_file_name(url: &str) -> String {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let path = url.split_once("://").map_or(url, |(_, path)| path);
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("moc")
+        .to_string()
+}
+
 #[cfg(target_arch = "wasm32")]
 fn execute<F: std::future::Future<Output = ()> + 'static>(f: F) {
     wasm_bindgen_futures::spawn_local(f);
@@ -245,30 +505,74 @@ pub fn fmt_qty(typ: MocQType) -> String {
         MocQType::Space => "Space".to_string(),
         MocQType::Time => "Time".to_string(),
         MocQType::TimeSpace => "Timespace".to_string(),
-        MocQType::Frequency => unreachable!(),
+        MocQType::Frequency => "Frequency".to_string(),
     }
 }
 
+// #Definition
+//      same_qty tells if two MOC types are the same, MocQType not implementing PartialEq.
+pub(crate) fn same_qty(left: MocQType, right: MocQType) -> bool {
+    matches!(
+        (left, right),
+        (MocQType::Space, MocQType::Space)
+            | (MocQType::Time, MocQType::Time)
+            | (MocQType::TimeSpace, MocQType::TimeSpace)
+            | (MocQType::Frequency, MocQType::Frequency)
+    )
+}
+
+// #Definition
+//      freq_range_hz gives the frequency range (in Hz) covered by an F-MOC, None if it is empty.
+// #Errors
+//      Error if the MOC is not in the store.
+pub(crate) fn freq_range_hz(id: usize) -> Result<Option<(f64, f64)>, Error> {
+    let min = U64MocStore.get_1st_axis_min(id)?;
+    let max = U64MocStore.get_1st_axis_max(id)?;
+    Ok(min.zip(max).map(|(min, max)| {
+        (
+            Frequency::<u64>::hash2freq(min),
+            Frequency::<u64>::hash2freq(max),
+        )
+    }))
+}
+
+// #Definition
+//      err shows the given error in a message dialog, a cancellation is not shown.
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn err(msg: &str) {
+pub(crate) fn err(e: &Error) {
     use rfd::MessageDialog;
 
+    if matches!(e, Error::Cancelled) {
+        return;
+    }
     let m = MessageDialog::new()
         .set_buttons(rfd::MessageButtons::Ok)
         .set_title("Error !")
-        .set_description(msg);
+        .set_description(&e.to_string());
     m.show();
 }
 
+// #Definition
+//      err shows the given error in a message dialog, a cancellation is not shown.
 #[cfg(target_arch = "wasm32")]
-pub(crate) fn err(msg: &str) {
+pub(crate) fn err(e: &Error) {
     use rfd::AsyncMessageDialog;
 
+    if matches!(e, Error::Cancelled) {
+        return;
+    }
     let m = AsyncMessageDialog::new()
         .set_buttons(rfd::MessageButtons::Ok)
         .set_title("Error !")
-        .set_description(msg);
-    m.show();
+        .set_description(&e.to_string());
+    execute(async move {
+        m.show().await;
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn web_err(msg: &str) -> Error {
+    Error::Io(std::io::Error::new(std::io::ErrorKind::Other, msg))
 }
 
 pub fn set_open(open: &mut BTreeSet<String>, key: &'static str, is_open: bool) {
@@ -279,4 +583,128 @@ pub fn set_open(open: &mut BTreeSet<String>, key: &'static str, is_open: bool) {
     } else {
         open.remove(key);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    const MOC: &[u8] = b"s3/1-10";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    // #Definition
+    //      serve starts a local HTTP server answering every request from its path, it gives
+    //      the URL of the server.
+    fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                // The headers.
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let (status, headers, body) = match path {
+                    "/redirect" => ("302 Found", "Location: /mocs/moc.txt\r\n", Vec::new()),
+                    "/mocs/moc.txt" => ("200 OK", "", MOC.to_vec()),
+                    "/moc.txt.gz?format=gz&v=2" => ("200 OK", "", gzip(MOC)),
+                    "/encoded.txt" => ("200 OK", "Content-Encoding: gzip\r\n", gzip(MOC)),
+                    _ => ("404 Not Found", "", Vec::new()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn download_follows_redirects() {
+        let url = serve();
+        let (name, data) = download(&format!("{url}/redirect")).unwrap();
+        assert_eq!(data, MOC);
+        // The name comes from the requested URL.
+        assert_eq!(name, "redirect");
+    }
+
+    #[test]
+    fn download_fails_on_error_status() {
+        let url = serve();
+        match download(&format!("{url}/missing.fits")) {
+            Err(Error::Http(msg)) => assert!(msg.contains("404"), "{msg}"),
+            _ => panic!("a 404 must be an error"),
+        }
+    }
+
+    #[test]
+    fn download_compressed() {
+        let url = serve();
+        // A gzip file, decompressed when unpacked.
+        let (name, data) = download(&format!("{url}/moc.txt.gz?format=gz&v=2")).unwrap();
+        assert_eq!(name, "moc.txt.gz");
+        assert_eq!(
+            unpack(&name, data).unwrap(),
+            vec![(String::from("moc.txt"), MOC.to_vec())]
+        );
+        // A content encoded by the server, decoded by the client.
+        let (_, data) = download(&format!("{url}/encoded.txt")).unwrap();
+        assert_eq!(data, MOC);
+    }
+
+    #[test]
+    fn fetch_url_in_background() {
+        let url = serve();
+        let fetched = Fetched::default();
+        fetch_url(
+            &egui::Context::default(),
+            &format!("{url}/mocs/moc.txt"),
+            &fetched,
+        );
+        let start = Instant::now();
+        let result = loop {
+            if let Some(result) = fetched.lock().unwrap().take() {
+                break result;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "download timed out"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let (name, data) = result.unwrap();
+        assert_eq!((name.as_str(), data.as_slice()), ("moc.txt", MOC));
+    }
+
+    #[test]
+    fn file_names_from_urls() {
+        for (url, name) in [
+            ("http://host/dir/moc.fits?x=1&y=2", "moc.fits"),
+            ("https://host/moc.json#part", "moc.json"),
+            ("https://host/dir/", "dir"),
+            ("https://host", "host"),
+            ("https://host/cgi?file=/a/b.fits", "cgi"),
+        ] {
+            assert_eq!(url_file_name(url), name, "{url}");
+        }
+    }
+}
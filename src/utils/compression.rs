@@ -0,0 +1,104 @@
+use std::{
+    borrow::Cow,
+    io::{BufRead, Cursor, Read},
+};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use zip::ZipArchive;
+
+use crate::error::Error;
+
+// The first bytes of the supported compressed files.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+// The extensions of the compressed files, added to the file dialogs filters.
+pub(crate) const COMPRESSED_EXTENSIONS: [&str; 3] = ["gz", "bz2", "zip"];
+
+// #Definition
+//      decompress decompresses a gzip or bzip2 file content, any other content is given back
+//      as it is.
+// #Args
+//  *   `data`: the file content
+// #Errors
+//      Error if the content looks compressed but can't be decompressed.
+pub(crate) fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    let mut decompressed = Vec::default();
+    if data.starts_with(GZIP_MAGIC) {
+        MultiGzDecoder::new(data).read_to_end(&mut decompressed)?;
+    } else if data.starts_with(BZIP2_MAGIC) {
+        MultiBzDecoder::new(data).read_to_end(&mut decompressed)?;
+    } else {
+        return Ok(Cow::Borrowed(data));
+    }
+    Ok(Cow::Owned(decompressed))
+}
+
+// #Definition
+//      decompress_reader decompresses a gzip or bzip2 file content while it is read, any other
+//      content is read as it is. Unlike decompress, the content is never held in memory.
+// #Args
+//  *   `reader`: the reader of the file content
+// #Errors
+//      Error if the first bytes of the content can't be read.
+pub(crate) fn decompress_reader<'a, R: BufRead + Send + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn Read + Send + 'a>, Error> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if start.starts_with(BZIP2_MAGIC) {
+        Ok(Box::new(MultiBzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+// #Definition
+//      unpack gives the files contained in a file content: every file of a zip archive, or
+//      the file itself, decompressed if it is a gzip or bzip2 file.
+//      The names lose their compression extension (e.g. "moc.fits.gz" gives "moc.fits").
+// #Args
+//  *   `name`: the file name
+//  *   `data`: the file content
+// #Errors
+//      Error if the file or one of the archived files can't be decompressed.
+pub(crate) fn unpack(name: &str, data: Vec<u8>) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    let data = match decompress(&data)? {
+        Cow::Owned(decompressed) => decompressed,
+        Cow::Borrowed(_) => data,
+    };
+    if !data.starts_with(ZIP_MAGIC) {
+        return Ok(vec![(strip_extension(name), data)]);
+    }
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let mut files = Vec::default();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        // Directories and the metadata added by macOS are not MOCs.
+        if file.is_dir() || file.name().starts_with("__MACOSX/") {
+            continue;
+        }
+        let file_name = file
+            .enclosed_name()
+            .and_then(|path| path.file_name())
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| Error::Parse(format!("Invalid file name in '{name}'")))?
+            .to_string();
+        let mut content = Vec::default();
+        file.read_to_end(&mut content)?;
+        let content = decompress(&content)?.into_owned();
+        files.push((strip_extension(&file_name), content));
+    }
+    Ok(files)
+}
+
+// The name without its compression extension.
+fn strip_extension(name: &str) -> String {
+    [".gz", ".bz2"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+        .to_string()
+}
@@ -0,0 +1,242 @@
+use std::{collections::BTreeSet, str::from_utf8};
+
+use moc::storage::u64idx::common::MocQType;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::utils::commons::type_reading;
+use crate::utils::compression::decompress;
+
+// The first bytes of a FITS file.
+const FITS_MAGIC: &[u8] = b"SIMPLE  =";
+// The maximum depths of the MOC quantities.
+const HPX_MAX_DEPTH: u8 = 29;
+const FREQ_MAX_DEPTH: u8 = 59;
+const TIME_MAX_DEPTH: u8 = 61;
+
+// #Definition
+//      detect_reading loads a MOC from a file content (decompressed if it is a gzip or bzip2
+//      file), its format and its quantity being detected from the content:
+//      *   FITS files start with the FITS magic, the quantity is read from their header,
+//      *   JSON files start with '{' (an object of depths, possibly under a "s", "t" or "f" key)
+//          or '[' (an array of ST-MOC elements with "t" and "s" keys),
+//      *   ASCII files are made of depths and cells, possibly prefixed by "s", "t" or "f".
+//      JSON and ASCII MOCs without any prefix are S-MOCs, as in the MOC 1.x standard, if their
+//      depth is valid for an S-MOC. Deeper ones are ambiguous (the MOC library writes T-MOCs and
+//      F-MOCs without prefix), unless their depth is only valid for a T-MOC.
+// #Args
+//  *   `data`: the file content
+// #Errors
+//      Error::Ambiguous if the quantity can't be told from the content.
+//      Error if the content is not valid UTF-8 or is not a MOC.
+pub(crate) fn detect_reading(data: &[u8]) -> Result<usize, Error> {
+    let data = &*decompress(data)?;
+    if data.starts_with(FITS_MAGIC) {
+        //Qty::Space here is a default it is not actually used
+        return type_reading("fits", &MocQType::Space, data);
+    }
+    let text = from_utf8(data).map_err(|e| {
+        Error::Parse(format!(
+            "Not a FITS file and not valid UTF-8 (JSON or ASCII) either: {e}"
+        ))
+    })?;
+    let text = text.trim();
+    let (reading, qty, content) = if text.starts_with('{') || text.starts_with('[') {
+        let (qty, content) = detect_json(text)?;
+        ("json", qty, content)
+    } else {
+        let (qty, content) = detect_ascii(text)?;
+        ("ascii", qty, content)
+    };
+    type_reading(reading, &qty, content.as_bytes())
+}
+
+// #Definition
+//      detect_json gives the quantity of a JSON MOC and its content in the format expected by
+//      the MOC library (a single MOC under a prefix key is taken out of it).
+fn detect_json(text: &str) -> Result<(MocQType, String), Error> {
+    let json: Value =
+        serde_json::from_str(text).map_err(|e| Error::Parse(format!("Invalid JSON: {e}")))?;
+    match json {
+        Value::Array(_) => Ok((MocQType::TimeSpace, text.to_string())),
+        Value::Object(map) if map.keys().all(|k| k.parse::<u8>().is_ok()) => {
+            let depth = map.keys().filter_map(|k| k.parse::<u8>().ok()).max();
+            Ok((qty_from_depth(depth.unwrap_or(0))?, text.to_string()))
+        }
+        Value::Object(map) => {
+            let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            let qty = match keys.as_slice() {
+                ["s"] => MocQType::Space,
+                ["t"] => MocQType::Time,
+                ["f"] => MocQType::Frequency,
+                // A single ST-MOC element.
+                ["s", "t"] => return Ok((MocQType::TimeSpace, format!("[{text}]"))),
+                keys => {
+                    return Err(Error::Parse(format!(
+                        "Unknown JSON MOC keys: {keys:?}, expected depths or \"s\", \"t\", \"f\""
+                    )))
+                }
+            };
+            let moc = map.into_iter().map(|(_, moc)| moc).next();
+            Ok((qty, moc.unwrap_or_default().to_string()))
+        }
+        _ => Err(Error::Parse(String::from(
+            "A JSON MOC must be an object or an array",
+        ))),
+    }
+}
+
+// #Definition
+//      detect_ascii gives the quantity of an ASCII MOC and its content in the format expected
+//      by the MOC library (the prefixes of a single MOC are removed).
+fn detect_ascii(text: &str) -> Result<(MocQType, String), Error> {
+    if let Some(c) = text
+        .chars()
+        .find(|c| !(c.is_ascii_digit() || c.is_whitespace() || "/-+stf".contains(*c)))
+    {
+        return Err(Error::Parse(format!(
+            "Not a MOC, unexpected character '{c}' (only FITS, JSON and ASCII MOCs are supported)"
+        )));
+    }
+    let prefixes: BTreeSet<char> = text.chars().filter(|c| c.is_ascii_alphabetic()).collect();
+    let prefixes: Vec<char> = prefixes.into_iter().collect();
+    let qty = match prefixes.as_slice() {
+        [] => return Ok((qty_from_depth(ascii_depth_max(text))?, text.to_string())),
+        ['s'] => MocQType::Space,
+        ['t'] => MocQType::Time,
+        ['f'] => MocQType::Frequency,
+        ['s', 't'] => return Ok((MocQType::TimeSpace, text.to_string())),
+        prefixes => {
+            return Err(Error::Parse(format!(
+                "The ASCII MOC mixes the prefixes {prefixes:?}"
+            )))
+        }
+    };
+    Ok((qty, text.replace(prefixes[0], " ")))
+}
+
+// The largest depth of an ASCII MOC without prefix, the depths are followed by a '/'.
+fn ascii_depth_max(text: &str) -> u8 {
+    text.split_whitespace()
+        .filter_map(|token| token.split_once('/'))
+        .filter_map(|(depth, _)| depth.parse::<u8>().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+// #Definition
+//      qty_from_depth gives the quantity of a MOC without prefix from its largest depth, an
+//      S-MOC if the depth is valid for an S-MOC, a MOC without prefix being an S-MOC in the
+//      MOC 1.x standard.
+// #Errors
+//      Error::Ambiguous if it could be a T-MOC or an F-MOC, error if the depth is too large.
+fn qty_from_depth(depth: u8) -> Result<MocQType, Error> {
+    match depth {
+        d if d <= HPX_MAX_DEPTH => Ok(MocQType::Space),
+        d if d <= FREQ_MAX_DEPTH => Err(Error::Ambiguous(format!(
+            "The MOC has no prefix and a depth of {d}, it can be a T-MOC or an F-MOC"
+        ))),
+        d if d <= TIME_MAX_DEPTH => Ok(MocQType::Time),
+        d => Err(Error::Parse(format!("Depth {d} is too large for any MOC"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moc::storage::u64idx::U64MocStore;
+
+    use super::*;
+    use crate::utils::commons::{fmt_qty, same_qty};
+
+    // Detects the quantity of a MOC content, the detected MOC is dropped from the store.
+    fn detect(data: &str) -> Result<MocQType, Error> {
+        let id = detect_reading(data.as_bytes())?;
+        let qty = U64MocStore.get_qty_type(id)?;
+        U64MocStore.drop(id)?;
+        Ok(qty)
+    }
+
+    fn assert_qty(data: &str, expected: MocQType) {
+        match detect(data) {
+            Ok(qty) => assert!(same_qty(qty, expected), "{data}: {}", fmt_qty(qty)),
+            Err(e) => panic!("{data}: {e}"),
+        }
+    }
+
+    fn assert_ambiguous(data: &str) {
+        assert!(matches!(detect(data), Err(Error::Ambiguous(_))), "{data}");
+    }
+
+    #[test]
+    fn fits_magic() {
+        let id = U64MocStore.from_cone(10.0, 20.0, 1.0, 8, 2).unwrap();
+        let fits = U64MocStore.to_fits_buff(id, None).unwrap();
+        let loaded = detect_reading(&fits).unwrap();
+        assert!(U64MocStore.eq(id, loaded).unwrap());
+        U64MocStore.drop(id).unwrap();
+        U64MocStore.drop(loaded).unwrap();
+    }
+
+    #[test]
+    fn prefixed_json() {
+        assert_qty(r#"{"s":{"3":[1,2]}}"#, MocQType::Space);
+        assert_qty(r#"{"t":{"10":[5]}}"#, MocQType::Time);
+        assert_qty(r#"{"f":{"10":[5]}}"#, MocQType::Frequency);
+        assert!(matches!(detect(r#"{"x":{"3":[1]}}"#), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn prefixed_ascii() {
+        assert_qty("s3/1-2 4/100", MocQType::Space);
+        assert_qty("t10/5", MocQType::Time);
+        assert_qty("f10/5", MocQType::Frequency);
+        assert_qty("t3/1 s3/1-2", MocQType::TimeSpace);
+        assert!(matches!(detect("s3/1 f3/2"), Err(Error::Parse(_))));
+        assert!(matches!(detect("3/1 x"), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn st_moc_json() {
+        assert_qty(r#"[{"t":{"3":[1]},"s":{"3":[1,2]}}]"#, MocQType::TimeSpace);
+        // A single element, not in an array.
+        assert_qty(r#"{"t":{"3":[1]},"s":{"3":[1,2]}}"#, MocQType::TimeSpace);
+    }
+
+    #[test]
+    fn unprefixed_depths() {
+        assert_qty(r#"{"3":[1,2]}"#, MocQType::Space);
+        assert_qty("3/1-2", MocQType::Space);
+        assert_qty(r#"{"29":[5]}"#, MocQType::Space);
+        for data in [r#"{"30":[5]}"#, r#"{"40":[5]}"#, "40/5"] {
+            assert_ambiguous(data);
+        }
+        assert_qty(r#"{"61":[5]}"#, MocQType::Time);
+        assert_qty("61/5", MocQType::Time);
+        assert!(matches!(detect("62/5"), Err(Error::Parse(_))));
+    }
+
+    // The MOC library writes T-MOCs and F-MOCs without prefix, deeper than an S-MOC they must
+    // not be reloaded as S-MOCs but be reloaded with the quantity given by the user.
+    #[test]
+    fn saved_tmoc_and_fmoc_are_ambiguous() {
+        let tmoc = U64MocStore
+            .from_decimal_jd_values(40, [2_451_545.0].into_iter())
+            .unwrap();
+        let fmoc = U64MocStore
+            .from_hz_ranges(40, [1e9..2e9].into_iter())
+            .unwrap();
+        for (id, qty) in [(tmoc, MocQType::Time), (fmoc, MocQType::Frequency)] {
+            for (reading, data) in [
+                ("json", U64MocStore.to_json_str(id, None).unwrap()),
+                ("ascii", U64MocStore.to_ascii_str(id, None).unwrap()),
+            ] {
+                assert_ambiguous(&data);
+                let loaded = type_reading(reading, &qty, data.as_bytes()).unwrap();
+                assert!(U64MocStore.eq(id, loaded).unwrap(), "{data}");
+                U64MocStore.drop(loaded).unwrap();
+            }
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+}
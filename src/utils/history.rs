@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use crate::utils::registry::MocEntry;
+
+// The maximum number of actions that can be undone, the MOCs only referenced by older actions
+// are freed.
+pub(crate) const MAX_UNDO: usize = 100;
+
+// The change of the registry entry of a MOC made by an action.
+pub(crate) struct Change {
+    pub(crate) id: usize,
+    pub(crate) before: Option<MocEntry>,
+    pub(crate) after: Option<MocEntry>,
+}
+
+// The Command struct, an action done on the MOCs (load, creation, operation, rename or
+// deletion) with what is needed to undo and redo it.
+// The MOCs removed by an action are only removed from the registry, they stay in the MOC
+// store until no command can bring them back.
+pub(crate) struct Command {
+    // The name of the action, e.g. "Union" or "Rename".
+    pub(crate) action: String,
+    // The MOCs the action was applied on.
+    pub(crate) inputs: Vec<usize>,
+    // The parameters of the action as shown to the user, e.g. "depth 8".
+    pub(crate) params: String,
+    // The registry changes, the resulting MOCs for a load, a creation or an operation.
+    pub(crate) changes: Vec<Change>,
+}
+impl Command {
+    // #Definition
+    //      results gives the ids of the MOCs added, renamed or removed by the action.
+    fn results(&self) -> Vec<usize> {
+        self.changes.iter().map(|c| c.id).collect()
+    }
+
+    // #Definition
+    //      label describes the action in the Edit menu, e.g. "Union of a, b" or
+    //      "Degrade (depth 8) of a". Rename and Delete only give the name.
+    // #Args
+    //  *   `entries`: the registry entries, giving the names of the inputs
+    pub(crate) fn label(&self, entries: &BTreeMap<usize, MocEntry>) -> String {
+        let mut label = self.action.clone();
+        if !self.params.is_empty() {
+            label.push_str(&format!(" ({})", self.params));
+        }
+        if !self.inputs.is_empty() && !matches!(self.action.as_str(), "Rename" | "Delete") {
+            let names: Vec<String> = self
+                .inputs
+                .iter()
+                .map(|id| match entries.get(id) {
+                    Some(entry) => entry.name.clone(),
+                    None => format!("#{id}"),
+                })
+                .collect();
+            label.push_str(&format!(" of {}", names.join(", ")));
+        }
+        label
+    }
+
+    // #Definition
+    //      apply sets the registry entries as they were before (undo) or after (redo) the action.
+    //      Only the name of a MOC still in the registry is set (a rename), so that what is not
+    //      recorded in the history, e.g. its colour, is kept.
+    fn apply(&self, entries: &mut BTreeMap<usize, MocEntry>, undo: bool) {
+        for change in &self.changes {
+            let entry = if undo { &change.before } else { &change.after };
+            match (entry, entries.get_mut(&change.id)) {
+                (Some(entry), Some(current)) => current.name = entry.name.clone(),
+                (Some(entry), None) => {
+                    entries.insert(change.id, entry.clone());
+                }
+                (None, _) => {
+                    entries.remove(&change.id);
+                }
+            }
+        }
+    }
+}
+
+// The History struct, the actions that can be undone and the ones that can be redone.
+#[derive(Default)]
+pub(crate) struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+}
+impl History {
+    // #Definition
+    //      record adds a command to the history. The undone actions can't be redone anymore and
+    //      the oldest actions are forgotten past MAX_UNDO.
+    // #Returns
+    //      The ids of the MOCs of the forgotten actions that no action references anymore, they
+    //      can be freed if they are not in the registry.
+    pub(crate) fn record(&mut self, command: Command) -> Vec<usize> {
+        let mut forgotten: Vec<Command> = self.redo.drain(..).collect();
+        self.undo.push(command);
+        let excess = self.undo.len().saturating_sub(MAX_UNDO);
+        forgotten.extend(self.undo.drain(..excess));
+        let mut ids: Vec<usize> = forgotten.iter().flat_map(Command::results).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.retain(|id| {
+            !self
+                .undo
+                .iter()
+                .any(|c| c.changes.iter().any(|change| change.id == *id))
+        });
+        ids
+    }
+
+    // #Definition
+    //      undo undoes the last action, if any.
+    // #Args
+    //  *   `entries`: the registry entries the action changed
+    // #Returns
+    //      The label of the undone action.
+    pub(crate) fn undo(&mut self, entries: &mut BTreeMap<usize, MocEntry>) -> Option<String> {
+        let command = self.undo.pop()?;
+        command.apply(entries, true);
+        let label = command.label(entries);
+        self.redo.push(command);
+        Some(label)
+    }
+
+    // #Definition
+    //      redo redoes the last undone action, if any.
+    // #Args
+    //  *   `entries`: the registry entries the action changes
+    // #Returns
+    //      The label of the redone action.
+    pub(crate) fn redo(&mut self, entries: &mut BTreeMap<usize, MocEntry>) -> Option<String> {
+        let command = self.redo.pop()?;
+        command.apply(entries, false);
+        let label = command.label(entries);
+        self.undo.push(command);
+        Some(label)
+    }
+
+    // #Definition
+    //      undo_label gives the label of the action undo would undo, None if there is none.
+    pub(crate) fn undo_label(&self, entries: &BTreeMap<usize, MocEntry>) -> Option<String> {
+        self.undo.last().map(|c| c.label(entries))
+    }
+
+    // #Definition
+    //      redo_label gives the label of the action redo would redo, None if there is none.
+    pub(crate) fn redo_label(&self, entries: &BTreeMap<usize, MocEntry>) -> Option<String> {
+        self.redo.last().map(|c| c.label(entries))
+    }
+}
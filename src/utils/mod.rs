@@ -1,2 +1,8 @@
 pub(crate) mod commons;
-pub(crate) mod namestore;
\ No newline at end of file
+pub(crate) mod compression;
+pub(crate) mod detect;
+pub(crate) mod history;
+pub(crate) mod provenance;
+pub(crate) mod registry;
+pub(crate) mod votable;
+pub(crate) mod workspace;
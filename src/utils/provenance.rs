@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use moc::storage::u64idx::U64MocStore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// The size of a FITS block and of a FITS header card.
+const FITS_BLOCK: usize = 2880;
+const FITS_CARD: usize = 80;
+
+// The Provenance struct, how a MOC was obtained: the action which gave it, its parameters and
+// the provenance of the MOCs it was applied on. A loaded or created MOC has no input.
+#[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) struct Provenance {
+    // The name of the MOC when it was used (its current name for the root).
+    pub(crate) name: String,
+    // The action, e.g. "Load", "Cone" or "Union", empty if unknown.
+    pub(crate) action: String,
+    // The parameters of the action as shown to the user, e.g. "depth 8".
+    pub(crate) params: String,
+    pub(crate) inputs: Vec<Provenance>,
+}
+impl Provenance {
+    // #Definition
+    //      expression gives the provenance as a formula over the names of the loaded or created
+    //      MOCs, e.g. "Degrade(Intersection(A, B), depth 8)".
+    pub(crate) fn expression(&self) -> String {
+        if self.inputs.is_empty() {
+            return self.name.clone();
+        }
+        // An expression of the expression bar is already a formula over its inputs.
+        if self.action == "Expression" {
+            return format!("({})", self.params);
+        }
+        let mut args: Vec<String> = self.inputs.iter().map(Provenance::expression).collect();
+        if !self.params.is_empty() {
+            args.push(self.params.clone());
+        }
+        format!("{}({})", self.action, args.join(", "))
+    }
+
+    // #Definition
+    //      step describes the action alone, e.g. "Degrade (depth 8)" or "Load (a.fits)".
+    pub(crate) fn step(&self) -> String {
+        match (self.action.is_empty(), self.params.is_empty()) {
+            (true, _) => String::from("unknown origin"),
+            (false, true) => self.action.clone(),
+            (false, false) => format!("{} ({})", self.action, self.params),
+        }
+    }
+}
+
+// #Definition
+//      to_fits_buff writes a MOC in FITS, with its provenance in HISTORY cards of the MOC
+//      extension header when it was derived from other MOCs.
+// #Args
+//  *   `id`: the id of the MOC in the store
+//  *   `provenance`: its provenance, see Registry::provenance
+// #Errors
+//      Error if the MOC can't be written in FITS.
+pub(crate) fn to_fits_buff(id: usize, provenance: &Provenance) -> Result<Box<[u8]>, Error> {
+    let data = U64MocStore.to_fits_buff(id, None)?;
+    if provenance.inputs.is_empty() {
+        return Ok(data);
+    }
+    let text: Vec<char> = format!("Provenance: {}", provenance.expression())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() || c == ' ' {
+                c
+            } else {
+                '?'
+            }
+        })
+        .collect();
+    let cards: Vec<String> = text
+        .chunks(FITS_CARD - 8)
+        .map(|chunk| format!("HISTORY {}", chunk.iter().collect::<String>()))
+        .collect();
+    insert_fits_cards(&data, &cards)
+}
+
+// #Definition
+//      insert_fits_cards inserts cards at the end of the header of the first FITS extension,
+//      the header growing by a block if needed.
+// #Args
+//  *   `data`: the FITS file content
+//  *   `cards`: the cards to insert, 80 characters at most
+// #Errors
+//      Error if the FITS file has no extension.
+fn insert_fits_cards(data: &[u8], cards: &[String]) -> Result<Box<[u8]>, Error> {
+    let is_end = |pos: &usize| data[*pos..*pos + FITS_CARD].starts_with(b"END     ");
+    let mut ends = (0..data.len().saturating_sub(FITS_CARD - 1))
+        .step_by(FITS_CARD)
+        .filter(is_end);
+    let (Some(primary_end), Some(end)) = (ends.next(), ends.next()) else {
+        return Err(Error::NotFound(String::from("FITS extension header")));
+    };
+    let block_end = |pos: usize| (pos + FITS_CARD + FITS_BLOCK - 1) / FITS_BLOCK * FITS_BLOCK;
+    let (header_start, header_end) = (block_end(primary_end), block_end(end));
+
+    let mut fits = data[..end].to_vec();
+    for card in cards.iter().chain([&String::from("END")]) {
+        fits.extend(format!("{card:<FITS_CARD$}").bytes().take(FITS_CARD));
+    }
+    let padding = (FITS_BLOCK - (fits.len() - header_start) % FITS_BLOCK) % FITS_BLOCK;
+    fits.resize(fits.len() + padding, b' ');
+    fits.extend_from_slice(&data[header_end..]);
+    Ok(fits.into_boxed_slice())
+}
+
+// #Definition
+//      fits_metadata reads the keywords of the MOC extension header of a FITS file, e.g. MOCTOOL,
+//      DATE or ORIGIN, leaving out the ones describing the structure of the file.
+// #Args
+//  *   `data`: the (decompressed) file content
+// #Returns
+//      The keywords and their values, empty if it is not a FITS file.
+pub(crate) fn fits_metadata(data: &[u8]) -> BTreeMap<String, String> {
+    let mut metadata = BTreeMap::new();
+    if !data.starts_with(b"SIMPLE") {
+        return metadata;
+    }
+    let card = |pos: usize| data.get(pos..pos + FITS_CARD);
+    // The extension header starts at the block following the END card of the primary header.
+    let mut pos = 0;
+    while card(pos).is_some_and(|c| !c.starts_with(b"END     ")) {
+        pos += FITS_CARD;
+    }
+    pos = (pos + FITS_CARD + FITS_BLOCK - 1) / FITS_BLOCK * FITS_BLOCK;
+    while let Some(card) = card(pos) {
+        pos += FITS_CARD;
+        let (key, value) = card.split_at(8);
+        let (key, value) = (String::from_utf8_lossy(key), String::from_utf8_lossy(value));
+        let key = key.trim();
+        if key == "END" {
+            break;
+        }
+        let structural = matches!(
+            key,
+            "XTENSION" | "BITPIX" | "PCOUNT" | "GCOUNT" | "TFIELDS" | ""
+        ) || ["NAXIS", "TTYPE", "TFORM", "TUNIT"]
+            .iter()
+            .any(|prefix| key.starts_with(prefix));
+        if let (false, Some(value)) = (structural, value.strip_prefix("= ")) {
+            metadata.insert(key.to_string(), card_value(value));
+        }
+    }
+    metadata
+}
+
+// #Definition
+//      card_value gives the value of a FITS header card without its comment, the quotes of a
+//      string value being removed.
+fn card_value(value: &str) -> String {
+    let value = value.trim_start();
+    let Some(quoted) = value.strip_prefix('\'') else {
+        return value
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+    };
+    // In a string value, two quotes stand for one.
+    let mut string = String::default();
+    let mut chars = quoted.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+            break;
+        }
+        string.push(c);
+    }
+    string.trim_end().to_string()
+}
@@ -0,0 +1,530 @@
+use std::collections::BTreeMap;
+
+use moc::storage::u64idx::{common::MocQType, U64MocStore};
+
+use crate::error::Error;
+use crate::utils::history::{Change, Command, History};
+use crate::utils::provenance::Provenance;
+
+// The colours given to the MOCs, in load order.
+const PALETTE: [[u8; 3]; 8] = [
+    [220, 50, 50],
+    [50, 120, 220],
+    [50, 180, 80],
+    [230, 160, 30],
+    [160, 70, 200],
+    [30, 190, 190],
+    [220, 90, 170],
+    [140, 140, 140],
+];
+
+// The MocEntry struct, what the app knows about a MOC of the store.
+#[derive(Clone)]
+pub(crate) struct MocEntry {
+    // The name of the MOC, unique in the registry.
+    pub(crate) name: String,
+    // The load order, the MOCs are listed in this order.
+    pub(crate) order: usize,
+    // The file (or URL) the MOC was loaded or created from, None if it was made in the app.
+    pub(crate) source: Option<String>,
+    pub(crate) qty: MocQType,
+    // The colour of the MOC in the viewers.
+    pub(crate) color: [u8; 3],
+    // The keywords of the FITS header the MOC was loaded from, e.g. MOCTOOL or DATE.
+    pub(crate) metadata: BTreeMap<String, String>,
+    // How the MOC was obtained, its name is left empty (see Registry::provenance).
+    provenance: Provenance,
+}
+
+// The Registry struct, the MOCs of the store known by the app, by store id, with the history
+// of the actions done on them. The names are unique.
+#[derive(Default)]
+pub struct Registry {
+    entries: BTreeMap<usize, MocEntry>,
+    next_order: usize,
+    history: History,
+}
+impl Registry {
+    ////////////////
+    // OPERATIONS //
+
+    // #Definition
+    //      insert adds a MOC of the store to the registry, without recording it in the history.
+    //      A suffix is appended to the name if it is already taken, e.g. "a(1)".
+    // #Args
+    //  *   `name`: the name of the MOC
+    //  *   `id`: the id of the MOC in the store
+    //  *   `source`: the file (or URL) it comes from, if any
+    //  *   `metadata`: the keywords of its FITS header, if any
+    // #Returns
+    //      The name given to the MOC.
+    // #Errors
+    //      Error if the MOC is not in the store.
+    pub(crate) fn insert(
+        &mut self,
+        name: &str,
+        id: usize,
+        source: Option<String>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<String, Error> {
+        let qty = U64MocStore.get_qty_type(id)?;
+        let name = self.unique_name(name, None);
+        let order = self.next_order;
+        self.next_order += 1;
+        self.entries.insert(
+            id,
+            MocEntry {
+                name: name.clone(),
+                order,
+                source,
+                qty,
+                color: PALETTE[order % PALETTE.len()],
+                metadata,
+                provenance: Provenance::default(),
+            },
+        );
+        Ok(name)
+    }
+
+    // #Definition
+    //      added records the MOCs just inserted by an action, undoing it removes them.
+    //      The provenance of the MOCs is recorded too.
+    // #Args
+    //  *   `action`: the name of the action, e.g. "Load" or "Union"
+    //  *   `inputs`: the MOCs the action was applied on
+    //  *   `params`: the parameters of the action as shown to the user
+    //  *   `ids`: the ids of the inserted MOCs
+    // #Errors
+    //      Error if a MOC is not in the registry or if a freed MOC can't be dropped.
+    pub(crate) fn added(
+        &mut self,
+        action: &str,
+        inputs: &[usize],
+        params: &str,
+        ids: &[usize],
+    ) -> Result<(), Error> {
+        let provenance = Provenance {
+            name: String::default(),
+            action: action.to_string(),
+            params: params.to_string(),
+            inputs: inputs.iter().map(|id| self.provenance(*id)).collect(),
+        };
+        let mut changes = Vec::with_capacity(ids.len());
+        for id in ids {
+            let entry = self
+                .entries
+                .get_mut(id)
+                .ok_or_else(|| Error::NotFound(format!("MOC '{id}'")))?;
+            entry.provenance = provenance.clone();
+            changes.push(Change {
+                id: *id,
+                before: None,
+                after: Some(entry.clone()),
+            });
+        }
+        self.record(Command {
+            action: action.to_string(),
+            inputs: inputs.to_vec(),
+            params: params.to_string(),
+            changes,
+        })
+    }
+
+    // #Definition
+    //      add inserts a MOC made in the app (see insert) and records it.
+    // #Args
+    //  *   `action`: the name of the action, e.g. "Cone" or "Union"
+    //  *   `params`: the parameters of the action as shown to the user
+    //  *   `name`: the name of the newly added MOC
+    //  *   `id`: the id of the MOC in the store
+    // #Returns
+    //      The name given to the MOC.
+    pub(crate) fn add(
+        &mut self,
+        action: &str,
+        params: &str,
+        name: &str,
+        id: usize,
+    ) -> Result<String, Error> {
+        let name = self.insert(name, id, None, BTreeMap::new())?;
+        self.added(action, &[], params, &[id])?;
+        Ok(name)
+    }
+
+    // #Definition
+    //      load inserts a MOC read from a file or a URL (see insert) and records it.
+    // #Args
+    //  *   `source`: the path of the file or its URL
+    //  *   `name`: the name of the MOC
+    //  *   `id`: the id of the MOC in the store
+    //  *   `metadata`: the keywords of its FITS header, if any
+    // #Returns
+    //      The name given to the MOC.
+    pub(crate) fn load(
+        &mut self,
+        source: &str,
+        name: &str,
+        id: usize,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<String, Error> {
+        let file_name = source.rsplit(['/', '\\']).next().unwrap_or(source);
+        let name = self.insert(name, id, Some(source.to_string()), metadata)?;
+        self.added("Load", &[], file_name, &[id])?;
+        Ok(name)
+    }
+
+    // #Definition
+    //      rename renames a MOC and records it, a suffix is appended to the name if it is
+    //      already taken by another MOC.
+    // #Args
+    //  *   `id`: the id of the MOC to rename
+    //  *   `name`: its new name
+    // #Returns
+    //      The name given to the MOC.
+    // #Errors
+    //      Error if the MOC is not in the registry.
+    pub(crate) fn rename(&mut self, id: usize, name: &str) -> Result<String, Error> {
+        let name = self.unique_name(name, Some(id));
+        let entry = self
+            .entries
+            .get_mut(&id)
+            .ok_or_else(|| Error::NotFound(format!("MOC '{id}'")))?;
+        let before = Some(entry.clone());
+        entry.name = name.clone();
+        let after = Some(entry.clone());
+        self.record(Command {
+            action: String::from("Rename"),
+            inputs: vec![id],
+            params: name.clone(),
+            changes: vec![Change { id, before, after }],
+        })?;
+        Ok(name)
+    }
+
+    // #Definition
+    //      remove removes a MOC from the registry and records it, the MOC is kept in the MOC
+    //      store until the deletion can't be undone anymore.
+    // #Args
+    //  *   `id`: the id of the MOC to remove
+    // #Errors
+    //      Error if the MOC is not in the registry.
+    pub(crate) fn remove(&mut self, id: usize) -> Result<(), Error> {
+        let before = self
+            .entries
+            .remove(&id)
+            .ok_or_else(|| Error::NotFound(format!("MOC '{id}'")))?;
+        self.record(Command {
+            action: String::from("Delete"),
+            inputs: vec![id],
+            params: before.name.clone(),
+            changes: vec![Change {
+                id,
+                before: Some(before),
+                after: None,
+            }],
+        })
+    }
+
+    // #Definition
+    //      set_color sets the colour of a MOC in the viewers, it is not recorded in the history.
+    // #Args
+    //  *   `id`: the id of the MOC
+    //  *   `color`: its new colour
+    pub(crate) fn set_color(&mut self, id: usize, color: [u8; 3]) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.color = color;
+        }
+    }
+
+    // #Definition
+    //      set_provenance sets how a MOC was obtained, e.g. when restoring a workspace. It is not
+    //      recorded in the history.
+    // #Args
+    //  *   `id`: the id of the MOC
+    //  *   `provenance`: its provenance, its name is replaced by the current name of the MOC
+    pub(crate) fn set_provenance(&mut self, id: usize, provenance: Provenance) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.provenance = Provenance {
+                name: String::default(),
+                ..provenance
+            };
+        }
+    }
+
+    // #Definition
+    //      undo undoes the last action, if any.
+    // #Returns
+    //      The label of the undone action.
+    pub(crate) fn undo(&mut self) -> Option<String> {
+        self.history.undo(&mut self.entries)
+    }
+
+    // #Definition
+    //      redo redoes the last undone action, if any.
+    // #Returns
+    //      The label of the redone action.
+    pub(crate) fn redo(&mut self) -> Option<String> {
+        self.history.redo(&mut self.entries)
+    }
+
+    /////////////
+    // GETTERS //
+
+    // #Definition
+    //      get gives the entry of a MOC, None if it is not in the registry.
+    // #Args
+    //  *   `id`: the id of the MOC in the store
+    pub(crate) fn get(&self, id: usize) -> Option<&MocEntry> {
+        self.entries.get(&id)
+    }
+
+    // #Definition
+    //      name gives the name of a MOC.
+    // #Args
+    //  *   `id`: the id of the MOC in the store
+    // #Errors
+    //      Error if the MOC is not in the registry.
+    pub(crate) fn name(&self, id: usize) -> Result<&str, Error> {
+        self.entries
+            .get(&id)
+            .map(|entry| entry.name.as_str())
+            .ok_or_else(|| Error::NotFound(format!("MOC '{id}'")))
+    }
+
+    // #Definition
+    //      find gives the id of the MOC of the given name, None if there is none.
+    pub(crate) fn find(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|(_, entry)| entry.name == name)
+            .map(|(id, _)| *id)
+    }
+
+    // #Definition
+    //      entries gives the MOCs of the registry with their id, in load order.
+    pub(crate) fn entries(&self) -> Vec<(usize, &MocEntry)> {
+        let mut entries: Vec<(usize, &MocEntry)> = self
+            .entries
+            .iter()
+            .map(|(id, entry)| (*id, entry))
+            .collect();
+        entries.sort_by_key(|(_, entry)| entry.order);
+        entries
+    }
+
+    // #Definition
+    //      ids gives the ids of the MOCs of the registry, in load order.
+    pub(crate) fn ids(&self) -> Vec<usize> {
+        self.entries().into_iter().map(|(id, _)| id).collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // #Definition
+    //      provenance gives the provenance of a MOC with its current name, a MOC with no recorded
+    //      provenance (e.g. restored from an older workspace) is given as a MOC of unknown origin.
+    // #Args
+    //  *   `id`: the id of the MOC in the store
+    pub(crate) fn provenance(&self, id: usize) -> Provenance {
+        match self.entries.get(&id) {
+            Some(entry) => Provenance {
+                name: entry.name.clone(),
+                ..entry.provenance.clone()
+            },
+            None => Provenance {
+                name: format!("#{id}"),
+                ..Provenance::default()
+            },
+        }
+    }
+
+    // #Definition
+    //      undo_label gives the label of the action undo would undo, None if there is none.
+    pub(crate) fn undo_label(&self) -> Option<String> {
+        self.history.undo_label(&self.entries)
+    }
+
+    // #Definition
+    //      redo_label gives the label of the action redo would redo, None if there is none.
+    pub(crate) fn redo_label(&self) -> Option<String> {
+        self.history.redo_label(&self.entries)
+    }
+
+    ///////////////
+    // UTILITIES //
+
+    // #Definition
+    //      unique_name gives the name itself if no other MOC has it, else the name with the
+    //      first free suffix: "a(1)", "a(2)"...
+    // #Args
+    //  *   `name`: the wanted name, "MOC" if empty
+    //  *   `id`: the MOC being renamed, whose current name doesn't count
+    fn unique_name(&self, name: &str, id: Option<usize>) -> String {
+        let name = match name.trim() {
+            "" => "MOC",
+            name => name,
+        };
+        let taken = |candidate: &str| {
+            self.entries
+                .iter()
+                .any(|(other, entry)| Some(*other) != id && entry.name == candidate)
+        };
+        if !taken(name) {
+            return name.to_string();
+        }
+        (1..)
+            .map(|n| format!("{name}({n})"))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_default()
+    }
+
+    // #Definition
+    //      record adds a command to the history, the MOCs no action can bring back anymore are
+    //      dropped from the store.
+    // #Errors
+    //      Error if a MOC can't be dropped from the store.
+    fn record(&mut self, command: Command) -> Result<(), Error> {
+        for id in self.history.record(command) {
+            if !self.entries.contains_key(&id) {
+                U64MocStore.drop(id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::history::MAX_UNDO;
+
+    // A MOC of the store, its content tells it from the MOCs of the other tests.
+    fn moc(content: &str) -> usize {
+        U64MocStore.load_smoc_from_ascii(content).unwrap()
+    }
+
+    // Whether the MOC is still in the store, the store may have given its id to another MOC.
+    fn in_store(id: usize, content: &str) -> bool {
+        U64MocStore
+            .to_ascii_str(id, None)
+            .is_ok_and(|ascii| ascii.trim() == content)
+    }
+
+    fn drop_all(registry: &Registry) {
+        for id in registry.ids() {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+
+    #[test]
+    fn unique_names() {
+        let mut registry = Registry::default();
+        let a = moc("29/1");
+        assert_eq!(registry.add("Test", "", "a", a).unwrap(), "a");
+        let a1 = moc("29/2");
+        assert_eq!(registry.add("Test", "", "a", a1).unwrap(), "a(1)");
+        let ab = moc("29/3");
+        assert_eq!(registry.add("Test", "", "ab", ab).unwrap(), "ab");
+        let a2 = moc("29/4");
+        assert_eq!(registry.add("Test", "", " a ", a2).unwrap(), "a(2)");
+        let unnamed = moc("29/5");
+        assert_eq!(registry.add("Test", "", "", unnamed).unwrap(), "MOC");
+
+        // Renaming a MOC to its own name keeps it.
+        assert_eq!(registry.rename(a, "a").unwrap(), "a");
+        assert_eq!(registry.rename(a1, "a(1)").unwrap(), "a(1)");
+        // The name of another MOC gets a suffix, the first free one.
+        assert_eq!(registry.rename(ab, "a").unwrap(), "a(3)");
+        assert_eq!(registry.rename(a1, "b").unwrap(), "b");
+        assert_eq!(registry.rename(a2, "a").unwrap(), "a(1)");
+        assert_eq!(registry.find("a(3)"), Some(ab));
+        assert!(registry.rename(usize::MAX, "c").is_err());
+        drop_all(&registry);
+    }
+
+    #[test]
+    fn undo_redo_remove() {
+        let mut registry = Registry::default();
+        let a = moc("29/11");
+        registry
+            .load("/data/a.fits", "a", a, BTreeMap::new())
+            .unwrap();
+        registry.remove(a).unwrap();
+        assert!(registry.get(a).is_none());
+        assert!(registry.remove(a).is_err());
+        // The MOC is kept in the store while the deletion can be undone.
+        assert!(in_store(a, "29/11"));
+
+        assert_eq!(registry.undo_label().as_deref(), Some("Delete (a)"));
+        assert_eq!(registry.undo().as_deref(), Some("Delete (a)"));
+        assert_eq!(registry.name(a).unwrap(), "a");
+        assert_eq!(
+            registry.get(a).unwrap().source.as_deref(),
+            Some("/data/a.fits")
+        );
+        assert_eq!(registry.redo().as_deref(), Some("Delete (a)"));
+        assert!(registry.is_empty());
+        assert_eq!(registry.undo().as_deref(), Some("Delete (a)"));
+        assert_eq!(registry.undo().as_deref(), Some("Load (a.fits)"));
+        assert!(registry.is_empty());
+        assert_eq!(registry.redo().as_deref(), Some("Load (a.fits)"));
+        assert_eq!(registry.ids(), vec![a]);
+        assert_eq!(registry.redo_label().as_deref(), Some("Delete (a)"));
+        drop_all(&registry);
+    }
+
+    // Undoing a rename only brings the name back, not the colour the MOC had then.
+    #[test]
+    fn undo_rename_keeps_color() {
+        let mut registry = Registry::default();
+        let a = moc("29/31");
+        registry.add("Test", "", "a", a).unwrap();
+        registry.rename(a, "b").unwrap();
+        registry.set_color(a, [1, 2, 3]);
+        assert_eq!(registry.undo().as_deref(), Some("Rename (b)"));
+        assert_eq!(registry.name(a).unwrap(), "a");
+        assert_eq!(registry.get(a).unwrap().color, [1, 2, 3]);
+        assert_eq!(registry.redo().as_deref(), Some("Rename (b)"));
+        assert_eq!(registry.name(a).unwrap(), "b");
+        assert_eq!(registry.get(a).unwrap().color, [1, 2, 3]);
+        drop_all(&registry);
+    }
+
+    #[test]
+    fn mocs_out_of_the_history_are_dropped() {
+        let mut registry = Registry::default();
+        let a = moc("29/21");
+        registry.add("Test", "", "a", a).unwrap();
+        // An undone creation can't be redone after a new action, its MOC is dropped.
+        let b = moc("29/22");
+        registry.add("Test", "", "b", b).unwrap();
+        registry.undo();
+        assert!(in_store(b, "29/22"));
+        registry.rename(a, "c").unwrap();
+        assert!(registry.redo_label().is_none());
+        assert!(!in_store(b, "29/22"));
+
+        // A deleted MOC is dropped once its deletion is forgotten.
+        registry.remove(a).unwrap();
+        for i in 0..MAX_UNDO - 1 {
+            let id = moc(&format!("28/{i}"));
+            registry.add("Test", "", "d", id).unwrap();
+        }
+        assert!(in_store(a, "29/21"));
+        let last = moc("29/23");
+        registry.add("Test", "", "d", last).unwrap();
+        assert!(!in_store(a, "29/21"));
+        // The MOCs still in the registry are kept.
+        assert!(registry
+            .ids()
+            .iter()
+            .all(|id| U64MocStore.is_empty(*id).is_ok()));
+        drop_all(&registry);
+    }
+}
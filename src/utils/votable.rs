@@ -0,0 +1,391 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use roxmltree::{Document, Node};
+
+use crate::error::Error;
+
+// A FIELD of a VOTable.
+#[derive(Clone)]
+pub(crate) struct VoField {
+    pub(crate) name: String,
+    pub(crate) ucd: String,
+    pub(crate) unit: String,
+    pub(crate) xtype: String,
+    pub(crate) datatype: String,
+    arraysize: String,
+    // The value standing for null in integer fields (VALUES null attribute).
+    null: Option<f64>,
+}
+
+// The VoTable struct, the first table of a VOTable document: its fields and its rows, the
+// values being kept as numbers (None for null or non numeric values).
+#[derive(Clone)]
+pub(crate) struct VoTable {
+    pub(crate) fields: Vec<VoField>,
+    pub(crate) rows: Vec<Vec<Option<f64>>>,
+}
+impl VoTable {
+    // #Definition
+    //      values gives the values of the given columns, row after row (e.g. [ra_1, dec_1,
+    //      ra_2, dec_2, ...]), each column going through its conversion function.
+    //      The rows with a null or non numeric value in one of the columns are skipped.
+    // #Args
+    //  *   `columns`: the columns with their conversion function
+    // #Returns
+    //      The values and the number of skipped rows.
+    pub(crate) fn values(&self, columns: &[(usize, &dyn Fn(f64) -> f64)]) -> (Vec<f64>, usize) {
+        let mut values = Vec::with_capacity(self.rows.len() * columns.len());
+        let mut skipped = 0;
+        for row in &self.rows {
+            let row_values: Option<Vec<f64>> = columns
+                .iter()
+                .map(|(i, convert)| row.get(*i).copied().flatten().map(convert))
+                .collect();
+            match row_values {
+                Some(row_values) => values.extend(row_values),
+                None => skipped += 1,
+            }
+        }
+        (values, skipped)
+    }
+}
+
+// #Definition
+//      parse_votable parses the first table of a VOTable document.
+//      The TABLEDATA, BINARY and BINARY2 serializations are supported, the streams of the
+//      binary ones being base64 encoded in the document.
+// #Args
+//  *   `data`: the content of the VOTable file
+// #Errors
+//      Error if the document is not a valid VOTable or if its serialization is not supported.
+pub(crate) fn parse_votable(data: &[u8]) -> Result<VoTable, Error> {
+    let text = std::str::from_utf8(data)
+        .map_err(|e| Error::Parse(format!("Invalid UTF-8 content: {e}")))?;
+    let doc = Document::parse(text).map_err(|e| Error::Parse(format!("Invalid XML: {e}")))?;
+    let table = doc
+        .descendants()
+        .find(|n| n.has_tag_name("TABLE"))
+        .ok_or_else(|| Error::NotFound(String::from("VOTable TABLE")))?;
+    let fields: Vec<VoField> = table
+        .children()
+        .filter(|n| n.has_tag_name("FIELD"))
+        .map(|n| VoField {
+            name: attribute(n, "name"),
+            ucd: attribute(n, "ucd"),
+            unit: attribute(n, "unit"),
+            xtype: attribute(n, "xtype"),
+            datatype: attribute(n, "datatype"),
+            arraysize: attribute(n, "arraysize"),
+            null: n
+                .children()
+                .find(|v| v.has_tag_name("VALUES"))
+                .and_then(|v| v.attribute("null"))
+                .and_then(|null| null.trim().parse().ok()),
+        })
+        .collect();
+    if fields.is_empty() {
+        return Err(Error::Parse(String::from("The VOTable has no FIELD")));
+    }
+    let data = table
+        .children()
+        .find(|n| n.has_tag_name("DATA"))
+        .and_then(|d| d.children().find(Node::is_element))
+        .ok_or_else(|| Error::NotFound(String::from("VOTable DATA")))?;
+    let rows = match data.tag_name().name() {
+        "TABLEDATA" => tabledata_rows(data, &fields),
+        "BINARY" => binary_rows(stream(data)?, &fields, false)?,
+        "BINARY2" => binary_rows(stream(data)?, &fields, true)?,
+        other => {
+            return Err(Error::Parse(format!(
+                "Unsupported VOTable serialization: {other}"
+            )))
+        }
+    };
+    Ok(VoTable { fields, rows })
+}
+
+// An attribute of an element, empty if missing.
+fn attribute(node: Node<'_, '_>, name: &str) -> String {
+    node.attribute(name).unwrap_or_default().to_string()
+}
+
+// #Definition
+//      tabledata_rows reads the TR/TD elements of a TABLEDATA.
+fn tabledata_rows(data: Node<'_, '_>, fields: &[VoField]) -> Vec<Vec<Option<f64>>> {
+    data.children()
+        .filter(|n| n.has_tag_name("TR"))
+        .map(|tr| {
+            tr.children()
+                .filter(|n| n.has_tag_name("TD"))
+                .zip(fields)
+                .map(|(td, field)| parse_number(td.text().unwrap_or_default(), field))
+                .collect()
+        })
+        .collect()
+}
+
+// #Definition
+//      stream decodes the base64 STREAM of a BINARY or BINARY2 element.
+// #Errors
+//      Error if the stream is not base64 encoded in the document.
+fn stream(data: Node<'_, '_>) -> Result<Vec<u8>, Error> {
+    let stream = data
+        .children()
+        .find(|n| n.has_tag_name("STREAM"))
+        .ok_or_else(|| Error::NotFound(String::from("VOTable STREAM")))?;
+    if stream.has_attribute("href") || stream.attribute("encoding") != Some("base64") {
+        return Err(Error::Parse(String::from(
+            "Only base64 streams embedded in the VOTable are supported",
+        )));
+    }
+    let encoded: String = stream
+        .text()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| Error::Parse(format!("Invalid base64 stream: {e}")))
+}
+
+// #Definition
+//      binary_rows reads the rows of a BINARY or BINARY2 stream. Only the first element of an
+//      array is kept, character strings are parsed as numbers.
+// #Args
+//  *   `bytes`: the decoded stream
+//  *   `fields`: the fields of the table
+//  *   `null_flags`: true for BINARY2, each row then starts with the null flags of its fields
+// #Errors
+//      Error if a datatype is unknown or if the stream ends in the middle of a row.
+fn binary_rows(
+    bytes: Vec<u8>,
+    fields: &[VoField],
+    null_flags: bool,
+) -> Result<Vec<Vec<Option<f64>>>, Error> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let mut rows = Vec::default();
+    while !reader.is_empty() {
+        let flags = if null_flags {
+            reader.take((fields.len() + 7) / 8)?.to_vec()
+        } else {
+            Vec::default()
+        };
+        let mut row = Vec::with_capacity(fields.len());
+        for (i, field) in fields.iter().enumerate() {
+            let value = read_value(&mut reader, field)?;
+            let is_null = flags.get(i / 8).is_some_and(|b| b & (0x80 >> (i % 8)) != 0);
+            row.push(if is_null { None } else { value });
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// A cursor over a binary stream.
+struct Reader {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+impl Reader {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&[u8], Error> {
+        let end = self.pos + n;
+        let bytes = self.bytes.get(self.pos..end).ok_or_else(|| {
+            Error::Parse(String::from(
+                "The binary stream ends in the middle of a row",
+            ))
+        })?;
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+// #Definition
+//      read_value reads the value of a field in a binary stream.
+// #Errors
+//      Error if the datatype is unknown or if the stream is too short.
+fn read_value(reader: &mut Reader, field: &VoField) -> Result<Option<f64>, Error> {
+    let size = match field.datatype.as_str() {
+        "boolean" | "unsignedByte" | "char" => 1,
+        "short" | "unicodeChar" => 2,
+        "int" | "float" => 4,
+        "long" | "double" | "floatComplex" => 8,
+        "doubleComplex" => 16,
+        "bit" => 0,
+        other => return Err(Error::Parse(format!("Unknown VOTable datatype: '{other}'"))),
+    };
+    // The fixed dimensions, the last one may be variable ('*'), its length is then given
+    // before the value.
+    let dims: Vec<&str> = field
+        .arraysize
+        .split('x')
+        .filter(|d| !d.is_empty())
+        .collect();
+    let fixed: usize = dims
+        .iter()
+        .filter(|d| !d.ends_with('*'))
+        .map(|d| d.parse::<usize>().unwrap_or(1))
+        .product();
+    let count = if dims.last().is_some_and(|d| d.ends_with('*')) {
+        let len = reader.take(4)?;
+        fixed * u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize
+    } else {
+        fixed
+    };
+    let bytes = match size {
+        0 => reader.take((count + 7) / 8)?,
+        _ => reader.take(size * count)?,
+    };
+    if count == 0 {
+        return Ok(None);
+    }
+    let value = match field.datatype.as_str() {
+        "char" => {
+            return Ok(parse_number(&String::from_utf8_lossy(bytes), field));
+        }
+        "unicodeChar" => {
+            let chars: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            return Ok(parse_number(&String::from_utf16_lossy(&chars), field));
+        }
+        "boolean" => match bytes[0] {
+            b'T' | b't' | b'1' => 1.0,
+            b'F' | b'f' | b'0' => 0.0,
+            _ => return Ok(None),
+        },
+        "bit" => f64::from(bytes[0] >> 7),
+        "unsignedByte" => f64::from(bytes[0]),
+        "short" => f64::from(i16::from_be_bytes([bytes[0], bytes[1]])),
+        "int" => f64::from(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        "long" => i64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()) as f64,
+        "float" | "floatComplex" => {
+            f64::from(f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        }
+        _ => f64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()),
+    };
+    Ok(checked(value, field))
+}
+
+// A value read as text, None if it is empty or not a number.
+fn parse_number(text: &str, field: &VoField) -> Option<f64> {
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    text.parse().ok().and_then(|v| checked(v, field))
+}
+
+// A value, None if it is the null value of its field or is not finite.
+fn checked(value: f64, field: &VoField) -> Option<f64> {
+    (value.is_finite() && field.null != Some(value)).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A VOTable document with the given FIELDs and DATA.
+    fn votable(fields: &str, data: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<VOTABLE version="1.4" xmlns="http://www.ivoa.net/xml/VOTable/v1.3">
+  <RESOURCE><TABLE>{fields}<DATA>{data}</DATA></TABLE></RESOURCE>
+</VOTABLE>"#
+        )
+    }
+
+    fn is_parse_error(doc: &str) -> bool {
+        matches!(parse_votable(doc.as_bytes()), Err(Error::Parse(_)))
+    }
+
+    const FIELDS: &str = r#"
+        <FIELD name="ra" ucd="pos.eq.ra;meta.main" unit="deg" datatype="double"/>
+        <FIELD name="n" datatype="int"><VALUES null="-2147483648"/></FIELD>"#;
+
+    #[test]
+    fn tabledata() {
+        let doc = votable(
+            FIELDS,
+            "<TABLEDATA><TR><TD>10.5</TD><TD>7</TD></TR><TR><TD> 20.25 </TD><TD>8</TD></TR>\
+             </TABLEDATA>",
+        );
+        let table = parse_votable(doc.as_bytes()).unwrap();
+        assert_eq!(table.fields.len(), 2);
+        assert_eq!(table.fields[0].ucd, "pos.eq.ra;meta.main");
+        assert_eq!(table.fields[0].unit, "deg");
+        assert_eq!(
+            table.rows,
+            vec![vec![Some(10.5), Some(7.0)], vec![Some(20.25), Some(8.0)]]
+        );
+    }
+
+    #[test]
+    fn missing_and_empty_cells() {
+        let doc = votable(
+            FIELDS,
+            "<TABLEDATA><TR><TD>10.5</TD><TD>7</TD></TR><TR><TD></TD><TD>8</TD></TR>\
+             <TR><TD>abc</TD><TD>9</TD></TR><TR><TD>30</TD></TR>\
+             <TR><TD>40</TD><TD>-2147483648</TD></TR></TABLEDATA>",
+        );
+        let table = parse_votable(doc.as_bytes()).unwrap();
+        assert_eq!(table.rows[1], vec![None, Some(8.0)]);
+        assert_eq!(table.rows[2], vec![None, Some(9.0)]);
+        assert_eq!(table.rows[3], vec![Some(30.0)]);
+        assert_eq!(table.rows[4], vec![Some(40.0), None]);
+        let double = |v: f64| v * 2.0;
+        let (values, skipped) = table.values(&[(0, &double), (1, &|v| v)]);
+        assert_eq!(values, vec![21.0, 7.0]);
+        assert_eq!(skipped, 4);
+    }
+
+    #[test]
+    fn binary() {
+        let doc = votable(
+            FIELDS,
+            r#"<BINARY><STREAM encoding="base64">QCUAAAAAAAAAAAAHQDRAAAAAAACAAAAA</STREAM></BINARY>"#,
+        );
+        let table = parse_votable(doc.as_bytes()).unwrap();
+        assert_eq!(
+            table.rows,
+            vec![vec![Some(10.5), Some(7.0)], vec![Some(20.25), None]]
+        );
+    }
+
+    #[test]
+    fn binary_errors() {
+        // An external stream.
+        let doc = votable(
+            FIELDS,
+            r#"<BINARY><STREAM href="http://localhost/table.bin"/></BINARY>"#,
+        );
+        assert!(is_parse_error(&doc));
+        // A stream ending in the middle of a row.
+        let doc = votable(
+            FIELDS,
+            r#"<BINARY><STREAM encoding="base64">P/AAAAAAAAAAAA==</STREAM></BINARY>"#,
+        );
+        assert!(is_parse_error(&doc));
+        // Not base64.
+        let doc = votable(
+            FIELDS,
+            r#"<BINARY><STREAM encoding="base64">%%%</STREAM></BINARY>"#,
+        );
+        assert!(is_parse_error(&doc));
+        // An unsupported serialization.
+        let doc = votable(FIELDS, r#"<FITS><STREAM href="table.fits"/></FITS>"#);
+        assert!(is_parse_error(&doc));
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(matches!(parse_votable(b"<VOTABLE>"), Err(Error::Parse(_))));
+        assert!(matches!(
+            parse_votable(b"<VOTABLE><RESOURCE/></VOTABLE>"),
+            Err(Error::NotFound(_))
+        ));
+        let doc = votable("", "<TABLEDATA/>");
+        assert!(is_parse_error(&doc));
+    }
+}
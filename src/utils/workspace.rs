@@ -0,0 +1,141 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use moc::storage::u64idx::U64MocStore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::utils::provenance::Provenance;
+use crate::utils::registry::Registry;
+
+/// Key under which the workspace is saved in the eframe storage.
+pub(crate) const WORKSPACE_KEY: &str = "workspace";
+
+// A MOC saved in a workspace, its content is kept as a FITS blob.
+#[derive(Serialize, Deserialize)]
+struct WorkspaceMoc {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    fits: Vec<u8>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    // None in the workspaces saved before the colours, the MOC then gets the colour of its rank.
+    #[serde(default)]
+    color: Option<[u8; 3]>,
+    #[serde(default)]
+    provenance: Provenance,
+}
+
+// The Workspace struct, everything needed to restore a session:
+// the MOCs (in loading order) with their names, sources, metadata, colours and provenance and
+// the windows that were open.
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Workspace {
+    mocs: Vec<WorkspaceMoc>,
+    pub(crate) open_mocs: BTreeSet<String>,
+    pub(crate) open_windows: BTreeSet<String>,
+}
+impl Workspace {
+    // #Definition
+    //      capture saves every MOC of the registry, in loading order, along with the open windows.
+    // #Args
+    //  *   `registry`: the MOCs of the app
+    //  *   `open_mocs`: the names of the MOCs whose info window is open
+    //  *   `open_windows`: the names of the open main windows
+    // #Errors
+    //      Error if a MOC can't be serialized.
+    pub(crate) fn capture(
+        registry: &Registry,
+        open_mocs: BTreeSet<String>,
+        open_windows: BTreeSet<String>,
+    ) -> Result<Self, Error> {
+        let entries = registry.entries();
+        let mut mocs = Vec::with_capacity(entries.len());
+        for (id, entry) in entries {
+            let fits = U64MocStore.to_fits_buff(id, None)?.into_vec();
+            mocs.push(WorkspaceMoc {
+                name: entry.name.clone(),
+                fits,
+                source: entry.source.clone(),
+                metadata: entry.metadata.clone(),
+                color: Some(entry.color),
+                provenance: registry.provenance(id),
+            });
+        }
+
+        Ok(Self {
+            mocs,
+            open_mocs,
+            open_windows,
+        })
+    }
+
+    // #Definition
+    //      restore loads the saved MOCs back into the store and the registry, keeping their names,
+    //      colours and provenance. The restored MOCs are not recorded in the history.
+    // #Args
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if a MOC can't be read back, the other MOCs are still restored.
+    pub(crate) fn restore(&self, registry: &mut Registry) -> Result<(), Error> {
+        let mut errors = Vec::default();
+        for moc in &self.mocs {
+            let restored = U64MocStore
+                .load_from_fits(moc.fits.as_slice())
+                .map_err(Error::from)
+                .and_then(|id| {
+                    registry.insert(&moc.name, id, moc.source.clone(), moc.metadata.clone())?;
+                    if let Some(color) = moc.color {
+                        registry.set_color(id, color);
+                    }
+                    registry.set_provenance(id, moc.provenance.clone());
+                    Ok(())
+                });
+            if let Err(e) = restored {
+                errors.push(format!("{}: {e}", moc.name));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Moc(format!(
+                "Some MOCs could not be restored:\n{}",
+                errors.join("\n")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_keeps_colours_and_provenance() {
+        let mut registry = Registry::default();
+        let a = U64MocStore.from_cone(10.0, 20.0, 1.0, 6, 2).unwrap();
+        registry
+            .load("/data/a.fits", "a", a, BTreeMap::new())
+            .unwrap();
+        let b = U64MocStore.not(a).unwrap();
+        registry.insert("b", b, None, BTreeMap::new()).unwrap();
+        registry.added("Complement", &[a], "", &[b]).unwrap();
+        registry.set_color(b, [1, 2, 3]);
+
+        let workspace = Workspace::capture(&registry, BTreeSet::new(), BTreeSet::new()).unwrap();
+        let saved = serde_json::to_string(&workspace).unwrap();
+        let workspace: Workspace = serde_json::from_str(&saved).unwrap();
+        let mut restored = Registry::default();
+        workspace.restore(&mut restored).unwrap();
+
+        let b = restored.find("b").unwrap();
+        assert_eq!(restored.get(b).unwrap().color, [1, 2, 3]);
+        assert_eq!(restored.provenance(b).expression(), "Complement(a)");
+        let a = restored.find("a").unwrap();
+        assert_eq!(restored.provenance(a).step(), "Load (a.fits)");
+        for id in restored.ids() {
+            U64MocStore.drop(id).unwrap();
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use csv::{ReaderBuilder, StringRecord, Trim};
+use egui::{Context, Ui};
+
+use crate::controllers::creation::CreationType;
+use crate::controllers::filter::{
+    guess_column, guess_delimiter, is_header, Role, DEC, DELIMITERS, JD, RA,
+};
+use crate::error::Error;
+use crate::utils::compression::decompress;
+
+// The number of rows shown in the preview.
+const PREVIEW_ROWS: usize = 10;
+const RADIUS: Role = Role {
+    label: "Radius (°)",
+    names: &["radius", "rad", "r", "radius_deg"],
+};
+const JD_MIN: Role = Role {
+    label: "JD min",
+    names: &["jd_min", "t_min", "start", "jd_start", "tmin"],
+};
+const JD_MAX: Role = Role {
+    label: "JD max",
+    names: &["jd_max", "t_max", "end", "stop", "jd_stop", "tmax"],
+};
+const FREQ_MIN: Role = Role {
+    label: "Frequency min (Hz)",
+    names: &["f_min", "freq_min", "fmin"],
+};
+const FREQ_MAX: Role = Role {
+    label: "Frequency max (Hz)",
+    names: &["f_max", "freq_max", "fmax"],
+};
+const WL_MIN: Role = Role {
+    label: "Wavelength min (m)",
+    names: &["em_min", "lambda_min", "wl_min"],
+};
+const WL_MAX: Role = Role {
+    label: "Wavelength max (m)",
+    names: &["em_max", "lambda_max", "wl_max"],
+};
+const UNIQ: Role = Role {
+    label: "UNIQ",
+    names: &["uniq", "nuniq"],
+};
+const VALUE: Role = Role {
+    label: "Value",
+    names: &["value", "val", "probdensity", "prob", "density"],
+};
+
+// #Definition
+//      roles gives the columns needed to create a MOC of the given type, in the order
+//      expected by the creation functions.
+fn roles(typ: CreationType) -> &'static [Role] {
+    match typ {
+        CreationType::Coo | CreationType::Polygon => &[RA, DEC],
+        CreationType::SmallCone | CreationType::LargeCone => &[RA, DEC, RADIUS],
+        CreationType::DecimalJd => &[JD],
+        CreationType::DecimalJdRange => &[JD_MIN, JD_MAX],
+        CreationType::FrequencyRange => &[FREQ_MIN, FREQ_MAX],
+        CreationType::WavelengthRange => &[WL_MIN, WL_MAX],
+        CreationType::ValuedCells => &[UNIQ, VALUE],
+        _ => &[],
+    }
+}
+
+// The extensions of the csv files, compressed or not.
+pub(crate) const CSV_EXTENSIONS: [&str; 5] = ["csv", "txt", "tsv", "gz", "bz2"];
+
+// The CsvImport struct, the import dialog of a csv file: it previews the file and maps its
+// columns to the values needed by the MOC creation.
+#[derive(Clone)]
+pub(crate) struct CsvImport {
+    pub(crate) typ: CreationType,
+    pub(crate) file_name: String,
+    content: String,
+    delimiter: u8,
+    has_headers: bool,
+    // The column of each role of the creation type.
+    columns: Vec<usize>,
+    error: Option<String>,
+}
+impl CsvImport {
+    // #Definition
+    //      new creates the import dialog of a csv file, guessing its delimiter, whether it has
+    //      a header row and the columns of the creation type.
+    // #Args
+    //  *   `typ`: the type of the MOC to create
+    //  *   `file_name`: the name of the csv file
+    //  *   `data`: the content of the file, possibly gzip or bzip2 compressed
+    // #Errors
+    //      Error if the content can't be decompressed or is not valid UTF-8.
+    pub(crate) fn new(typ: CreationType, file_name: String, data: &[u8]) -> Result<Self, Error> {
+        let content = String::from_utf8(decompress(data)?.into_owned())
+            .map_err(|e| Error::Parse(format!("Invalid UTF-8 content: {e}")))?;
+        let first_line = content
+            .lines()
+            .find(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .unwrap_or_default();
+        let delimiter = guess_delimiter(first_line);
+        let has_headers = is_header(first_line, delimiter);
+        let mut import = Self {
+            typ,
+            file_name,
+            content,
+            delimiter,
+            has_headers,
+            columns: Vec::default(),
+            error: None,
+        };
+        import.guess_columns();
+        Ok(import)
+    }
+
+    // #Definition
+    //      show shows the import dialog.
+    // #Args
+    //  *   `ctx`: the app's context
+    //  *   `open`: set to false when the dialog is closed
+    // #Returns
+    //      true when "Create" is clicked.
+    pub(crate) fn show(&mut self, ctx: &Context, open: &mut bool) -> bool {
+        let mut create = false;
+        egui::Window::new("CSV import")
+            .open(open)
+            .default_width(500.0)
+            .show(ctx, |ui| create = self.ui(ui));
+        create
+    }
+
+    // #Definition
+    //      ui shows the delimiter and header options, the column of each role and a preview
+    //      of the first rows, it returns true when "Create" is clicked.
+    fn ui(&mut self, ui: &mut Ui) -> bool {
+        ui.label(format!("File: {}", self.file_name));
+        ui.label(format!("Creation type: {}", self.typ));
+        let (headers, rows) = self.preview();
+        let mut changed = false;
+        egui::Grid::new("csv_import_options")
+            .num_columns(2)
+            .spacing([10.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Delimiter:");
+                let selected = DELIMITERS
+                    .iter()
+                    .find(|(d, _)| *d == self.delimiter)
+                    .map_or("", |(_, name)| *name);
+                egui::ComboBox::from_id_source("csv_delimiter")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (d, name) in DELIMITERS {
+                            changed |= ui.selectable_value(&mut self.delimiter, d, name).changed();
+                        }
+                    });
+                ui.end_row();
+                ui.label("Header row:");
+                changed |= ui.checkbox(&mut self.has_headers, "").changed();
+                ui.end_row();
+                for (role, column) in roles(self.typ).iter().zip(self.columns.iter_mut()) {
+                    ui.label(format!("{}:", role.label));
+                    egui::ComboBox::from_id_source(role.label)
+                        .selected_text(column_name(&headers, *column))
+                        .show_ui(ui, |ui| {
+                            for i in 0..headers.len() {
+                                ui.selectable_value(column, i, column_name(&headers, i));
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+        if changed {
+            self.guess_columns();
+        }
+
+        ui.separator();
+        egui::ScrollArea::both().max_height(250.0).show(ui, |ui| {
+            egui::Grid::new("csv_import_preview")
+                .striped(true)
+                .spacing([10.0, 2.0])
+                .show(ui, |ui| {
+                    for i in 0..headers.len() {
+                        let role = roles(self.typ)
+                            .iter()
+                            .zip(&self.columns)
+                            .find(|(_, c)| **c == i)
+                            .map(|(r, _)| r.label);
+                        match role {
+                            Some(role) => {
+                                ui.strong(format!("{} ({role})", column_name(&headers, i)))
+                            }
+                            None => ui.label(column_name(&headers, i)),
+                        };
+                    }
+                    ui.end_row();
+                    for row in &rows {
+                        for field in row {
+                            ui.label(field);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        ui.separator();
+
+        let create = ui.button("Create").clicked();
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+        create
+    }
+
+    // #Definition
+    //      set_error sets the error shown in the dialog, e.g. when the MOC creation fails.
+    pub(crate) fn set_error(&mut self, e: Option<Error>) {
+        self.error = e.map(|e| e.to_string());
+    }
+
+    // #Definition
+    //      values gives the values of the mapped columns, row after row, in the order of the
+    //      roles of the creation type (e.g. [ra_1, dec_1, ra_2, dec_2, ...]).
+    // #Errors
+    //      Error if the csv can't be read or if a value is not a number.
+    pub(crate) fn values(&self) -> Result<Vec<f64>, Error> {
+        let mut values = Vec::default();
+        for record in self.records() {
+            let record = record?;
+            for column in &self.columns {
+                values.push(field(&record, *column)?);
+            }
+        }
+        Ok(values)
+    }
+
+    // #Definition
+    //      uniq_values gives the (UNIQ, value) pairs of the mapped columns, the UNIQs are
+    //      parsed as integers so that they are not rounded.
+    // #Errors
+    //      Error if the csv can't be read or if a UNIQ or a value is not a number.
+    pub(crate) fn uniq_values(&self) -> Result<Vec<(u64, f64)>, Error> {
+        let [uniq, value] = self.columns[..] else {
+            return Err(Error::Parse(String::from(
+                "UNIQ and value columns expected",
+            )));
+        };
+        self.records()
+            .map(|record| {
+                let record = record?;
+                Ok((field(&record, uniq)?, field(&record, value)?))
+            })
+            .collect()
+    }
+
+    // The records of the csv, the header row and the comment lines (starting with '#') left
+    // aside.
+    fn records(&self) -> impl Iterator<Item = Result<StringRecord, Error>> + '_ {
+        ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .has_headers(self.has_headers)
+            .flexible(true)
+            .trim(Trim::All)
+            .comment(Some(b'#'))
+            .from_reader(self.content.as_bytes())
+            .into_records()
+            .map(|record| record.map_err(|e| Error::Parse(format!("Invalid csv: {e}"))))
+    }
+
+    // The header names (or column numbers) and the first rows of the csv.
+    fn preview(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let rows: Vec<Vec<String>> = self
+            .records()
+            .take(PREVIEW_ROWS)
+            .filter_map(Result::ok)
+            .map(|r| r.iter().map(String::from).collect())
+            .collect();
+        let n_columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let mut headers: Vec<String> = if self.has_headers {
+            let mut reader = ReaderBuilder::new()
+                .delimiter(self.delimiter)
+                .trim(Trim::All)
+                .comment(Some(b'#'))
+                .from_reader(self.content.as_bytes());
+            reader
+                .headers()
+                .map(|h| h.iter().map(String::from).collect())
+                .unwrap_or_default()
+        } else {
+            Vec::default()
+        };
+        for i in headers.len()..n_columns {
+            headers.push(format!("Column {}", i + 1));
+        }
+        (headers, rows)
+    }
+
+    // #Definition
+    //      guess_columns maps each role to the column whose header is one of the role's usual
+    //      names, or to the column at the same position.
+    fn guess_columns(&mut self) {
+        let (headers, _) = self.preview();
+        self.columns = roles(self.typ)
+            .iter()
+            .enumerate()
+            .map(|(i, role)| guess_column(&headers, role).unwrap_or(i))
+            .collect();
+    }
+}
+
+// The name of a column shown to the user.
+fn column_name(headers: &[String], column: usize) -> String {
+    match headers.get(column) {
+        Some(header) => format!("{}: {header}", column + 1),
+        None => format!("{}: (missing)", column + 1),
+    }
+}
+
+// #Definition
+//      field parses the value of a record's column.
+// #Errors
+//      Error, giving the line of the record, if the column is missing or is not a number.
+fn field<T: std::str::FromStr>(record: &StringRecord, column: usize) -> Result<T, Error> {
+    let line = record.position().map_or(0, |p| p.line());
+    let value = record
+        .get(column)
+        .ok_or_else(|| Error::Parse(format!("Line {line}: no column {}", column + 1)))?;
+    value.parse().map_err(|_| {
+        Error::Parse(format!(
+            "Line {line}: '{value}' (column {}) is not a valid number",
+            column + 1
+        ))
+    })
+}
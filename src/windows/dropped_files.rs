@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use egui::{Align2, Color32, Context, Id, LayerId, Order, TextStyle};
+use moc::storage::u64idx::common::MocQType;
+
+use crate::error::Error;
+use crate::utils::commons::{dropped_file_content, err, type_reading};
+use crate::utils::compression::unpack;
+use crate::utils::detect::detect_reading;
+use crate::utils::provenance::fits_metadata;
+use crate::utils::registry::Registry;
+
+// A dropped (or downloaded) JSON or ASCII file whose MOC type is ambiguous, waiting for the user to give it.
+struct PendingFile {
+    // The path or the URL of the file.
+    source: String,
+    name: String,
+    reading: &'static str,
+    data: Vec<u8>,
+}
+
+// The DroppedFiles struct, loads the MOC files dropped on the app (or downloaded).
+// The format and the type of the MOCs are detected, the type is asked when it is ambiguous.
+#[derive(Default)]
+pub(crate) struct DroppedFiles {
+    pending: Vec<PendingFile>,
+}
+impl DroppedFiles {
+    // #Definition
+    //      ui loads the files dropped on the app, shows an overlay while files are dragged
+    //      over it and a window to give the type of the MOCs of the pending files.
+    // #Args
+    //  *   `ctx`: the app's context
+    //  *   `registry`: the MOCs of the app, the loaded MOCs are added to it
+    pub(crate) fn ui(&mut self, ctx: &Context, registry: &mut Registry) {
+        preview_hovered_files(ctx);
+
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in &dropped {
+            if let Err(e) = self.load(file, registry) {
+                err(&e);
+            }
+        }
+
+        if self.pending.is_empty() {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Dropped files")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Type of the MOC in:");
+                egui::Grid::new("dropped_files_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 4.0])
+                    .show(ui, |ui| {
+                        self.pending.retain(|file| {
+                            ui.label(&file.name);
+                            let qty = ui.horizontal(qty_buttons).inner;
+                            ui.end_row();
+                            let Some(qty) = qty else {
+                                return true;
+                            };
+                            if let Err(e) =
+                                type_reading(file.reading, &qty, &file.data).and_then(|id| {
+                                    registry.load(&file.source, &file.name, id, BTreeMap::new())
+                                })
+                            {
+                                err(&e);
+                            }
+                            false
+                        });
+                    });
+            });
+        if !open {
+            self.pending.clear();
+        }
+    }
+
+    // #Definition
+    //      load loads a dropped file.
+    // #Args
+    //  *   `file`: the dropped file
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if the file can't be read, if it is not a MOC file or if the MOC can't be loaded.
+    fn load(&mut self, file: &egui::DroppedFile, registry: &mut Registry) -> Result<(), Error> {
+        let (name, data) = dropped_file_content(file)?;
+        let source = match &file.path {
+            Some(path) => path.display().to_string(),
+            None => name.clone(),
+        };
+        self.add_content(&source, &name, data, registry)
+    }
+
+    // #Definition
+    //      add_content loads the MOCs of a file content (every MOC of a zip archive), keeping
+    //      for later the MOCs whose type is ambiguous.
+    // #Args
+    //  *   `source`: the path or the URL of the file
+    //  *   `name`: the file name
+    //  *   `data`: the file content
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if it is not a MOC file or if the MOC can't be loaded.
+    pub(crate) fn add_content(
+        &mut self,
+        source: &str,
+        name: &str,
+        data: Vec<u8>,
+        registry: &mut Registry,
+    ) -> Result<(), Error> {
+        for (name, data) in unpack(name, data)? {
+            match detect_reading(&data) {
+                Ok(id) => {
+                    registry.load(source, &name, id, fits_metadata(&data))?;
+                }
+                Err(Error::Ambiguous(_)) => {
+                    let json = data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+                    self.pending.push(PendingFile {
+                        source: source.to_string(),
+                        name,
+                        reading: if json { "json" } else { "ascii" },
+                        data,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+// #Definition
+//      qty_buttons shows a button per MOC type, it returns the type clicked if any.
+fn qty_buttons(ui: &mut egui::Ui) -> Option<MocQType> {
+    let mut clicked = None;
+    if ui.button("Space").clicked() {
+        clicked = Some(MocQType::Space);
+    }
+    if ui.button("Time").clicked() {
+        clicked = Some(MocQType::Time);
+    }
+    if ui.button("Spacetime").clicked() {
+        clicked = Some(MocQType::TimeSpace);
+    }
+    if ui.button("Frequency").clicked() {
+        clicked = Some(MocQType::Frequency);
+    }
+    clicked
+}
+
+// #Definition
+//      preview_hovered_files darkens the app and lists the files being dragged over it.
+fn preview_hovered_files(ctx: &Context) {
+    let names: Vec<String> = ctx.input(|i| {
+        i.raw
+            .hovered_files
+            .iter()
+            .map(|file| match &file.path {
+                Some(path) => path.display().to_string(),
+                None if !file.mime.is_empty() => file.mime.clone(),
+                None => String::from("???"),
+            })
+            .collect()
+    });
+    if names.is_empty() {
+        return;
+    }
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("file_drop_target")));
+    let screen_rect = ctx.screen_rect();
+    painter.rect_filled(screen_rect, 0.0, Color32::from_black_alpha(192));
+    painter.text(
+        screen_rect.center(),
+        Align2::CENTER_CENTER,
+        format!("Drop to load:\n{}", names.join("\n")),
+        TextStyle::Heading.resolve(&ctx.style()),
+        Color32::WHITE,
+    );
+}
@@ -0,0 +1,51 @@
+use egui::{Context, TextEdit};
+
+use crate::controllers::expression::{evaluate, SYNTAX};
+use crate::utils::registry::Registry;
+
+// The ExpressionBar struct, a bar at the bottom of the app evaluating an expression over the
+// names of the MOCs, e.g. "(a | b) & !c - degrade(d, 8)".
+#[derive(Default)]
+pub(crate) struct ExpressionBar {
+    expression: String,
+    name: String,
+    error: Option<String>,
+}
+impl ExpressionBar {
+    // #Definition
+    //      ui shows the bar, the expression is evaluated with the Evaluate button or Enter.
+    // #Args
+    //  *   `ctx`: the app's context
+    //  *   `registry`: the MOCs of the app, the result is added to it
+    pub(crate) fn ui(&mut self, ctx: &Context, registry: &mut Registry) {
+        egui::TopBottomPanel::bottom("expression_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Expression:").on_hover_text(SYNTAX);
+                let response = ui.add(
+                    TextEdit::singleline(&mut self.expression)
+                        .hint_text("(a | b) & !c - degrade(d, 8)")
+                        .desired_width(ui.available_width() - 240.0),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut self.name)
+                        .hint_text("Name")
+                        .desired_width(120.0),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Evaluate").clicked() || submitted {
+                    match evaluate(registry, &self.expression, self.name.trim()) {
+                        Ok(_) => {
+                            self.error = None;
+                            self.name.clear();
+                        }
+                        Err(e) => self.error = Some(e.to_string()),
+                    }
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+    }
+}
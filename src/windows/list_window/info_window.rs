@@ -1,23 +1,53 @@
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::ops::Range;
 
-use egui::Color32;
+use egui::plot::{Bar, BarChart, Plot};
+use egui::{Color32, Pos2, Rect, Sense};
+use mapproj::{
+    img2celestial::Img2Celestial, img2proj::ReversedEastPngImgXY2ProjXY, pseudocyl::mol::Mol,
+    CanonicalProjection, CenteredProjection, ImgXY, LonLat,
+};
 use moc::storage::u64idx::{common::MocQType, U64MocStore};
 
 use crate::{
+    controllers::{
+        creation::JD_TO_USEC,
+        query::{contains, Point},
+        stats::{fmt_size, smoc_stats, SmocStats},
+    },
+    error::Error,
     utils::{
-        commons::{fmt_qty, to_file},
-        namestore::get_name,
+        commons::{err, fmt_qty, freq_range_hz, to_file},
+        provenance::Provenance,
+        registry::Registry,
+    },
+    windows::{
+        sky_view::lon_lat_deg,
+        timeline::{jd_label, timeline_ui, TimeLayer},
+        View, Window,
     },
-    windows::{View, Window},
 };
 
-#[derive(Clone, PartialEq, Default, Eq)]
+// The size in pixels of the preview of an S-MOC.
+const IMG_SIZE: (u16, u16) = (300, 150);
+
+#[derive(Clone, PartialEq, Default)]
 pub struct InfoWindow {
     pub id: usize,
     texture: Option<egui::TextureHandle>,
     size: usize,
     info: String,
-    name: String,
+    pub(crate) name: String,
+    // The file (or URL) the MOC was loaded or created from.
+    source: Option<String>,
+    // The keywords of the FITS header the MOC was loaded from.
+    metadata: BTreeMap<String, String>,
+    // The ranges of a T-MOC, in microseconds since JD=0.
+    ranges: Vec<Range<u64>>,
+    provenance: Provenance,
+    // The statistics of an S-MOC, computed the first time they are shown.
+    stats: Option<Result<SmocStats, String>>,
 }
 impl Window for InfoWindow {
     fn name(&self) -> &'static str {
@@ -25,20 +55,26 @@ impl Window for InfoWindow {
         s
     }
 
-    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
         egui::Window::new(self.name())
             .open(open)
             .resizable(false)
             .show(ctx, |ui| {
                 use crate::windows::View as _;
-                self.ui(ui);
+                self.ui(ui, registry);
             });
     }
 }
 
 impl View for InfoWindow {
-    fn ui(&mut self, ui: &mut egui::Ui) {
-        let qty = U64MocStore.get_qty_type(self.id).unwrap();
+    fn ui(&mut self, ui: &mut egui::Ui, registry: &mut Registry) {
+        let qty = match U64MocStore.get_qty_type(self.id) {
+            Ok(qty) => qty,
+            Err(e) => {
+                ui.label(e);
+                return;
+            }
+        };
 
         ui.horizontal(|ui| {
             ui.label("MOC type:");
@@ -47,84 +83,307 @@ impl View for InfoWindow {
 
         //ui.add(egui::Slider::new(&mut self.size, 0..=150));
 
+        if let Some(source) = &self.source {
+            ui.horizontal(|ui| {
+                ui.label("Source:");
+                ui.label(source);
+            });
+        }
+        if !self.metadata.is_empty() {
+            egui::CollapsingHeader::new("Metadata")
+                .id_source(("metadata", self.id))
+                .show(ui, |ui| {
+                    egui::Grid::new(("metadata_grid", self.id))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (key, value) in &self.metadata {
+                                ui.label(key);
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+
+        if self.provenance.inputs.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label("Origin:");
+                ui.label(self.provenance.step());
+            });
+        } else {
+            egui::CollapsingHeader::new("Provenance")
+                .id_source(("provenance", self.id))
+                .show(ui, |ui| {
+                    ui.label(self.provenance.expression());
+                    provenance_ui(ui, &self.provenance, &mut 0);
+                });
+        }
+
         match qty {
             MocQType::Space => {
                 ui.label(&self.info);
-                let texture = &self.texture.clone().unwrap();
-                ui.add(egui::Image::new(texture, texture.size_vec2()).bg_fill(Color32::WHITE));
-                if ui.button("Download image").clicked() {
-                    let _ = to_file(
-                        &get_name(self.id).unwrap(),
-                        ".png",
-                        "image/x-png",
-                        U64MocStore.to_png(self.id, 300).unwrap(),
+                self.stats_ui(ui, registry);
+                if let Some(texture) = &self.texture {
+                    let response = ui.add(
+                        egui::Image::new(texture, texture.size_vec2())
+                            .bg_fill(Color32::WHITE)
+                            .sense(Sense::hover()),
                     );
+                    match response
+                        .hover_pos()
+                        .and_then(|pos| img_lonlat(response.rect, pos))
+                    {
+                        Some(lonlat) => self.cell_ui(ui, &lonlat),
+                        None => {
+                            ui.label("Hover the preview to inspect its cells.");
+                        }
+                    }
+                }
+                if ui.button("Download image").clicked() {
+                    let _ = U64MocStore
+                        .to_png(self.id, 300)
+                        .map_err(Error::from)
+                        .and_then(|png| to_file(&self.name, ".png", "image/x-png", png))
+                        .map_err(|e| err(&e));
                 }
             }
             MocQType::Time => {
                 ui.label(&self.info);
+                let layer = TimeLayer {
+                    name: &self.name,
+                    ranges: &self.ranges,
+                    color: registry
+                        .get(self.id)
+                        .map_or([50, 120, 220], |entry| entry.color),
+                };
+                timeline_ui(ui, "info_timeline", &[layer]);
             }
             MocQType::TimeSpace => {
                 ui.label(&self.info);
             }
-            MocQType::Frequency => unreachable!(),
+            MocQType::Frequency => {
+                ui.label(&self.info);
+            }
         };
     }
 }
 
 impl InfoWindow {
-    pub fn new(ctx: &egui::Context, id: usize) -> Result<Self, String> {
+    // #Definition
+    //      new creates the info window of a MOC, with its preview for S-MOCs.
+    // #Errors
+    //      Error if the MOC is not in the store or in the registry.
+    pub fn new(ctx: &egui::Context, id: usize, registry: &Registry) -> Result<Self, Error> {
         let mut texture: Option<egui::TextureHandle> = None;
-        if let Ok(i) = U64MocStore.to_image(id, 150) {
+        if let Ok(i) = U64MocStore.to_image(id, IMG_SIZE.1) {
             texture =
                 // Load the texture only once.
                 Some(ctx.load_texture(
                     "moc_img",
-                    egui::ColorImage::from_rgba_unmultiplied([300, 150], i.borrow()),
+                    egui::ColorImage::from_rgba_unmultiplied(
+                        [IMG_SIZE.0 as usize, IMG_SIZE.1 as usize],
+                        i.borrow(),
+                    ),
                     Default::default(),
                 ));
         }
 
         let mut info = String::default();
-        match U64MocStore.get_qty_type(id) {
-            Ok(qty) => match qty {
-                MocQType::Space => {
-                    if let Ok(s) = U64MocStore.get_smoc_depth(id) {
-                        info = format!(
-                            "Depth: {s}, Coverage: {}",
-                            U64MocStore.get_coverage_percentage(id).unwrap()
-                        )
-                    }
+        let mut ranges = Vec::default();
+        match U64MocStore.get_qty_type(id)? {
+            MocQType::Space => {
+                if let (Ok(s), Ok(coverage)) = (
+                    U64MocStore.get_smoc_depth(id),
+                    U64MocStore.get_coverage_percentage(id),
+                ) {
+                    info = format!("Depth: {s}, Coverage: {coverage}")
                 }
-                MocQType::Time => {
-                    if let Ok(t) = U64MocStore.get_tmoc_depth(id) {
-                        info = format!("Depth: {t}")
-                    }
+            }
+            MocQType::Time => {
+                if let Ok(t) = U64MocStore.get_tmoc_depth(id) {
+                    info = format!("Depth: {t}")
                 }
-                MocQType::Frequency => {
-                    return Err(String::from("Frequency MOCs are not supported"))
+                if let Ok(r) = U64MocStore.to_ranges(id) {
+                    if let (Some(first), Some(last)) = (r.first(), r.last()) {
+                        info.push_str(&format!(
+                            "\nFrom: {}\nTo: {}",
+                            jd_label(first.start as f64 / JD_TO_USEC),
+                            jd_label(last.end as f64 / JD_TO_USEC)
+                        ));
+                    }
+                    ranges = r;
                 }
-                MocQType::TimeSpace => {
-                    if let Ok(st) = U64MocStore.get_stmoc_depths(id) {
-                        info = format!(
-                            "Depth S: {}\nDepth T: {}",
-                            st.0,
-                            st.1
-                        )
+            }
+            MocQType::Frequency => {
+                if let Ok(f) = U64MocStore.get_fmoc_depth(id) {
+                    info = format!("Depth: {f}");
+                    if let Ok(Some((min, max))) = freq_range_hz(id) {
+                        info.push_str(&format!("\nFrequencies: [{min:e}, {max:e}] Hz"));
                     }
                 }
-            },
-            Err(e) => return Err(e),
+            }
+            MocQType::TimeSpace => {
+                if let Ok(st) = U64MocStore.get_stmoc_depths(id) {
+                    info = format!("Depth T: {}\nDepth S: {}", st.0, st.1)
+                }
+            }
         }
 
-        let name = get_name(id).unwrap();
+        let entry = registry
+            .get(id)
+            .ok_or_else(|| Error::NotFound(format!("MOC '{id}'")))?;
+        let provenance = registry.provenance(id);
 
         Ok(Self {
             id,
             texture,
             info,
-            name,
+            name: entry.name.clone(),
+            source: entry.source.clone(),
+            metadata: entry.metadata.clone(),
             size: 150,
+            ranges,
+            provenance,
+            stats: None,
         })
     }
+
+    // #Definition
+    //      stats_ui shows the statistics of an S-MOC: its area, its numbers of ranges and cells,
+    //      its size in each format and a histogram of its cells per depth.
+    // #Args
+    //  *   `registry`: the MOCs of the app, giving the provenance written in FITS
+    fn stats_ui(&mut self, ui: &mut egui::Ui, registry: &Registry) {
+        egui::CollapsingHeader::new("Statistics")
+            .id_source(("stats", self.id))
+            .show(ui, |ui| {
+                let stats = self.stats.get_or_insert_with(|| {
+                    smoc_stats(self.id, &registry.provenance(self.id)).map_err(|e| e.to_string())
+                });
+                let stats = match stats {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+                        return;
+                    }
+                };
+                egui::Grid::new(("stats_grid", self.id))
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Area:");
+                        ui.label(format!(
+                            "{:.6} deg², {:.3} arcmin², {:.6e} sr",
+                            stats.area_deg2(),
+                            stats.area_arcmin2(),
+                            stats.area_sr
+                        ));
+                        ui.end_row();
+                        ui.label("Ranges:");
+                        ui.label(stats.n_ranges.to_string());
+                        ui.end_row();
+                        ui.label("Cells:");
+                        ui.label(stats.n_cells().to_string());
+                        ui.end_row();
+                        for (format, size) in &stats.sizes {
+                            ui.label(format!("{format} size:"));
+                            ui.label(fmt_size(*size));
+                            ui.end_row();
+                        }
+                    });
+                ui.label("Cells per depth:");
+                let bars: Vec<Bar> = stats
+                    .cells_per_depth
+                    .iter()
+                    .enumerate()
+                    .map(|(depth, n)| {
+                        Bar::new(depth as f64, *n as f64).name(format!("Depth {depth}"))
+                    })
+                    .collect();
+                Plot::new(("stats_histogram", self.id))
+                    .height(120.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .allow_boxed_zoom(false)
+                    .show_x(false)
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(
+                            BarChart::new(bars)
+                                .width(0.8)
+                                .color(Color32::from_rgb(200, 60, 60)),
+                        );
+                    });
+            });
+    }
+
+    // #Definition
+    //      cell_ui shows the position hovered on the preview of an S-MOC, the HEALPix cell
+    //      holding it at the depth of the MOC and whether this cell is in the MOC.
+    // #Args
+    //  *   `lonlat`: the hovered position, in radians
+    fn cell_ui(&self, ui: &mut egui::Ui, lonlat: &LonLat) {
+        let (ra, dec) = lon_lat_deg(lonlat);
+        ui.label(format!("RA: {ra:.6}°, Dec: {dec:.6}°"));
+        let Ok(depth) = U64MocStore.get_smoc_depth(self.id) else {
+            return;
+        };
+        let hash = healpix::nested::hash(depth, lonlat.lon(), lonlat.lat());
+        let uniq = healpix::nested::to_uniq_ivoa(depth, hash);
+        ui.label(format!(
+            "HEALPix cell: depth {depth}, index {hash}, UNIQ {uniq}"
+        ));
+        let point = Point {
+            pos: Some((ra, dec)),
+            jd: None,
+        };
+        match contains(self.id, &[point]).map(|inside| inside.first().copied()) {
+            Ok(Some(true)) => ui.label("The cell is in the MOC."),
+            Ok(_) => ui.label("The cell is not in the MOC."),
+            Err(e) => ui.label(e.to_string()),
+        };
+    }
+}
+
+// #Definition
+//      img_lonlat inverts the projection of the preview of an S-MOC, made by the moc library:
+//      a Mollweide projection centred on (0, 0), East being on the left.
+// #Args
+//  *   `rect`: where the preview is shown
+//  *   `pos`: a position in the preview
+// #Returns
+//      The position on the sky, in radians, None out of the projected sky.
+fn img_lonlat(rect: Rect, pos: Pos2) -> Option<LonLat> {
+    let proj = Mol::new();
+    let bounds = proj.bounds();
+    let (x_bounds, y_bounds) = (bounds.x_bounds().as_ref()?, bounds.y_bounds().as_ref()?);
+    let img2proj = ReversedEastPngImgXY2ProjXY::from(IMG_SIZE, (x_bounds, y_bounds));
+    let img2cel = Img2Celestial::new(img2proj, CenteredProjection::new(proj));
+    let offset = pos - rect.min;
+    img2cel.img2lonlat(&ImgXY::new(
+        (offset.x / rect.width() * IMG_SIZE.0 as f32) as f64,
+        (offset.y / rect.height() * IMG_SIZE.1 as f32) as f64,
+    ))
+}
+
+// #Definition
+//      provenance_ui shows the provenance of a MOC as a tree, each derived MOC being a collapsing
+//      node holding the MOCs it was obtained from.
+// #Args
+//  *   `provenance`: the provenance to show
+//  *   `node`: the index of the node in the tree, used as id of the collapsing nodes
+fn provenance_ui(ui: &mut egui::Ui, provenance: &Provenance, node: &mut usize) {
+    *node += 1;
+    let title = format!("{}: {}", provenance.name, provenance.step());
+    if provenance.inputs.is_empty() {
+        ui.label(title);
+        return;
+    }
+    egui::CollapsingHeader::new(title)
+        .id_source(("provenance_node", *node))
+        .default_open(true)
+        .show(ui, |ui| {
+            for input in &provenance.inputs {
+                provenance_ui(ui, input, node);
+            }
+        });
 }
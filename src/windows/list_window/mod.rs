@@ -5,8 +5,10 @@ use egui_extras::{Column, TableBuilder};
 use moc::storage::u64idx::U64MocStore;
 use std::collections::BTreeSet;
 
-use crate::utils::commons::{err, to_file, set_open};
-use crate::utils::namestore::{self, get_store, list_ids, rename};
+use crate::error::Error;
+use crate::utils::commons::{err, set_open, to_file};
+use crate::utils::provenance;
+use crate::utils::registry::Registry;
 
 use self::info_window::InfoWindow;
 
@@ -18,12 +20,11 @@ use super::Window;
 pub struct InfoWindows {
     infouis: Vec<Box<InfoWindow>>,
     open: BTreeSet<String>,
-    filenames: Vec<(usize, (String, usize))>,
+    filenames: Vec<(usize, String)>,
     name: String,
 }
 impl InfoWindows {
-    pub fn from_mocs(infouis: Vec<Box<InfoWindow>>) -> Self {
-        let open = BTreeSet::new();
+    pub fn from_mocs(infouis: Vec<Box<InfoWindow>>, open: BTreeSet<String>) -> Self {
         let filenames = Vec::default();
         Self {
             infouis,
@@ -33,15 +34,12 @@ impl InfoWindows {
         }
     }
 
-    pub fn checkboxes(&mut self, ui: &mut Ui) {
-        let binding = get_store().read().unwrap().clone();
-        for file in binding.iter() {
-            let owned_file = (file.0.to_owned(), file.1.to_owned());
-            if !self.filenames.contains(&owned_file) {
-                self.filenames.push(owned_file);
-            }
-        }
-        self.filenames.sort_by(|a, b| a.1 .1.cmp(&b.1 .1));
+    pub fn checkboxes(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        self.filenames = registry
+            .entries()
+            .into_iter()
+            .map(|(id, entry)| (id, entry.name.clone()))
+            .collect();
 
         let txt_h = 30.0;
         ui.vertical(|ui| {
@@ -65,28 +63,28 @@ impl InfoWindows {
                 })
                 .body(|body| {
                     body.rows(txt_h, self.filenames.len(), |row_index, mut row| {
+                        let (id_moc, moc_name) = self.filenames[row_index].clone();
                         row.col(|ui| {
-                            let mut is_open = self
-                                .open
-                                .contains(&self.filenames.get(row_index).unwrap().1 .0);
+                            let mut is_open = self.open.contains(&moc_name);
                             ui.horizontal(|ui| {
                                 ui.toggle_value(
                                     &mut is_open,
-                                    &self.filenames.get(row_index).unwrap().1 .0,
+                                    &moc_name,
                                 ).on_hover_text_at_pointer(
                                     "Right click to see different operations available on this MOC."
                                 )
                                 // Right click menu
                                 .context_menu(|ui| {
-                                    let id_moc = self.filenames.get(row_index).unwrap().0;
-                                    ui.menu_button("Unitary ops", |ui| lite_unit_ui(ui, id_moc));
+                                    ui.menu_button("Unitary ops", |ui| {
+                                        lite_unit_ui(ui, id_moc, registry)
+                                    });
                                     if self.is_multiple_selected() {
                                         ui.menu_button("Multiple ops", |ui| {
                                             let m = self.mutlitple();
-                                            lite_mult_ui(ui, m.0, m.1);
+                                            lite_mult_ui(ui, m.0, m.1, registry);
                                         });
                                     }
-                                    self.download(ui, id_moc, "Download");
+                                    self.download(ui, row_index, "Download", registry);
                                     if ui.button("Preview").clicked() {
                                         is_open = !is_open;
                                     }
@@ -95,8 +93,9 @@ impl InfoWindows {
                                             TextEdit::singleline(&mut self.name).hint_text("Name"),
                                         );
                                         if ui.button("Rename").clicked() {
-                                            let _ =
-                                                rename(id_moc, &self.name).map_err(|e| err(&e));
+                                            let _ = registry
+                                                .rename(id_moc, &self.name)
+                                                .map_err(|e| err(&e));
                                         }
                                     });
                                 })
@@ -104,14 +103,14 @@ impl InfoWindows {
                             self.open(row_index, is_open);
                         });
                         row.col(|ui| {
-                            self.download(ui, self.filenames.get(row_index).unwrap().0, "📥");
+                            self.download(ui, row_index, "📥", registry);
                         });
                         row.col(|ui| {
                             if ui.button("❌").clicked() {
                                 self.open(row_index, false);
                                 if let Some(id) = self.filenames.get(row_index) {
-                                    let _ = namestore::drop(id.0).map_err(|e| err(&e));
-                                    let _ = U64MocStore.drop(id.0).map_err(|e| err(&e));
+                                    // The MOC stays in the store while the deletion can be undone.
+                                    let _ = registry.remove(id.0).map_err(|e| err(&e));
                                 }
                             }
                         });
@@ -120,7 +119,7 @@ impl InfoWindows {
         });
     }
 
-    pub fn windows(&mut self, ctx: &Context) {
+    pub fn windows(&mut self, ctx: &Context, registry: &mut Registry) {
         let Self {
             infouis,
             open,
@@ -129,7 +128,7 @@ impl InfoWindows {
         } = self;
         for infoui in infouis {
             let mut is_open = open.contains(infoui.name());
-            infoui.show(ctx, &mut is_open);
+            infoui.show(ctx, &mut is_open, registry);
             set_open(open, infoui.name(), is_open);
         }
     }
@@ -181,62 +180,61 @@ impl InfoWindows {
         (l, r)
     }
 
-    fn download(&mut self, ui: &mut Ui, id: usize, title: &str) {
+    // #Definition
+    //      download shows the download menu of a MOC of the list.
+    // #Args
+    //  *   `row`: the row of the MOC in the list
+    //  *   `title`: the title of the menu
+    //  *   `registry`: the MOCs of the app, giving the provenance written in FITS
+    fn download(&mut self, ui: &mut Ui, row: usize, title: &str, registry: &Registry) {
+        let Some((id, name)) = self.filenames.get(row).cloned() else {
+            return;
+        };
         ui.menu_button(title, |ui| {
             if ui.button("FITS").clicked() {
-                let _ = to_file(
-                    &self.filenames.get(id).unwrap().1 .0,
-                    ".fits",
-                    "application/fits",
-                    U64MocStore
-                        .to_fits_buff(self.filenames.get(id).unwrap().0, None)
-                        .unwrap(),
-                )
-                .map_err(|e| err(&e));
+                let _ = provenance::to_fits_buff(id, &registry.provenance(id))
+                    .and_then(|data| to_file(&name, ".fits", "application/fits", data))
+                    .map_err(|e| err(&e));
             }
             if ui.button("ASCII").clicked() {
-                let _ = to_file(
-                    &self.filenames.get(id).unwrap().1 .0,
-                    ".txt",
-                    "text/plain",
-                    U64MocStore
-                        .to_ascii_str(self.filenames.get(id).unwrap().0, None)
-                        .unwrap()
-                        .into_bytes()
-                        .into_boxed_slice(),
-                )
-                .map_err(|e| err(&e));
+                let _ = U64MocStore
+                    .to_ascii_str(id, None)
+                    .map_err(Error::from)
+                    .and_then(|data| {
+                        to_file(
+                            &name,
+                            ".txt",
+                            "text/plain",
+                            data.into_bytes().into_boxed_slice(),
+                        )
+                    })
+                    .map_err(|e| err(&e));
             }
             if ui.button("JSON").clicked() {
-                let _ = to_file(
-                    &self.filenames.get(id).unwrap().1 .0,
-                    ".json",
-                    "application/json",
-                    U64MocStore
-                        .to_json_str(self.filenames.get(id).unwrap().0, None)
-                        .unwrap()
-                        .into_bytes()
-                        .into_boxed_slice(),
-                )
-                .map_err(|e| err(&e));
+                let _ = U64MocStore
+                    .to_json_str(id, None)
+                    .map_err(Error::from)
+                    .and_then(|data| {
+                        to_file(
+                            &name,
+                            ".json",
+                            "application/json",
+                            data.into_bytes().into_boxed_slice(),
+                        )
+                    })
+                    .map_err(|e| err(&e));
             }
         });
     }
 
-    fn open(&mut self, id: usize, is_open: bool) {
-        set_open(
-            &mut self.open,
-            Box::leak(
-                self.filenames
-                    .get(id)
-                    .unwrap()
-                    .1
-                     .0
-                    .to_string()
-                    .into_boxed_str(),
-            ),
-            is_open,
-        );
+    fn open(&mut self, row: usize, is_open: bool) {
+        if let Some((_, name)) = self.filenames.get(row) {
+            set_open(
+                &mut self.open,
+                Box::leak(name.to_string().into_boxed_str()),
+                is_open,
+            );
+        }
     }
 }
 
@@ -249,40 +247,64 @@ pub struct ListUi {
 
 impl ListUi {
     /// Show the app ui (menu bar and windows).
-    pub fn ui(&mut self, ctx: &Context) {
-        self.updater(ctx);
-        self.desktop_ui(ctx);
+    pub fn ui(&mut self, ctx: &Context, registry: &mut Registry) {
+        self.updater(ctx, registry);
+        self.desktop_ui(ctx, registry);
     }
 
-    fn desktop_ui(&mut self, ctx: &Context) {
+    fn desktop_ui(&mut self, ctx: &Context, registry: &mut Registry) {
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::trace!(ui);
-            self.infoui_list_ui(ui);
+            self.infoui_list_ui(ui, registry);
         });
 
-        self.show_windows(ctx);
+        self.show_windows(ctx, registry);
     }
 
     /// Show the open windows.
-    fn show_windows(&mut self, ctx: &Context) {
-        self.infouis.windows(ctx);
+    fn show_windows(&mut self, ctx: &Context, registry: &mut Registry) {
+        self.infouis.windows(ctx, registry);
     }
 
-    fn infoui_list_ui(&mut self, ui: &mut egui::Ui) {
+    fn infoui_list_ui(&mut self, ui: &mut egui::Ui, registry: &mut Registry) {
         ScrollArea::vertical().show(ui, |ui| {
             ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
-                self.infouis.checkboxes(ui);
+                self.infouis.checkboxes(ui, registry);
             });
         });
     }
 
-    fn updater(&mut self, ctx: &Context) {
-        if list_ids().unwrap().len() != self.infouis.filenames.len() {
+    // #Definition
+    //      updater rebuilds the info windows when MOCs have been added, renamed or removed.
+    fn updater(&mut self, ctx: &Context, registry: &Registry) {
+        let entries = registry.entries();
+        let up_to_date = entries.len() == self.infouis.infouis.len()
+            && entries
+                .iter()
+                .zip(&self.infouis.infouis)
+                .all(|((id, entry), infoui)| *id == infoui.id && entry.name == infoui.name);
+        if !up_to_date {
             let mut mocs: Vec<Box<InfoWindow>> = Vec::default();
-            for id in list_ids().unwrap() {
-                mocs.push(Box::new(InfoWindow::new(ctx, id).unwrap()));
+            for (id, _) in entries {
+                match InfoWindow::new(ctx, id, registry) {
+                    Ok(infoui) => mocs.push(Box::new(infoui)),
+                    Err(e) => log::error!("{e}"),
+                }
             }
-            self.infouis = InfoWindows::from_mocs(mocs)
+            // Keep the info windows that were open before the update.
+            let open = std::mem::take(&mut self.infouis.open);
+            self.infouis = InfoWindows::from_mocs(mocs, open)
         }
     }
+
+    // #Definition
+    //      open_mocs gives the names of the MOCs whose info window is open.
+    pub(crate) fn open_mocs(&self) -> BTreeSet<String> {
+        self.infouis.open.clone()
+    }
+    // #Definition
+    //      set_open_mocs opens the info windows of the given MOC names.
+    pub(crate) fn set_open_mocs(&mut self, open: BTreeSet<String>) {
+        self.infouis.open = open;
+    }
 }
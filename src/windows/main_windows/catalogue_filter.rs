@@ -0,0 +1,438 @@
+use std::io::{BufReader, Read};
+use std::sync::{Arc, Mutex};
+
+use crate::controllers::filter::{
+    filter_csv, first_line, guess_column, guess_delimiter, is_header, split_line, Filter,
+    FilterStats, Role, DEC, DELIMITERS, JD, RA,
+};
+use crate::controllers::query::columns;
+use crate::error::Error;
+use crate::utils::commons::{err, take_picked, PickedFile};
+use crate::utils::compression::decompress_reader;
+use crate::utils::registry::Registry;
+use crate::windows::csv_import::CSV_EXTENSIONS;
+
+use eframe::egui;
+use egui::Ui;
+use moc::storage::u64idx::common::MocQType;
+
+use crate::windows::{View, Window};
+
+// Where a catalogue is read from: its path on native, so that it is streamed from the disk,
+// and its content on web where files are given by the browser.
+#[derive(Clone)]
+enum Source {
+    #[cfg(not(target_arch = "wasm32"))]
+    Path(std::path::PathBuf),
+    Data(Arc<Vec<u8>>),
+}
+impl Source {
+    // #Definition
+    //      open gives a reader of the (decompressed) catalogue.
+    // #Errors
+    //      Error if the file can't be opened.
+    fn open(&self) -> Result<Box<dyn Read + Send + '_>, Error> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Path(path) => decompress_reader(BufReader::new(std::fs::File::open(path)?)),
+            Source::Data(data) => decompress_reader(BufReader::new(data.as_slice())),
+        }
+    }
+}
+
+// The Catalogue struct, the picked catalogue with what has been guessed from its first line.
+#[derive(Clone)]
+struct Catalogue {
+    name: String,
+    source: Source,
+    first_line: String,
+    delimiter: u8,
+    has_headers: bool,
+}
+impl Catalogue {
+    // #Definition
+    //      headers gives the header names, empty if the catalogue has no header row.
+    fn headers(&self) -> Vec<String> {
+        if self.has_headers {
+            split_line(&self.first_line, self.delimiter)
+        } else {
+            Vec::default()
+        }
+    }
+}
+
+// The Job struct, a filtering shared with the thread running it (native): the numbers of rows
+// so far, whether it has been cancelled and its result once it is over.
+#[derive(Default)]
+struct Job {
+    stats: FilterStats,
+    cancel: bool,
+    done: Option<Result<FilterStats, String>>,
+}
+
+// The CatalogueFilterUi struct, a window filtering the rows of a csv catalogue by a MOC.
+#[derive(Default, Clone)]
+pub struct CatalogueFilterUi {
+    picked_file: Option<usize>,
+    picked: PickedFile,
+    catalogue: Option<Catalogue>,
+    // The RA, Dec and JD columns of the catalogue.
+    columns: [usize; 3],
+    // Whether the rows out of the MOC are kept, instead of the rows in it.
+    keep_outside: bool,
+    job: Option<Arc<Mutex<Job>>>,
+    error: Option<String>,
+}
+
+impl Window for CatalogueFilterUi {
+    fn name(&self) -> &'static str {
+        "Catalogue filter"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for CatalogueFilterUi {
+    // #Definition
+    //      A function creating the UI of the catalogue filter: the rows of a csv catalogue in
+    //      (or out of) the picked MOC are written in a new csv file.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        self.make_cbox(ui, registry);
+        let qty = self
+            .picked_file
+            .and_then(|id| registry.get(id))
+            .map(|entry| entry.qty);
+        if let Some((name, data)) = take_picked(&self.picked) {
+            self.set_catalogue(name, Source::Data(Arc::new(data)), qty);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Open catalogue…").clicked() {
+                let _ = self.open_catalogue(qty).map_err(|e| err(&e));
+            }
+            if let Some(catalogue) = &self.catalogue {
+                ui.label(&catalogue.name);
+            }
+        });
+        if let (Some(qty), Some(catalogue)) = (qty, &mut self.catalogue) {
+            let headers = catalogue.headers();
+            let mut changed = false;
+            egui::Grid::new("catalogue_filter_grid")
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Delimiter:");
+                    egui::ComboBox::from_id_source("catalogue_filter_delimiter")
+                        .selected_text(
+                            DELIMITERS
+                                .iter()
+                                .find(|(d, _)| *d == catalogue.delimiter)
+                                .map_or("", |(_, name)| *name),
+                        )
+                        .show_ui(ui, |ui| {
+                            for (d, name) in DELIMITERS {
+                                changed |= ui
+                                    .selectable_value(&mut catalogue.delimiter, d, name)
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Header row:");
+                    changed |= ui.checkbox(&mut catalogue.has_headers, "").changed();
+                    ui.end_row();
+                    let n_columns = split_line(&catalogue.first_line, catalogue.delimiter).len();
+                    for (i, role) in roles(qty).iter().enumerate() {
+                        ui.label(format!("{}:", role.label));
+                        egui::ComboBox::from_id_source(("catalogue_filter", role.label))
+                            .selected_text(column_name(&headers, self.columns[i]))
+                            .show_ui(ui, |ui| {
+                                for c in 0..n_columns {
+                                    let name = column_name(&headers, c);
+                                    ui.selectable_value(&mut self.columns[i], c, name);
+                                }
+                            });
+                        ui.end_row();
+                    }
+                    ui.label("Keep the rows:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.keep_outside, false, "in the MOC");
+                        ui.radio_value(&mut self.keep_outside, true, "out of the MOC");
+                    });
+                    ui.end_row();
+                });
+            if changed {
+                self.guess_columns(qty);
+            }
+        }
+
+        self.job_ui(ui);
+        let running = self
+            .job
+            .as_ref()
+            .is_some_and(|job| job.lock().is_ok_and(|job| job.done.is_none()));
+        let ready = qty.is_some() && self.catalogue.is_some() && !running;
+        if ui
+            .add_enabled(ready, egui::Button::new("Filter and save…"))
+            .clicked()
+        {
+            self.error = None;
+            if let Err(e) = self.run(ui.ctx(), qty) {
+                self.error = Some(e.to_string());
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+impl CatalogueFilterUi {
+    // #Definition
+    //      A function creating the combo box picking the MOC among the S-, T- and ST-MOCs of
+    //      the registry.
+    fn make_cbox(&mut self, ui: &mut Ui, registry: &Registry) {
+        // The picked MOC may have been removed from the registry.
+        if self
+            .picked_file
+            .is_some_and(|id| registry.get(id).is_none())
+        {
+            self.picked_file = None;
+        }
+        let sel_text = self
+            .picked_file
+            .and_then(|id| registry.name(id).ok())
+            .unwrap_or("pick a MOC");
+        let previous = self.picked_file;
+        ui.horizontal(|ui| {
+            ui.label("MOC :");
+            egui::ComboBox::from_id_source("catalogue_filter_cbox")
+                .selected_text(sel_text)
+                .show_ui(ui, |ui| {
+                    for (id, entry) in registry.entries() {
+                        if !matches!(entry.qty, MocQType::Frequency) {
+                            ui.selectable_value(&mut self.picked_file, Some(id), &entry.name);
+                        }
+                    }
+                });
+        });
+        if let (true, Some(entry)) = (
+            previous != self.picked_file,
+            self.picked_file.and_then(|id| registry.get(id)),
+        ) {
+            self.guess_columns(entry.qty);
+        }
+    }
+
+    // #Definition
+    //      open_catalogue opens a file dialog picking the catalogue. On native, only the first
+    //      line of the file is read.
+    // #Args
+    //  *   `qty`: the type of the picked MOC, if any
+    // #Errors
+    //      Error if the file can't be read.
+    fn open_catalogue(&mut self, qty: Option<MocQType>) -> Result<(), Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = crate::utils::commons::pick_path("CSV", &CSV_EXTENSIONS) {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            self.set_catalogue(name, Source::Path(path), qty);
+        }
+        #[cfg(target_arch = "wasm32")]
+        crate::utils::commons::pick_file("CSV", &CSV_EXTENSIONS, &self.picked)?;
+        Ok(())
+    }
+
+    // #Definition
+    //      set_catalogue sets the catalogue to filter, guessing its delimiter, whether it has a
+    //      header row and its columns from its first line.
+    // #Args
+    //  *   `name`: the name of the catalogue file
+    //  *   `source`: where the catalogue is read from
+    //  *   `qty`: the type of the picked MOC, if any, whose columns are guessed
+    fn set_catalogue(&mut self, name: String, source: Source, qty: Option<MocQType>) {
+        self.error = None;
+        match source.open().and_then(first_line) {
+            Ok(line) => {
+                let delimiter = guess_delimiter(&line);
+                self.catalogue = Some(Catalogue {
+                    name,
+                    has_headers: is_header(&line, delimiter),
+                    first_line: line,
+                    delimiter,
+                    source,
+                });
+                self.columns = [0, 1, 2];
+                if let Some(qty) = qty {
+                    self.guess_columns(qty);
+                }
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    // #Definition
+    //      guess_columns maps each column the MOC type needs to the column whose header is one
+    //      of its usual names, or to the column at the same position.
+    fn guess_columns(&mut self, qty: MocQType) {
+        let Some(catalogue) = &self.catalogue else {
+            return;
+        };
+        let headers = catalogue.headers();
+        for (i, role) in roles(qty).iter().enumerate() {
+            self.columns[i] = guess_column(&headers, role).unwrap_or(i);
+        }
+    }
+
+    // #Definition
+    //      run asks where to write the filtered catalogue and filters it: in a thread on native,
+    //      the rows being streamed from file to file, and at once on web.
+    // #Args
+    //  *   `ctx`: the app's context, repainted while the filtering is running
+    //  *   `qty`: the type of the picked MOC
+    // #Errors
+    //      Error if the filtering can't be started (or fails on web).
+    fn run(&mut self, ctx: &egui::Context, qty: Option<MocQType>) -> Result<(), Error> {
+        let (Some(id), Some(qty), Some(catalogue)) = (self.picked_file, qty, &self.catalogue)
+        else {
+            return Ok(());
+        };
+        let filter = Filter {
+            delimiter: catalogue.delimiter,
+            has_headers: catalogue.has_headers,
+            columns: self.columns[..columns(qty)?.len()].to_vec(),
+            keep_inside: !self.keep_outside,
+        };
+        let stem = catalogue.name.split('.').next().unwrap_or("catalogue");
+        let job = Arc::new(Mutex::new(Job::default()));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(output) = crate::utils::commons::save_path(&format!("{stem}_filtered.csv"))
+            else {
+                return Ok(());
+            };
+            let (source, shared, ctx) = (catalogue.source.clone(), job.clone(), ctx.clone());
+            std::thread::spawn(move || {
+                let result =
+                    std::fs::File::create(output)
+                        .map_err(Error::from)
+                        .and_then(|output| {
+                            let progress = |stats: &FilterStats| {
+                                ctx.request_repaint();
+                                shared.lock().is_ok_and(|mut job| {
+                                    job.stats = *stats;
+                                    !job.cancel
+                                })
+                            };
+                            let output = std::io::BufWriter::new(output);
+                            filter_csv(id, source.open()?, output, &filter, progress)
+                        });
+                if let Ok(mut job) = shared.lock() {
+                    job.done = Some(result.map_err(|e| e.to_string()));
+                }
+                ctx.request_repaint();
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let mut output = Vec::default();
+            let stats = filter_csv(id, catalogue.source.open()?, &mut output, &filter, |_| true)?;
+            crate::utils::commons::to_file(
+                &format!("{stem}_filtered"),
+                ".csv",
+                "text/csv",
+                output.into_boxed_slice(),
+            )?;
+            job.lock()?.done = Some(Ok(stats));
+            ctx.request_repaint();
+        }
+        self.job = Some(job);
+        Ok(())
+    }
+
+    // #Definition
+    //      job_ui shows the numbers of rows of the running (or last) filtering, with a button
+    //      cancelling it while it is running.
+    fn job_ui(&mut self, ui: &mut Ui) {
+        let Some(Ok(mut job)) = self.job.as_ref().map(|job| job.lock()) else {
+            return;
+        };
+        let stats = match &job.done {
+            Some(Ok(stats)) => *stats,
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+                return;
+            }
+            None => job.stats,
+        };
+        ui.horizontal(|ui| {
+            if job.done.is_none() {
+                ui.spinner();
+            }
+            ui.label(format!(
+                "Rows read: {}, kept: {}, invalid: {}",
+                stats.read, stats.kept, stats.invalid
+            ));
+            if job.done.is_none() && ui.button("Cancel").clicked() {
+                job.cancel = true;
+            }
+        });
+    }
+}
+
+// #Definition
+//      roles gives the columns a MOC type needs, in the order of query::columns.
+fn roles(qty: MocQType) -> &'static [Role] {
+    match qty {
+        MocQType::Space => &[RA, DEC],
+        MocQType::Time => &[JD],
+        MocQType::TimeSpace => &[RA, DEC, JD],
+        MocQType::Frequency => &[],
+    }
+}
+
+// The name of a column shown to the user.
+fn column_name(headers: &[String], column: usize) -> String {
+    match headers.get(column) {
+        Some(header) => format!("{}: {header}", column + 1),
+        None => format!("Column {}", column + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The columns are guessed from the headers when the MOC is picked before the catalogue.
+    #[test]
+    fn columns_are_guessed_when_the_catalogue_is_picked_last() {
+        let mut filter = CatalogueFilterUi::default();
+        let data = Arc::new(b"id,dec,ra,jd\n1,2.0,3.0,2451545.0\n".to_vec());
+        filter.set_catalogue(
+            String::from("cat.csv"),
+            Source::Data(data.clone()),
+            Some(MocQType::Space),
+        );
+        assert_eq!(filter.columns[..2], [2, 1]);
+        filter.set_catalogue(
+            String::from("cat.csv"),
+            Source::Data(data),
+            Some(MocQType::TimeSpace),
+        );
+        assert_eq!(filter.columns, [2, 1, 3]);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::controllers::compare::{compare, Comparison};
+use crate::utils::registry::Registry;
+
+use eframe::egui;
+use egui::Ui;
+use moc::storage::u64idx::common::MocQType;
+
+use crate::windows::{View, Window};
+
+// The CompareUi struct, a window measuring the overlap of two S-MOCs or two T-MOCs.
+#[derive(Default, Clone)]
+pub struct CompareUi {
+    picked_file: Option<usize>,
+    picked_second_file: Option<usize>,
+    // The compared MOCs and their comparison, or why they can't be compared.
+    result: Option<((usize, usize), Result<Comparison, String>)>,
+}
+
+impl Window for CompareUi {
+    fn name(&self) -> &'static str {
+        "MOC comparison"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for CompareUi {
+    // #Definition
+    //      A function creating the UI of the comparison of 2 S-MOCs or 2 T-MOCs: the measures of
+    //      the MOCs and of their intersection and union, the fraction of each MOC covered by the
+    //      other, their Jaccard index and whether they are equal or one contains the other.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        // The picked MOCs may have been removed from the registry.
+        for picked in [&mut self.picked_file, &mut self.picked_second_file] {
+            if picked.is_some_and(|id| registry.get(id).is_none()) {
+                *picked = None;
+                self.result = None;
+            }
+        }
+        egui::Grid::new("compare_picks").show(ui, |ui| {
+            ui.label("First MOC :");
+            make_cbox(ui, "compare_cbox_1", &mut self.picked_file, registry);
+            ui.end_row();
+            ui.label("Second MOC :");
+            make_cbox(ui, "compare_cbox_2", &mut self.picked_second_file, registry);
+            ui.end_row();
+        });
+        let (Some(l), Some(r)) = (self.picked_file, self.picked_second_file) else {
+            ui.label("Pick two S-MOCs or two T-MOCs to compare them!");
+            return;
+        };
+        if self.result.as_ref().map(|(ids, _)| *ids) != Some((l, r)) {
+            self.result = Some(((l, r), compare(l, r).map_err(|e| e.to_string())));
+        }
+        ui.separator();
+        match self.result.as_ref().map(|(_, result)| result) {
+            Some(Ok(comparison)) => comparison_ui(ui, comparison),
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+            }
+            None => {}
+        }
+    }
+}
+
+// #Definition
+//      make_cbox creates a combo box picking a MOC among the S- and T-MOCs of the registry.
+// #Args
+//  *   `id_source`: the combo box gui ID
+//  *   `picked`: the id of the picked MOC
+//  *   `registry`: the MOCs that can be picked
+fn make_cbox(ui: &mut Ui, id_source: &str, picked: &mut Option<usize>, registry: &Registry) {
+    let sel_text = picked
+        .and_then(|id| registry.name(id).ok())
+        .unwrap_or("pick a MOC");
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(sel_text)
+        .show_ui(ui, |ui| {
+            for (id, entry) in registry.entries() {
+                if matches!(entry.qty, MocQType::Space | MocQType::Time) {
+                    ui.selectable_value(picked, Some(id), &entry.name);
+                }
+            }
+        });
+}
+
+// #Definition
+//      comparison_ui shows the measures and the checks of a comparison.
+fn comparison_ui(ui: &mut Ui, comparison: &Comparison) {
+    let unit = comparison.unit();
+    let percent = |fraction: Option<f64>| {
+        fraction.map_or_else(|| "-".to_string(), |f| format!("{:.4} %", f * 100.0))
+    };
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    egui::Grid::new("compare_results")
+        .striped(true)
+        .show(ui, |ui| {
+            for (label, value) in [
+                ("First MOC:", comparison.left),
+                ("Second MOC:", comparison.right),
+                ("Intersection:", comparison.intersection),
+                ("Union:", comparison.union()),
+            ] {
+                ui.label(label);
+                ui.label(format!("{value:.6} {unit}"));
+                ui.end_row();
+            }
+            ui.label("First MOC covered by the second:");
+            ui.label(percent(comparison.left_covered()));
+            ui.end_row();
+            ui.label("Second MOC covered by the first:");
+            ui.label(percent(comparison.right_covered()));
+            ui.end_row();
+            ui.label("Jaccard index:");
+            ui.label(
+                comparison
+                    .jaccard()
+                    .map_or_else(|| "-".to_string(), |j| format!("{j:.6}")),
+            );
+            ui.end_row();
+            ui.label("Equal:");
+            ui.label(yes_no(comparison.equal));
+            ui.end_row();
+            ui.label("First MOC contains the second:");
+            ui.label(yes_no(comparison.left_contains_right));
+            ui.end_row();
+            ui.label("Second MOC contains the first:");
+            ui.label(yes_no(comparison.right_contains_left));
+            ui.end_row();
+        });
+}
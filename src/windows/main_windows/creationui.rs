@@ -1,24 +1,18 @@
 use crate::controllers::creation::*;
-use crate::utils::namestore::add;
+use crate::controllers::frame::Frame;
+use crate::error::Error;
+use crate::utils::registry::Registry;
+use std::collections::BTreeMap;
 
 use super::creationui::CreationType;
+use crate::utils::commons::{pick_file, take_picked, PickedFile};
+use crate::windows::csv_import::{CsvImport, CSV_EXTENSIONS};
 use crate::windows::{View, Window};
 use eframe::egui;
 use egui::{TextEdit, Ui};
 use eq_float::F64;
 
-#[cfg(target_arch = "wasm32")]
-use crate::utils::commons::err;
-use moc::storage::u64idx::U64MocStore;
-#[cfg(target_arch = "wasm32")]
-use rfd::AsyncFileDialog;
-
-#[cfg(not(target_arch = "wasm32"))]
-use rfd::FileDialog;
-use std::fs::File;
-use std::io::Read;
-
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct CreationUis {
     name: String,
     depth: u8,
@@ -29,6 +23,8 @@ pub struct CreationUis {
     lat_deg_min_pa: F64,
     comp: bool,
     typ: CreationType,
+    // The frame of the positions, converted to ICRS before the creation.
+    frame: Frame,
     error: Option<String>,
     // Valued cells requirements
     density: bool,
@@ -38,6 +34,10 @@ pub struct CreationUis {
     revese_recursive_descent: bool,
     from_threshold: F64,
     to_threshold: F64,
+    // The csv file picked for the creation (of the given type), and its import dialog.
+    picked: PickedFile,
+    picked_typ: CreationType,
+    import: Option<CsvImport>,
 }
 impl Default for CreationUis {
     fn default() -> Self {
@@ -51,6 +51,7 @@ impl Default for CreationUis {
             lat_deg_min_pa: Default::default(),
             comp: Default::default(),
             typ: Default::default(),
+            frame: Default::default(),
             error: Default::default(),
             density: Default::default(),
             asc: Default::default(),
@@ -59,6 +60,9 @@ impl Default for CreationUis {
             revese_recursive_descent: Default::default(),
             from_threshold: Default::default(),
             to_threshold: Default::default(),
+            picked: Default::default(),
+            picked_typ: Default::default(),
+            import: Default::default(),
         }
     }
 }
@@ -67,14 +71,19 @@ impl Window for CreationUis {
         "MOC creation"
     }
 
-    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
         egui::Window::new(self.name())
             .open(open)
             .resizable(false)
             .show(ctx, |ui| {
                 use crate::windows::View as _;
-                self.ui(ui);
+                self.ui(ui, registry);
             });
+        if *open {
+            self.import_window(ctx, registry);
+        } else {
+            self.import = None;
+        }
     }
 }
 impl View for CreationUis {
@@ -85,7 +94,8 @@ impl View for CreationUis {
     //      Creation_ui, the main UI component for MOC creation
     // #Args
     //  *   `ui`: the egui UI that needs to show the given components
-    fn ui(&mut self, ui: &mut Ui) {
+    //  *   `registry`: the registry the created MOCs are added to
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
         let sel_text = format!("{}", self.typ);
 
         ui.horizontal(|ui| {
@@ -108,6 +118,12 @@ impl View for CreationUis {
                     ui.selectable_value(&mut self.typ, CreationType::LargeCone, "Cone L");
                     ui.selectable_value(&mut self.typ, CreationType::DecimalJd, "Time: dec");
                     ui.selectable_value(&mut self.typ, CreationType::DecimalJdRange, "Time: range");
+                    ui.selectable_value(&mut self.typ, CreationType::FrequencyRange, "Freq: range");
+                    ui.selectable_value(
+                        &mut self.typ,
+                        CreationType::WavelengthRange,
+                        "Wavelength: range",
+                    );
                     ui.selectable_value(&mut self.typ, CreationType::ValuedCells, "Valued cells");
                 });
         });
@@ -121,13 +137,21 @@ impl View for CreationUis {
             .striped(false)
             .show(ui, |ui| {
                 match self.typ {
-                    CreationType::Cone => self.error = self.cone_ui(ui, &self.error.clone()),
-                    CreationType::Ring => self.error = self.ring_ui(ui, &self.error.clone()),
+                    CreationType::Cone => {
+                        self.error = self.cone_ui(ui, &self.error.clone(), registry)
+                    }
+                    CreationType::Ring => {
+                        self.error = self.ring_ui(ui, &self.error.clone(), registry)
+                    }
                     CreationType::EllipticalCone => {
-                        self.error = self.eliptical_ui(ui, &self.error.clone())
+                        self.error = self.eliptical_ui(ui, &self.error.clone(), registry)
+                    }
+                    CreationType::Zone => {
+                        self.error = self.zone_ui(ui, &self.error.clone(), registry)
+                    }
+                    CreationType::Box => {
+                        self.error = self.box_ui(ui, &self.error.clone(), registry)
                     }
-                    CreationType::Zone => self.error = self.zone_ui(ui, &self.error.clone()),
-                    CreationType::Box => self.error = self.box_ui(ui, &self.error.clone()),
                     CreationType::Polygon => self.error = self.polygon_ui(ui, &self.error.clone()),
                     CreationType::Coo => self.error = self.coo_ui(ui, &self.error.clone()),
                     CreationType::SmallCone => self.error = self.smallc_ui(ui, &self.error.clone()),
@@ -136,15 +160,20 @@ impl View for CreationUis {
                     CreationType::DecimalJdRange => {
                         self.error = self.jdr_ui(ui, &self.error.clone())
                     }
+                    CreationType::FrequencyRange => {
+                        self.error = self.freqr_ui(ui, &self.error.clone())
+                    }
+                    CreationType::WavelengthRange => {
+                        self.error = self.wlr_ui(ui, &self.error.clone())
+                    }
                     CreationType::ValuedCells => {
                         self.error = self.valued_c(ui, &self.error.clone())
                     }
                 };
             });
-        // if self.error.is_some() {
-        //     return Err(self.error.clone().unwrap());
-        // }
-        // Ok(())
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
     }
 }
 impl CreationUis {
@@ -158,14 +187,21 @@ impl CreationUis {
     // #Args
     //  *   `ui`: the egui UI that needs to show the given components
     //  *   `e`: an optional String in case of past errors to keep it visible until change
+    //  *   `registry`: the registry the created MOC is added to (for the direct creations)
     // #Errors
     //      Depending on the outcome of the "from_[Type]" Moc creation operation
     //      the program may return an error
     //////////////////////////////////////////////////////////////////////////
 
-    pub(crate) fn cone_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+    pub(crate) fn cone_ui(
+        &mut self,
+        ui: &mut Ui,
+        e: &Option<String>,
+        registry: &mut Registry,
+    ) -> Option<String> {
         let mut err = e.to_owned();
         self.depth_builder(ui);
+        self.frame_builder(ui);
         self.lon_lat_deg_builder(ui);
         self.radius_builder(ui);
 
@@ -178,27 +214,32 @@ impl CreationUis {
             if self.name.is_empty() {
                 self.name = format!("Cone_of_rad_{}", self.radius_a.to_string().as_str());
             }
-            if let Ok(id) = U64MocStore
-                .from_cone(
-                    self.lon_deg_polf1.0,
-                    self.lat_deg_polf2.0,
-                    self.radius_a.0,
-                    self.depth,
-                    2,
-                )
-                .map_err(|e| err = Some(e))
+            if let Ok(id) = from_cone(
+                self.frame,
+                self.lon_deg_polf1.0,
+                self.lat_deg_polf2.0,
+                self.radius_a.0,
+                self.depth,
+            )
+            .map_err(|e| err = Some(e.to_string()))
             {
-                if let Err(e) = add(&self.name, id) {
-                    err = Some(e);
+                if let Err(e) = registry.add("Cone", &self.params(), &self.name, id) {
+                    err = Some(e.to_string());
                 }
             }
             self.name = String::default();
         }
         err
     }
-    pub(crate) fn ring_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+    pub(crate) fn ring_ui(
+        &mut self,
+        ui: &mut Ui,
+        e: &Option<String>,
+        registry: &mut Registry,
+    ) -> Option<String> {
         let mut err = e.to_owned();
         self.depth_builder(ui);
+        self.frame_builder(ui);
         self.lon_lat_deg_builder(ui);
         self.radii_builder(ui);
 
@@ -215,26 +256,30 @@ impl CreationUis {
                     self.radius_a.to_string().as_str()
                 );
             }
-            if let Ok(id) = U64MocStore
-                .from_ring(
-                    self.lon_deg_polf1.0,
-                    self.lat_deg_polf2.0,
-                    self.lon_deg_min_b_int.0,
-                    self.radius_a.0,
-                    self.depth,
-                    2,
-                )
-                .map_err(|e| err = Some(e))
+            if let Ok(id) = from_ring(
+                self.frame,
+                self.lon_deg_polf1.0,
+                self.lat_deg_polf2.0,
+                self.lon_deg_min_b_int.0,
+                self.radius_a.0,
+                self.depth,
+            )
+            .map_err(|e| err = Some(e.to_string()))
             {
-                if let Err(e) = add(&self.name, id) {
-                    err = Some(e);
+                if let Err(e) = registry.add("Ring", &self.params(), &self.name, id) {
+                    err = Some(e.to_string());
                 }
             }
             self.name = String::default();
         }
         err
     }
-    pub(crate) fn eliptical_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+    pub(crate) fn eliptical_ui(
+        &mut self,
+        ui: &mut Ui,
+        e: &Option<String>,
+        registry: &mut Registry,
+    ) -> Option<String> {
         let mut err = e.to_owned();
         self.elipbox_builder(ui);
 
@@ -246,29 +291,34 @@ impl CreationUis {
                     self.radius_a, self.lon_deg_min_b_int, self.lat_deg_min_pa,
                 )
             }
-            if let Ok(id) = U64MocStore
-                .from_elliptical_cone(
-                    self.lon_deg_polf1.0,
-                    self.lat_deg_polf2.0,
-                    self.radius_a.0,
-                    self.lon_deg_min_b_int.0,
-                    self.lat_deg_min_pa.0,
-                    self.depth,
-                    2,
-                )
-                .map_err(|e| err = Some(e))
+            if let Ok(id) = from_elliptical_cone(
+                self.frame,
+                self.lon_deg_polf1.0,
+                self.lat_deg_polf2.0,
+                self.radius_a.0,
+                self.lon_deg_min_b_int.0,
+                self.lat_deg_min_pa.0,
+                self.depth,
+            )
+            .map_err(|e| err = Some(e.to_string()))
             {
-                if let Err(e) = add(&self.name, id) {
-                    err = Some(e);
+                if let Err(e) = registry.add("Elliptical cone", &self.params(), &self.name, id) {
+                    err = Some(e.to_string());
                 }
             }
             self.name = String::default();
         }
         err
     }
-    pub(crate) fn zone_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+    pub(crate) fn zone_ui(
+        &mut self,
+        ui: &mut Ui,
+        e: &Option<String>,
+        registry: &mut Registry,
+    ) -> Option<String> {
         let mut err = e.to_owned();
         self.depth_builder(ui);
+        self.frame_builder(ui);
         self.lons_lats_builder(ui);
 
         ui.label("New MOC name :");
@@ -283,18 +333,18 @@ impl CreationUis {
                     self.lon_deg_min_b_int, self.lat_deg_min_pa
                 );
             }
-            if let Ok(id) = U64MocStore
-                .from_zone(
-                    self.lon_deg_min_b_int.0,
-                    self.lat_deg_min_pa.0,
-                    self.lon_deg_polf1.0,
-                    self.lat_deg_polf2.0,
-                    self.depth,
-                )
-                .map_err(|e| err = Some(e))
+            if let Ok(id) = from_zone(
+                self.frame,
+                self.lon_deg_min_b_int.0,
+                self.lat_deg_min_pa.0,
+                self.lon_deg_polf1.0,
+                self.lat_deg_polf2.0,
+                self.depth,
+            )
+            .map_err(|e| err = Some(e.to_string()))
             {
-                if let Err(e) = add(&self.name, id) {
-                    err = Some(e);
+                if let Err(e) = registry.add("Zone", &self.params(), &self.name, id) {
+                    err = Some(e.to_string());
                 }
             }
             self.name = String::default();
@@ -302,7 +352,12 @@ impl CreationUis {
         err
     }
 
-    pub(crate) fn box_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+    pub(crate) fn box_ui(
+        &mut self,
+        ui: &mut Ui,
+        e: &Option<String>,
+        registry: &mut Registry,
+    ) -> Option<String> {
         let mut err = e.to_owned();
         self.elipbox_builder(ui);
 
@@ -314,19 +369,19 @@ impl CreationUis {
                     self.radius_a, self.lon_deg_min_b_int, self.lat_deg_min_pa
                 );
             }
-            if let Ok(id) = U64MocStore
-                .from_box(
-                    self.lon_deg_polf1.0,
-                    self.lat_deg_polf2.0,
-                    self.radius_a.0,
-                    self.lon_deg_min_b_int.0,
-                    self.lat_deg_min_pa.0,
-                    self.depth,
-                )
-                .map_err(|e| err = Some(e))
+            if let Ok(id) = from_box(
+                self.frame,
+                self.lon_deg_polf1.0,
+                self.lat_deg_polf2.0,
+                self.radius_a.0,
+                self.lon_deg_min_b_int.0,
+                self.lat_deg_min_pa.0,
+                self.depth,
+            )
+            .map_err(|e| err = Some(e.to_string()))
             {
-                if let Err(e) = add(&self.name, id) {
-                    err = Some(e)
+                if let Err(e) = registry.add("Box", &self.params(), &self.name, id) {
+                    err = Some(e.to_string())
                 }
             }
             self.name = String::default();
@@ -338,6 +393,7 @@ impl CreationUis {
         let mut err = e.to_owned();
 
         self.depth_builder(ui);
+        self.frame_builder(ui);
         ui.checkbox(&mut self.comp, "Complement");
         ui.end_row();
 
@@ -348,11 +404,12 @@ impl CreationUis {
         ui.label("Creating a MOC like this will ask you for a .csv file.");
         ui.end_row();
 
-        if ui.button("Open file & create").clicked() {
+        if ui.button("Open file").clicked() {
             err = None;
 
-            let _ = self.load_csv(CreationType::Coo).map_err(|e| err = Some(e));
-            self.name = String::default();
+            let _ = self
+                .load_csv(CreationType::Polygon)
+                .map_err(|e| err = Some(e.to_string()));
         }
         err
     }
@@ -375,82 +432,14 @@ impl CreationUis {
         self.coo_cones_jd_builder(ui, CreationType::DecimalJdRange, e)
     }
 
-    #[cfg(target_arch = "wasm32")]
-    fn valued_c(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
-        let mut err = e.clone();
-
-        self.depth_builder(ui);
-        self.threshold_builder(ui);
-
-        ui.label("Density: ");
-        ui.checkbox(&mut self.density, "");
-        ui.end_row();
-        ui.label("Asc: ");
-        ui.checkbox(&mut self.asc, "");
-        ui.end_row();
-        ui.label("Strict: ");
-        ui.checkbox(&mut self.not_strict, "");
-        ui.end_row();
-        ui.label("Split: ");
-        ui.checkbox(&mut self.split, "");
-        ui.end_row();
-        ui.label("Revese recursive descent: ");
-        ui.checkbox(&mut self.revese_recursive_descent, "");
-        ui.end_row();
-        ui.label("New MOC name :");
-        ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
-        ui.end_row();
-
-        if ui
-            .button("Open coo file")
-            .on_hover_text_at_pointer(
-                "CSV file containing one coordinate per row:RA,DEC in decimal degrees",
-            )
-            .clicked()
-        {
-            err = None;
-
-            let task = AsyncFileDialog::new()
-                .add_filter("MOCs", &["csv"])
-                .pick_file();
-
-            let depth = self.depth;
-            let mut name = format!("ValuedC_{}", self.depth);
-            if !self.name.is_empty() {
-                name = self.name.clone();
-            }
-            let density = self.density;
-            let asc = self.asc;
-            let not_strict = self.not_strict;
-            let split = self.split;
-            let revese_recursive_descent = self.revese_recursive_descent;
-            let from_threshold = self.from_threshold;
-            let to_threshold = self.to_threshold;
-
-            execute(async move {
-                let handle = task.await;
-                if let Some(file) = handle {
-                    let file_content = unsafe { String::from_utf8_unchecked(file.read().await) };
-                    let id = from_valued_cells(
-                        depth,
-                        density,
-                        from_threshold.0,
-                        to_threshold.0,
-                        asc,
-                        not_strict,
-                        split,
-                        revese_recursive_descent,
-                        file_content,
-                    )
-                    .unwrap();
-                    add(&name, id);
-                }
-            });
-        }
-        err
+    // Freqr_ui and Wlr_ui allow Frequency MOCs creation, from ranges in Hz or in meters.
+    pub(crate) fn freqr_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+        self.coo_cones_jd_builder(ui, CreationType::FrequencyRange, e)
+    }
+    pub(crate) fn wlr_ui(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
+        self.coo_cones_jd_builder(ui, CreationType::WavelengthRange, e)
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
     fn valued_c(&mut self, ui: &mut Ui, e: &Option<String>) -> Option<String> {
         let mut err = e.clone();
 
@@ -477,56 +466,16 @@ impl CreationUis {
         ui.end_row();
 
         if ui
-            .button("Open coo file")
+            .button("Open csv file")
             .on_hover_text_at_pointer(
-                "CSV file containing one coordinate per row:RA,DEC in decimal degrees",
+                "CSV file containing UNIQ HEALPix cells and their values, the columns are picked in the import dialog",
             )
             .clicked()
         {
             err = None;
-
-            if let Some(path) = FileDialog::new().add_filter("MOCs", &["csv"]).pick_file() {
-                let depth = self.depth;
-                let mut name = format!("ValuedC_{}", self.depth);
-                if !self.name.is_empty() {
-                    name = self.name.clone();
-                }
-
-                let mut file = File::open(path)
-                    .map_err(|_| err = Some("Error while opening file".to_string()))
-                    .unwrap();
-                let mut file_content = Vec::default();
-                file.read_to_end(&mut file_content)
-                    .map_err(|e| err = Some(e.to_string()))
-                    .unwrap();
-                let file_content = unsafe { String::from_utf8_unchecked(file_content) };
-
-                let density = self.density;
-                let asc = self.asc;
-                let not_strict = self.not_strict;
-                let split = self.split;
-                let revese_recursive_descent = self.revese_recursive_descent;
-                let from_threshold = self.from_threshold;
-                let to_threshold = self.to_threshold;
-
-                if let Ok(id) = from_valued_cells(
-                    depth,
-                    density,
-                    from_threshold.0,
-                    to_threshold.0,
-                    asc,
-                    not_strict,
-                    split,
-                    revese_recursive_descent,
-                    file_content,
-                )
-                .map_err(|e| err = Some(e))
-                {
-                    if let Err(e) = add(&name, id) {
-                        err = Some(e)
-                    }
-                }
-            }
+            let _ = self
+                .load_csv(CreationType::ValuedCells)
+                .map_err(|e| err = Some(e.to_string()));
         }
         err
     }
@@ -541,6 +490,7 @@ impl CreationUis {
     //  *   `ui`: the egui UI that needs to show the given components
     fn elipbox_builder(&mut self, ui: &mut Ui) {
         self.depth_builder(ui);
+        self.frame_builder(ui);
         self.lon_lat_deg_builder(ui);
         self.degs_builder(ui);
 
@@ -568,6 +518,12 @@ impl CreationUis {
         let mut err = e.to_owned();
 
         self.depth_builder(ui);
+        if matches!(
+            typ,
+            CreationType::Coo | CreationType::SmallCone | CreationType::LargeCone
+        ) {
+            self.frame_builder(ui);
+        }
 
         ui.label("New MOC name :");
         ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
@@ -575,14 +531,11 @@ impl CreationUis {
 
         if ui
             .button("Open coo file")
-            .on_hover_text_at_pointer(
-                "CSV file containing one coordinate per row:RA,DEC in decimal degrees",
-            )
+            .on_hover_text_at_pointer("CSV file, its columns are picked in the import dialog")
             .clicked()
         {
             err = None;
-            let _ = self.load_csv(typ).map_err(|e| err = Some(e));
-            self.name = String::default();
+            let _ = self.load_csv(typ).map_err(|e| err = Some(e.to_string()));
         }
         err
     }
@@ -596,6 +549,28 @@ impl CreationUis {
         ui.end_row();
     }
 
+    fn frame_builder(&mut self, ui: &mut Ui) {
+        ui.label("Frame:");
+        egui::ComboBox::from_id_source("Frame_cbox")
+            .selected_text(self.frame.to_string())
+            .show_ui(ui, |ui| {
+                for frame in Frame::ALL {
+                    ui.selectable_value(&mut self.frame, frame, frame.to_string());
+                }
+            });
+        ui.end_row();
+    }
+
+    // #Definition
+    //      params gives the parameters of a creation recorded in the provenance of the MOC:
+    //      its depth, and its frame if not ICRS.
+    fn params(&self) -> String {
+        match self.frame {
+            Frame::Icrs => format!("depth {}", self.depth),
+            frame => format!("depth {}, {frame}", self.depth),
+        }
+    }
+
     fn lon_lat_deg_builder(&mut self, ui: &mut Ui) {
         ui.label("Longitude degrees:");
         ui.add(
@@ -715,73 +690,93 @@ impl CreationUis {
     //////////////////////////
     // Useful csv functions //
 
-    #[cfg(target_arch = "wasm32")]
-    fn load_csv(&mut self, typ: CreationType) -> Result<(), String> {
-        let task = AsyncFileDialog::new()
-            .add_filter("MOCs", &["csv"])
-            .pick_file();
-
-        let depth = self.depth;
-        let mut name = format!("{}_{}", typ, self.depth);
-        if !self.name.is_empty() {
-            name = self.name.clone();
-        }
-        let complement = self.comp;
-
-        execute(async move {
-            let handle = task.await;
-            if let Some(file) = handle {
-                let file_content = unsafe { String::from_utf8_unchecked(file.read().await) };
-
-                if let Ok(id) = match typ {
-                    CreationType::Coo => from_coo(depth, file_content),
-                    CreationType::DecimalJd => from_decimal_jd(depth, file_content),
-                    CreationType::DecimalJdRange => from_decimal_jd_range(depth, file_content),
-                    CreationType::LargeCone => from_large_cones(depth, file_content),
-                    CreationType::Polygon => from_polygon(depth, file_content, complement),
-                    CreationType::SmallCone => from_small_cones(depth, file_content),
-                    _ => unreachable!(),
-                } {
-                    let _ = add(&name, id).map_err(|e| err(&e));
-                }
-            }
-        });
-        Ok(())
+    // #Definition
+    //      load_csv opens a file dialog, the picked csv file is then shown in the import dialog.
+    fn load_csv(&mut self, typ: CreationType) -> Result<(), Error> {
+        self.picked_typ = typ;
+        pick_file("CSV", &CSV_EXTENSIONS, &self.picked)
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load_csv(&mut self, typ: CreationType) -> Result<(), String> {
-        if let Some(path) = FileDialog::new().add_filter("MOCs", &["csv"]).pick_file() {
-            let depth = self.depth;
-            let mut name = format!("{}_{}", typ, self.depth);
-            if !self.name.is_empty() {
-                name = self.name.clone();
+    // #Definition
+    //      import_window shows the import dialog of the picked csv file and creates the MOC
+    //      when asked to, with the current depth, name and options of the creation window.
+    // #Args
+    //  *   `ctx`: the app's context
+    //  *   `registry`: the registry the created MOC is added to
+    fn import_window(&mut self, ctx: &egui::Context, registry: &mut Registry) {
+        if let Some((file_name, data)) = take_picked(&self.picked) {
+            match CsvImport::new(self.picked_typ, file_name, &data) {
+                Ok(import) => self.import = Some(import),
+                Err(e) => self.error = Some(e.to_string()),
             }
-            let complement = self.comp;
-
-            let mut file = File::open(path).map_err(|_| "Error while opening file".to_string())?;
-            let mut file_content = Vec::default();
-            file.read_to_end(&mut file_content)
-                .map_err(|e| format!("Error while reading file: {e}"))?;
-            let file_content = unsafe { String::from_utf8_unchecked(file_content) };
-
-            if let Ok(id) = match typ {
-                CreationType::Coo => from_coo(depth, file_content),
-                CreationType::DecimalJd => from_decimal_jd(depth, file_content),
-                CreationType::DecimalJdRange => from_decimal_jd_range(depth, file_content),
-                CreationType::LargeCone => from_large_cones(depth, file_content),
-                CreationType::Polygon => from_polygon(depth, file_content, complement),
-                CreationType::SmallCone => from_small_cones(depth, file_content),
-                _ => unreachable!(),
-            } {
-                add(&name, id)?
+        }
+        let Some(mut import) = self.import.take() else {
+            return;
+        };
+        let mut open = true;
+        if import.show(ctx, &mut open) {
+            match self.create_from_csv(&import, registry) {
+                Ok(()) => return,
+                Err(e) => import.set_error(Some(e)),
             }
         }
-        Ok(())
+        if open {
+            self.import = Some(import);
+        }
     }
-}
 
-#[cfg(target_arch = "wasm32")]
-fn execute<F: std::future::Future<Output = ()> + 'static>(f: F) {
-    wasm_bindgen_futures::spawn_local(f);
+    // #Definition
+    //      create_from_csv creates a MOC from the mapped columns of a csv file and adds it to
+    //      the store and the registry.
+    // #Errors
+    //      Error if a value can't be parsed or if the MOC creation fails.
+    fn create_from_csv(
+        &mut self,
+        import: &CsvImport,
+        registry: &mut Registry,
+    ) -> Result<(), Error> {
+        let typ = import.typ;
+        let depth = self.depth;
+        let id = match typ {
+            CreationType::Coo => from_coo(depth, import.values()?, self.frame),
+            CreationType::DecimalJd => from_decimal_jd(depth, import.values()?),
+            CreationType::DecimalJdRange => from_decimal_jd_range(depth, import.values()?),
+            CreationType::FrequencyRange => from_frequency_range(depth, import.values()?),
+            CreationType::WavelengthRange => from_wavelength_range(depth, import.values()?),
+            CreationType::LargeCone => from_large_cones(depth, import.values()?, self.frame),
+            CreationType::Polygon => from_polygon(depth, import.values()?, self.comp, self.frame),
+            CreationType::SmallCone => from_small_cones(depth, import.values()?, self.frame),
+            CreationType::ValuedCells => from_valued_cells(
+                depth,
+                self.density,
+                self.from_threshold.0,
+                self.to_threshold.0,
+                self.asc,
+                self.not_strict,
+                self.split,
+                self.revese_recursive_descent,
+                import.uniq_values()?,
+            ),
+            _ => Err(Error::Parse(format!(
+                "{typ} MOCs are not created from a csv file"
+            ))),
+        }?;
+        let name = if self.name.is_empty() {
+            match typ {
+                CreationType::ValuedCells => format!("ValuedC_{depth}"),
+                _ => format!("{typ}_{depth}"),
+            }
+        } else {
+            std::mem::take(&mut self.name)
+        };
+        registry.insert(&name, id, Some(import.file_name.clone()), BTreeMap::new())?;
+        let params = match typ {
+            CreationType::Coo
+            | CreationType::LargeCone
+            | CreationType::Polygon
+            | CreationType::SmallCone => self.params(),
+            _ => format!("depth {depth}"),
+        };
+        registry.added(&typ.to_string(), &[], &params, &[id])
+    }
 }
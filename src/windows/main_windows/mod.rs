@@ -1,15 +1,34 @@
+pub(crate) mod catalogue_filter;
+pub(crate) mod compare;
 pub(crate) mod creationui;
 pub(crate) mod multiple;
+pub(crate) mod nary;
+pub(crate) mod query;
+pub(crate) mod sky_viewer;
+pub(crate) mod stmoc_explorer;
+pub(crate) mod timeline_viewer;
 pub(crate) mod unitary;
+pub(crate) mod votable_import;
 
 use std::collections::BTreeSet;
 
 use egui::{Context, Ui};
 
-use crate::{windows::Window, utils::commons::set_open};
+use crate::{
+    utils::{commons::set_open, registry::Registry},
+    windows::Window,
+};
+use catalogue_filter::CatalogueFilterUi;
+use compare::CompareUi;
 use creationui::CreationUis;
 use multiple::MultipleUi;
+use nary::NaryUi;
+use query::QueryUi;
+use sky_viewer::SkyViewerUi;
+use stmoc_explorer::StMocExplorerUi;
+use timeline_viewer::TimelineViewerUi;
 use unitary::UnitaryUi;
+use votable_import::VoTableImportUi;
 
 pub struct MainWindows {
     mainuis: Vec<Box<dyn Window>>,
@@ -19,8 +38,16 @@ impl Default for MainWindows {
     fn default() -> Self {
         MainWindows::from_main_uis(vec![
             Box::<CreationUis>::default(),
+            Box::<VoTableImportUi>::default(),
             Box::<UnitaryUi>::default(),
             Box::<MultipleUi>::default(),
+            Box::<NaryUi>::default(),
+            Box::<SkyViewerUi>::default(),
+            Box::<TimelineViewerUi>::default(),
+            Box::<StMocExplorerUi>::default(),
+            Box::<QueryUi>::default(),
+            Box::<CatalogueFilterUi>::default(),
+            Box::<CompareUi>::default(),
         ])
     }
 }
@@ -39,12 +66,19 @@ impl MainWindows {
         }
     }
 
-    pub fn windows(&mut self, ctx: &Context) {
+    pub(crate) fn open_windows(&self) -> BTreeSet<String> {
+        self.open.clone()
+    }
+    pub(crate) fn set_open_windows(&mut self, open: BTreeSet<String>) {
+        self.open = open;
+    }
+
+    pub fn windows(&mut self, ctx: &Context, registry: &mut Registry) {
         let Self { mainuis, open } = self;
         for mainui in mainuis {
             let mut is_open = open.contains(mainui.name());
-            mainui.show(ctx, &mut is_open);
+            mainui.show(ctx, &mut is_open, registry);
             set_open(open, mainui.name(), is_open);
         }
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,7 @@
 use crate::controllers::op2::*;
-use crate::utils::commons::{err, fmt_qty};
-use crate::utils::namestore::{get_last, get_name, get_store, list_names, get_before_last};
+use crate::error::Error;
+use crate::utils::commons::err;
+use crate::utils::registry::Registry;
 
 use eframe::egui;
 use egui::{TextEdit, Ui};
@@ -23,13 +24,13 @@ impl Window for MultipleUi {
         "MOC multiple logical operations"
     }
 
-    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
         egui::Window::new(self.name())
             .open(open)
             .resizable(false)
             .show(ctx, |ui| {
                 use crate::windows::View as _;
-                self.ui(ui);
+                self.ui(ui, registry);
             });
     }
 }
@@ -41,10 +42,7 @@ impl MultipleUi {
     //  *   `ui`: The ui from the app.
     fn op_two_ui(&mut self, ui: &mut Ui) {
         // An operation combo box including Intersection and Union
-        if self.picked_file.is_some() && self.picked_second_file.is_some() {
-            let l = self.picked_file.unwrap();
-            let r = self.picked_second_file.unwrap();
-
+        if let (Some(l), Some(r)) = (self.picked_file, self.picked_second_file) {
             if files_have_same_type(l, r) {
                 if self.operation.eq(&Op2::SFold) || self.operation.eq(&Op2::TFold) {
                     self.operation = Op2::Intersection;
@@ -95,19 +93,23 @@ impl MultipleUi {
     //  *   sel_text: &str, the text to show in the combo box.
     //  *   id: &str, the combobox gui ID.
     //  *   op: Option<u8>, to know if there needs to be multiple selected mocs.
-    fn make_cbox(&mut self, ui: &mut Ui, sel_text: &str, id: &str, op: Option<u8>) {
+    //  *   registry: &Registry, the MOCs that can be picked.
+    fn make_cbox(
+        &mut self,
+        ui: &mut Ui,
+        sel_text: &str,
+        id: &str,
+        op: Option<u8>,
+        registry: &Registry,
+    ) {
         egui::ComboBox::from_id_source(id)
             .selected_text(sel_text)
             .show_ui(ui, |ui| {
-                for file in get_store().read().unwrap().iter() {
+                for (id, entry) in registry.entries() {
                     if op.is_none() {
-                        ui.selectable_value(&mut self.picked_file, Some(*file.0), &file.1 .0);
+                        ui.selectable_value(&mut self.picked_file, Some(id), &entry.name);
                     } else {
-                        ui.selectable_value(
-                            &mut self.picked_second_file,
-                            Some(*file.0),
-                            &file.1 .0,
-                        );
+                        ui.selectable_value(&mut self.picked_second_file, Some(id), &entry.name);
                     }
                 }
             });
@@ -119,47 +121,29 @@ impl View for MultipleUi {
     //      A function creating the UI for operations on 2 stored MOCs.
     // #Args
     //  *   `ui`: The ui from the app.
-    //  *   `e`: an optional String in case of past errors to keep it visible until change
-    fn ui(&mut self, ui: &mut Ui) {
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
         let op: Op2 = self.operation;
 
         // If no file has been imported yet.
-        if list_names().unwrap().len() < 2 {
+        if registry.len() < 2 {
             ui.label("Pick at least 2 files!");
         // If files have been imported and can be chosen from.
         } else {
-            let sel_text: String;
-            let sel_text_2: String;
-            if self.picked_file.is_some() {
-                if let Ok(txt) = get_name(self.picked_file.unwrap()) {
-                    sel_text = txt
-                } else {
-                    self.picked_file = Some(get_last().unwrap().0);
-                    sel_text = get_name(self.picked_file.unwrap())
-                        .map_err(|e| err(&e))
-                        .unwrap();
-                }
-            } else {
-                self.picked_file = Some(get_last().unwrap().0);
-                sel_text = get_name(self.picked_file.unwrap())
-                    .map_err(|e| err(&e))
-                    .unwrap();
-            }
-            if self.picked_second_file.is_some() {
-                if let Ok(txt) = get_name(self.picked_second_file.unwrap()) {
-                    sel_text_2 = txt
-                } else {
-                    self.picked_second_file = Some(get_before_last().unwrap().0);
-                    sel_text_2 = get_name(self.picked_second_file.unwrap())
-                        .map_err(|e| err(&e))
-                        .unwrap();
+            // The last two MOCs are picked by default.
+            let ids = registry.ids();
+            let (last, before_last) = (ids.last().copied(), ids.iter().rev().nth(1).copied());
+            let names = picked_name(&mut self.picked_file, last, registry).and_then(|sel_text| {
+                picked_name(&mut self.picked_second_file, before_last, registry)
+                    .map(|sel_text_2| (sel_text, sel_text_2))
+            });
+            let (sel_text, sel_text_2) = match names {
+                Ok(names) => names,
+                Err(e) => {
+                    ui.label(e.to_string());
+                    return;
                 }
-            } else {
-                self.picked_second_file = Some(get_before_last().unwrap().0);
-                sel_text_2 = get_name(self.picked_second_file.unwrap())
-                    .map_err(|e| err(&e))
-                    .unwrap();
-            }
+            };
 
             // The small paragraph before the match sets a grid layout to have every element aligned.
             egui::Grid::new("my_grid")
@@ -169,18 +153,16 @@ impl View for MultipleUi {
                 .show(ui, |ui| {
                     //Combo boxes containing the different files that can be picked from
                     ui.label("First MOC :");
-                    self.make_cbox(ui, &sel_text, "file_cbox", None);
+                    self.make_cbox(ui, &sel_text, "file_cbox", None, registry);
                     ui.end_row();
                     ui.label("Second MOC :");
-                    self.make_cbox(ui, &sel_text_2, "file_cbox_2", Some(1));
+                    self.make_cbox(ui, &sel_text_2, "file_cbox_2", Some(1), registry);
                     ui.end_row();
 
                     self.op_two_ui(ui);
                     ui.end_row();
 
-                    if self.picked_file.is_some() && self.picked_second_file.is_some() {
-                        let l = self.picked_file.unwrap();
-                        let r = self.picked_second_file.unwrap();
+                    if let (Some(l), Some(r)) = (self.picked_file, self.picked_second_file) {
                         if files_have_same_type(l, r) || files_have_stmoc(l, r) {
                             ui.label("New MOC name :");
                             ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
@@ -188,18 +170,14 @@ impl View for MultipleUi {
 
                             //Button launching the operation
                             if ui.button("Launch").clicked() {
-                                let mut l = self.picked_file.as_ref().unwrap();
-                                let mut r = self.picked_second_file.as_ref().unwrap();
-                                if matches!(
-                                    U64MocStore.get_qty_type(self.picked_file.unwrap()),
-                                    Ok(MocQType::TimeSpace)
-                                ) {
+                                let (mut l, mut r) = (l, r);
+                                if matches!(U64MocStore.get_qty_type(l), Ok(MocQType::TimeSpace)) {
                                     std::mem::swap(&mut r, &mut l);
                                 }
                                 if self.name.is_empty() {
                                     self.name = format!("{op}_{l}_{r}");
                                 }
-                                let _ = op2(*l, *r, op, &self.name).map_err(|e| err(&e));
+                                let _ = op2(registry, l, r, op, &self.name).map_err(|e| err(&e));
                                 self.name = String::default();
                             };
                         }
@@ -215,27 +193,28 @@ impl View for MultipleUi {
 //  *   `l`: the index of the first MOC
 //  *   `r`: the index of the second MOC
 //  *   `ui`: the ui used by the app
-pub(crate) fn lite_mult_ui(ui: &mut Ui, l: usize, r: usize) {
+//  *   `registry`: the registry the result is added to
+pub(crate) fn lite_mult_ui(ui: &mut Ui, l: usize, r: usize, registry: &mut Registry) {
     if files_have_same_type(l, r) {
         if ui.button("Intersection").clicked() {
-            lite_op(l, r, Op2::Intersection);
+            lite_op(l, r, Op2::Intersection, registry);
         };
         if ui.button("Minus").clicked() {
-            lite_op(l, r, Op2::Minus);
+            lite_op(l, r, Op2::Minus, registry);
         };
         if ui.button("Union").clicked() {
-            lite_op(l, r, Op2::Union);
+            lite_op(l, r, Op2::Union, registry);
         };
         if ui.button("Difference").clicked() {
-            lite_op(l, r, Op2::Difference);
+            lite_op(l, r, Op2::Difference, registry);
         };
     } else if files_have_stmoc(l, r) {
         if have_space(l, r) {
             if ui.button("SFold").clicked() {
-                lite_op(l, r, Op2::SFold);
+                lite_op(l, r, Op2::SFold, registry);
             };
         } else if have_time(l, r) && ui.button("TFold").clicked() {
-            lite_op(l, r, Op2::TFold);
+            lite_op(l, r, Op2::TFold, registry);
         };
     } else {
         ui.label("Mocs need to be of same type or with 1 STMOC");
@@ -248,20 +227,22 @@ pub(crate) fn lite_mult_ui(ui: &mut Ui, l: usize, r: usize) {
 //  *   `l`: the index of the first MOC
 //  *   `r`: the index of the second MOC
 //  *   `operation`: the operation that needs to be applied on the MOCs
+//  *   `registry`: the registry the result is added to
 // #Errors
 //      may show an error message coming from the op2 function
-fn lite_op(mut l: usize, mut r: usize, operation: Op2) {
+fn lite_op(mut l: usize, mut r: usize, operation: Op2, registry: &mut Registry) {
     //Button launching the operation
     if matches!(U64MocStore.get_qty_type(l), Ok(MocQType::TimeSpace)) {
         std::mem::swap(&mut r, &mut l);
     }
-    let name = format!(
-        "{}_{}_{}",
-        operation,
-        get_name(l).unwrap(),
-        get_name(r).unwrap()
-    );
-    let _ = op2(l, r, operation, &name).map_err(|e| err(&e));
+    let _ = registry
+        .name(l)
+        .and_then(|l_name| {
+            let r_name = registry.name(r)?;
+            Ok(format!("{operation}_{l_name}_{r_name}"))
+        })
+        .and_then(|name| op2(registry, l, r, operation, &name))
+        .map_err(|e| err(&e));
 }
 
 // #Definitions
@@ -274,9 +255,13 @@ fn files_have_stmoc(l: usize, r: usize) -> bool {
         || matches!(U64MocStore.get_qty_type(r), Ok(MocQType::TimeSpace))
 }
 fn files_have_same_type(l: usize, r: usize) -> bool {
-    let a = fmt_qty(U64MocStore.get_qty_type(l).unwrap());
-    let b = fmt_qty(U64MocStore.get_qty_type(r).unwrap());
-    a == b
+    matches!(
+        (U64MocStore.get_qty_type(l), U64MocStore.get_qty_type(r)),
+        (Ok(MocQType::Space), Ok(MocQType::Space))
+            | (Ok(MocQType::Time), Ok(MocQType::Time))
+            | (Ok(MocQType::TimeSpace), Ok(MocQType::TimeSpace))
+            | (Ok(MocQType::Frequency), Ok(MocQType::Frequency))
+    )
 }
 fn have_space(l: usize, r: usize) -> bool {
     matches!(U64MocStore.get_qty_type(l), Ok(MocQType::Space))
@@ -286,3 +271,24 @@ fn have_time(l: usize, r: usize) -> bool {
     matches!(U64MocStore.get_qty_type(l), Ok(MocQType::Time))
         || matches!(U64MocStore.get_qty_type(r), Ok(MocQType::Time))
 }
+
+// #Definition
+//      picked_name gives the name of the picked MOC, the default one is picked if there is none.
+// #Args
+//  *   `picked`: the id of the picked MOC
+//  *   `default`: the MOC picked by default
+//  *   `registry`: the MOCs of the app
+// #Errors
+//      Error if there is no MOC to pick by default.
+fn picked_name(
+    picked: &mut Option<usize>,
+    default: Option<usize>,
+    registry: &Registry,
+) -> Result<String, Error> {
+    if let Some(Ok(name)) = picked.map(|id| registry.name(id)) {
+        return Ok(name.to_owned());
+    }
+    let id = default.ok_or_else(|| Error::NotFound(String::from("MOC to pick")))?;
+    *picked = Some(id);
+    Ok(registry.name(id)?.to_owned())
+}
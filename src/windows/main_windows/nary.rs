@@ -0,0 +1,108 @@
+use crate::controllers::opn::*;
+use crate::utils::commons::err;
+use crate::utils::registry::Registry;
+
+use eframe::egui;
+use egui::{ScrollArea, TextEdit, Ui};
+
+use crate::windows::{View, Window};
+
+#[derive(Default, Clone, Eq, PartialEq)]
+pub struct NaryUi {
+    name: String,
+    operation: OpN,
+    // The picked MOCs, in picking order (the order matters for minus).
+    picked_files: Vec<usize>,
+}
+
+impl Window for NaryUi {
+    fn name(&self) -> &'static str {
+        "MOC N-ary logical operations"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for NaryUi {
+    // #Definition
+    //      A function creating the UI for operations on any number of stored MOCs.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        // Forget the MOCs that have been removed from the registry.
+        self.picked_files.retain(|id| registry.get(*id).is_some());
+
+        if registry.len() < 2 {
+            ui.label("Pick at least 2 files!");
+            return;
+        }
+
+        ui.label("MOCs :");
+        ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            for (id, entry) in registry.entries() {
+                let mut is_picked = self.picked_files.contains(&id);
+                if ui.checkbox(&mut is_picked, &entry.name).changed() {
+                    if is_picked {
+                        self.picked_files.push(id);
+                    } else {
+                        self.picked_files.retain(|p| *p != id);
+                    }
+                }
+            }
+        });
+
+        egui::Grid::new("nary_grid")
+            .num_columns(2)
+            .spacing([5.0, 4.0])
+            .striped(false)
+            .show(ui, |ui| {
+                ui.label("Operation :");
+                egui::ComboBox::from_id_source("nary_operation_cbox")
+                    .selected_text(format!("{}", self.operation))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.operation, OpN::Union, "Union");
+                        ui.selectable_value(&mut self.operation, OpN::Intersection, "Intersection");
+                        ui.selectable_value(&mut self.operation, OpN::Minus, "Minus");
+                    });
+                ui.end_row();
+
+                if self.operation == OpN::Minus && !self.picked_files.is_empty() {
+                    // Minus is not commutative, show the order in which MOCs are subtracted.
+                    let order: Vec<&str> = self
+                        .picked_files
+                        .iter()
+                        .filter_map(|id| registry.name(*id).ok())
+                        .collect();
+                    ui.label("Order :");
+                    ui.label(order.join(" - "));
+                    ui.end_row();
+                }
+
+                ui.label("New MOC name :");
+                ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
+                ui.end_row();
+
+                if ui
+                    .add_enabled(self.picked_files.len() >= 2, egui::Button::new("Launch"))
+                    .clicked()
+                {
+                    if self.name.is_empty() {
+                        self.name =
+                            format!("{}_of_{}_mocs", self.operation, self.picked_files.len());
+                    }
+                    let _ = opn(registry, &self.picked_files, self.operation, &self.name)
+                        .map_err(|e| err(&e));
+                    self.name = String::default();
+                }
+            });
+    }
+}
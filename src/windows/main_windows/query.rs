@@ -0,0 +1,285 @@
+use crate::controllers::query::{columns, contains, parse_list, query_list, to_csv, Point, Row};
+use crate::utils::commons::{err, pick_file, take_picked, to_file, PickedFile};
+use crate::utils::compression::decompress;
+use crate::utils::registry::Registry;
+use crate::windows::csv_import::CSV_EXTENSIONS;
+
+use eframe::egui;
+use egui::Ui;
+use egui_extras::{Column, TableBuilder};
+use moc::storage::u64idx::common::MocQType;
+
+use crate::windows::{View, Window};
+
+// The QueryUi struct, a window testing whether points (positions and/or times) are in a MOC.
+#[derive(Default, Clone)]
+pub struct QueryUi {
+    picked_file: Option<usize>,
+    // The single point: its RA and Dec (°), its JD and whether it is in the MOC.
+    ra: f64,
+    dec: f64,
+    jd: f64,
+    single: Option<bool>,
+    // The list of points, as typed or loaded, and its results.
+    list: String,
+    picked: PickedFile,
+    results: Option<(Vec<Row>, Vec<Option<bool>>)>,
+    error: Option<String>,
+}
+
+impl Window for QueryUi {
+    fn name(&self) -> &'static str {
+        "Point query"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for QueryUi {
+    // #Definition
+    //      A function creating the UI of the point query: a single point or a list of points
+    //      is tested against the picked MOC, the results of a list can be exported in CSV.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        if let Some((_, data)) = take_picked(&self.picked) {
+            match decompress(&data).map(|data| String::from_utf8_lossy(&data).into_owned()) {
+                Ok(list) => self.list = list,
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+        // The picked MOC may have been removed from the registry.
+        if self
+            .picked_file
+            .is_some_and(|id| registry.get(id).is_none())
+        {
+            self.pick(None);
+        }
+        self.make_cbox(ui, registry);
+        let Some((id, qty)) = self
+            .picked_file
+            .and_then(|id| registry.get(id).map(|entry| (id, entry.qty)))
+        else {
+            ui.label("Pick an S-MOC, a T-MOC or an ST-MOC to query it!");
+            return;
+        };
+        let Ok(columns) = columns(qty) else {
+            return;
+        };
+
+        ui.separator();
+        ui.heading("Point");
+        self.single_ui(ui, id, qty);
+
+        ui.separator();
+        ui.heading("List");
+        ui.label(format!(
+            "One point per line ({}), separated by commas, semicolons, tabs or spaces:",
+            columns.join(", ")
+        ));
+        egui::ScrollArea::vertical()
+            .id_source("query_list")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.list)
+                        .code_editor()
+                        .desired_rows(5)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        ui.horizontal(|ui| {
+            if ui.button("Load list…").clicked() {
+                let _ = pick_file("CSV", &CSV_EXTENSIONS, &self.picked).map_err(|e| err(&e));
+            }
+            if ui.button("Test list").clicked() {
+                self.error = None;
+                match parse_list(qty, &self.list)
+                    .and_then(|rows| query_list(id, &rows).map(|results| (rows, results)))
+                {
+                    Ok((rows, results)) => self.results = Some((rows, results)),
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if let Some((rows, results)) = &self.results {
+            results_ui(ui, qty, rows, results, registry.name(id).unwrap_or("query"));
+        }
+    }
+}
+
+impl QueryUi {
+    // #Definition
+    //      A function creating the combo box picking the queried MOC among the S-, T- and
+    //      ST-MOCs of the registry.
+    fn make_cbox(&mut self, ui: &mut Ui, registry: &Registry) {
+        let sel_text = self
+            .picked_file
+            .and_then(|id| registry.name(id).ok())
+            .unwrap_or("pick a MOC");
+        ui.horizontal(|ui| {
+            ui.label("MOC :");
+            egui::ComboBox::from_id_source("query_cbox")
+                .selected_text(sel_text)
+                .show_ui(ui, |ui| {
+                    for (id, entry) in registry.entries() {
+                        let selected = self.picked_file == Some(id);
+                        if !matches!(entry.qty, MocQType::Frequency)
+                            && ui.selectable_label(selected, &entry.name).clicked()
+                            && !selected
+                        {
+                            self.pick(Some(id));
+                        }
+                    }
+                });
+        });
+    }
+
+    // #Definition
+    //      pick sets the queried MOC, the results of the previous one are dropped.
+    fn pick(&mut self, id: Option<usize>) {
+        self.picked_file = id;
+        self.single = None;
+        self.results = None;
+    }
+
+    // #Definition
+    //      single_ui shows the values of the single point the MOC type needs and whether the
+    //      point is in the MOC.
+    // #Args
+    //  *   `id`: the id of the picked MOC
+    //  *   `qty`: its type
+    fn single_ui(&mut self, ui: &mut Ui, id: usize, qty: MocQType) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if !matches!(qty, MocQType::Time) {
+                ui.label("RA (°):");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.ra)
+                            .clamp_range(0.0..=360.0)
+                            .speed(0.1),
+                    )
+                    .changed();
+                ui.label("Dec (°):");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.dec)
+                            .clamp_range(-90.0..=90.0)
+                            .speed(0.1),
+                    )
+                    .changed();
+            }
+            if !matches!(qty, MocQType::Space) {
+                ui.label("JD:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.jd)
+                            .clamp_range(0.0..=f64::MAX)
+                            .speed(1.0),
+                    )
+                    .changed();
+            }
+            if ui.button("Test").clicked() {
+                let point = Point {
+                    pos: (!matches!(qty, MocQType::Time)).then_some((self.ra, self.dec)),
+                    jd: (!matches!(qty, MocQType::Space)).then_some(self.jd),
+                };
+                match contains(id, &[point]) {
+                    Ok(inside) => self.single = inside.first().copied(),
+                    Err(e) => err(&e),
+                }
+            } else if changed {
+                self.single = None;
+            }
+        });
+        match self.single {
+            Some(true) => ui.label("The point is in the MOC."),
+            Some(false) => ui.label("The point is not in the MOC."),
+            None => ui.label(""),
+        };
+    }
+}
+
+// #Definition
+//      results_ui shows the number of points in and out of the MOC and the result of each row,
+//      with a button exporting them in CSV.
+// #Args
+//  *   `qty`: the type of the queried MOC
+//  *   `rows`: the rows of the list
+//  *   `results`: the result of each row
+//  *   `name`: the name of the queried MOC, giving the name of the CSV file
+fn results_ui(ui: &mut Ui, qty: MocQType, rows: &[Row], results: &[Option<bool>], name: &str) {
+    let inside = results.iter().filter(|r| **r == Some(true)).count();
+    let outside = results.iter().filter(|r| **r == Some(false)).count();
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "In: {inside}, out: {outside}, invalid: {}",
+            results.len() - inside - outside
+        ));
+        if ui.button("Export CSV").clicked() {
+            let _ = to_csv(qty, rows, results)
+                .and_then(|csv| {
+                    to_file(
+                        &format!("query_{name}"),
+                        ".csv",
+                        "text/csv",
+                        csv.into_boxed_slice(),
+                    )
+                })
+                .map_err(|e| err(&e));
+        }
+    });
+    let txt_h = 18.0;
+    TableBuilder::new(ui)
+        .striped(true)
+        .max_scroll_height(250.0)
+        .column(Column::auto().at_least(40.0))
+        .column(Column::initial(200.0).at_least(100.0))
+        .column(Column::remainder().at_least(60.0))
+        .header(20.0, |mut header| {
+            header.col(|ui| {
+                ui.strong("Line");
+            });
+            header.col(|ui| {
+                ui.strong("Values");
+            });
+            header.col(|ui| {
+                ui.strong("In the MOC");
+            });
+        })
+        .body(|body| {
+            body.rows(txt_h, rows.len(), |i, mut row| {
+                row.col(|ui| {
+                    ui.label(rows[i].line.to_string());
+                });
+                row.col(|ui| {
+                    ui.label(rows[i].values.join(", "));
+                });
+                row.col(|ui| match (&results[i], &rows[i].point) {
+                    (Some(true), _) => {
+                        ui.label("yes");
+                    }
+                    (Some(false), _) => {
+                        ui.label("no");
+                    }
+                    (None, Err(e)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+                    (None, Ok(_)) => {}
+                });
+            });
+        });
+}
@@ -0,0 +1,217 @@
+use crate::error::Error;
+use crate::utils::commons::err;
+use crate::utils::registry::{MocEntry, Registry};
+use crate::windows::sky_view::{lon_lat_deg, Layer, SMoc, SkyView};
+
+use eframe::egui;
+use egui::{ScrollArea, Ui};
+use moc::storage::u64idx::common::MocQType;
+use moc::storage::u64idx::U64MocStore;
+
+use crate::windows::{View, Window};
+
+// A S-MOC of the store that can be drawn in the viewer.
+#[derive(Clone)]
+struct MocLayer {
+    id: usize,
+    // The load order of the MOC, telling it from a MOC given the same id after it was dropped.
+    order: usize,
+    name: String,
+    visible: bool,
+    color: [u8; 3],
+    opacity: f32,
+    // A copy of the MOC, made when the layer is first shown so that it is not copied from
+    // the store at each render.
+    moc: Option<SMoc>,
+}
+
+#[derive(Default, Clone)]
+pub struct SkyViewerUi {
+    // The layers, in load order, the last one is drawn on top.
+    layers: Vec<MocLayer>,
+    view: SkyView,
+}
+
+impl Window for SkyViewerUi {
+    fn name(&self) -> &'static str {
+        "Sky viewer"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for SkyViewerUi {
+    // #Definition
+    //      A function creating the UI of the sky viewer: the layers list, the zoom controls,
+    //      the sky view and the readout of the position under the cursor.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        if let Err(e) = self.update_layers(registry) {
+            err(&e);
+        }
+        if self.layers.is_empty() {
+            ui.label("Load a S-MOC to view it!");
+        }
+        self.layers_ui(ui, registry);
+
+        ui.horizontal(|ui| {
+            if ui.button("➖").clicked() {
+                self.view.set_zoom(self.view.zoom() / 2.0);
+            }
+            if ui.button("➕").clicked() {
+                self.view.set_zoom(self.view.zoom() * 2.0);
+            }
+            if ui.button("Reset view").clicked() {
+                self.view.reset();
+            }
+            ui.label(format!("FoV: {:.6}°", self.view.fov_deg()));
+        });
+
+        let shown: Vec<(&str, Layer<'_>)> = self
+            .layers
+            .iter()
+            .filter(|l| l.visible)
+            .filter_map(|l| {
+                let moc = l.moc.as_ref()?;
+                let layer = Layer {
+                    moc,
+                    color: l.color,
+                    opacity: l.opacity,
+                };
+                Some((l.name.as_str(), layer))
+            })
+            .collect();
+        let layers: Vec<Layer<'_>> = shown.iter().map(|(_, l)| *l).collect();
+        let hovered = self.view.ui(ui, &layers);
+        ui.label("Drag to pan, scroll to zoom, double click to reset.");
+
+        let Some(lonlat) = hovered else {
+            return;
+        };
+        let (ra, dec) = lon_lat_deg(&lonlat);
+        ui.label(format!("RA: {ra:.6}°, Dec: {dec:.6}°"));
+        // The cell is given at the depth of the deepest shown MOC.
+        if let Some(depth) = layers.iter().map(|l| l.moc.depth_max()).max() {
+            let hash = healpix::nested::hash(depth, lonlat.lon(), lonlat.lat());
+            let covering: Vec<&str> = shown
+                .iter()
+                .filter(|(_, l)| l.moc.is_in(lonlat.lon(), lonlat.lat()))
+                .map(|(name, _)| *name)
+                .collect();
+            ui.label(format!("HEALPix cell: depth {depth}, index {hash}"));
+            if covering.is_empty() {
+                ui.label("In none of the shown MOCs");
+            } else {
+                ui.label(format!("In: {}", covering.join(", ")));
+            }
+        }
+    }
+}
+
+impl SkyViewerUi {
+    // #Definition
+    //      A function that creates the list of layers: for each S-MOC, a checkbox to show it,
+    //      its colour (kept in the registry) and its opacity.
+    // #Args
+    //  *   `ui`: the ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn layers_ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        let mut changed = false;
+        ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+            egui::Grid::new("sky_viewer_layers")
+                .num_columns(3)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    for layer in &mut self.layers {
+                        changed |= ui.checkbox(&mut layer.visible, &layer.name).changed();
+                        if ui.color_edit_button_srgb(&mut layer.color).changed() {
+                            registry.set_color(layer.id, layer.color);
+                            changed = true;
+                        }
+                        let opacity = egui::Slider::new(&mut layer.opacity, 0.0..=1.0);
+                        changed |= ui.add(opacity.text("opacity")).changed();
+                        ui.end_row();
+                    }
+                });
+        });
+        if changed {
+            self.view.invalidate();
+        }
+    }
+
+    // #Definition
+    //      update_layers keeps the layers up to date with the S-MOCs of the registry: removed
+    //      MOCs are forgotten, new ones are added (hidden) and shown ones are copied if needed.
+    // #Args
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if a MOC can't be copied from the store.
+    fn update_layers(&mut self, registry: &Registry) -> Result<(), Error> {
+        let files: Vec<(usize, &MocEntry)> = registry
+            .entries()
+            .into_iter()
+            .filter(|(_, entry)| matches!(entry.qty, MocQType::Space))
+            .collect();
+
+        let mut changed = false;
+        let mut layers = Vec::with_capacity(files.len());
+        for (id, entry) in files {
+            let layer = match self
+                .layers
+                .iter()
+                .position(|l| l.id == id && l.order == entry.order)
+            {
+                Some(pos) => {
+                    let mut layer = self.layers.swap_remove(pos);
+                    // The MOC may have been renamed or recoloured.
+                    changed |= layer.visible && layer.color != entry.color;
+                    layer.name = entry.name.clone();
+                    layer.color = entry.color;
+                    layer
+                }
+                None => MocLayer {
+                    id,
+                    order: entry.order,
+                    name: entry.name.clone(),
+                    visible: false,
+                    color: entry.color,
+                    opacity: 0.6,
+                    moc: None,
+                },
+            };
+            layers.push(layer);
+        }
+        // The layers left are the ones of the MOCs removed from the store, whose id may have
+        // been given to another MOC.
+        changed |= self.layers.iter().any(|l| l.visible);
+
+        let mut res = Ok(());
+        for layer in layers.iter_mut().filter(|l| l.visible && l.moc.is_none()) {
+            match U64MocStore.get_smoc_copy(layer.id) {
+                Ok(moc) => {
+                    layer.moc = Some(moc);
+                    changed = true;
+                }
+                Err(e) => {
+                    layer.visible = false;
+                    res = Err(Error::from(e));
+                }
+            }
+        }
+        self.layers = layers;
+        if changed {
+            self.view.invalidate();
+        }
+        res
+    }
+}
@@ -0,0 +1,218 @@
+use std::ops::Range;
+
+use crate::controllers::creation::JD_TO_USEC;
+use crate::controllers::stmoc::{space_coverage, time_coverage};
+use crate::error::Error;
+use crate::utils::commons::err;
+use crate::utils::registry::Registry;
+use crate::windows::sky_view::{lon_lat_deg, Layer, SMoc, SkyView};
+use crate::windows::timeline::{jd_label, timeline_ui, TimeLayer};
+
+use eframe::egui;
+use egui::Ui;
+use moc::storage::u64idx::common::MocQType;
+
+use crate::windows::{View, Window};
+
+// The colours of the sky observed during the epoch and of the epoch on the timeline.
+const MOC_COLOR: [u8; 3] = [220, 50, 50];
+const COVERAGE_COLOR: [u8; 3] = [50, 120, 220];
+// The smallest epoch width, in days (about a tenth of a second).
+const MIN_WIDTH: f64 = 1e-6;
+
+#[derive(Default, Clone)]
+pub struct StMocExplorerUi {
+    picked_file: Option<usize>,
+    // The time coverage of the picked ST-MOC (in microseconds since JD=0).
+    coverage: Option<(usize, Vec<Range<u64>>)>,
+    // The selected epoch: its start (JD) and its width (days).
+    start: f64,
+    width: f64,
+    // The sky observed during an epoch, with the epoch it has been computed for.
+    moc: Option<(Range<f64>, SMoc)>,
+    view: SkyView,
+}
+
+impl Window for StMocExplorerUi {
+    fn name(&self) -> &'static str {
+        "ST-MOC explorer"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for StMocExplorerUi {
+    // #Definition
+    //      A function creating the UI of the ST-MOC explorer: an epoch is selected with the
+    //      sliders and the sky observed during this epoch is shown on the sky view.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        self.make_cbox(ui, registry);
+        if let Err(e) = self.update_coverage(registry) {
+            err(&e);
+            self.picked_file = None;
+            self.coverage = None;
+        }
+        let Some((_, coverage)) = &self.coverage else {
+            ui.label("Pick a ST-MOC to explore it!");
+            return;
+        };
+        let (Some(first), Some(last)) = (coverage.first(), coverage.last()) else {
+            ui.label("The ST-MOC is empty.");
+            return;
+        };
+        let (t_min, t_max) = (
+            first.start as f64 / JD_TO_USEC,
+            last.end as f64 / JD_TO_USEC,
+        );
+        ui.label(format!("Observations from: {}", jd_label(t_min)));
+        ui.label(format!("To: {}", jd_label(t_max)));
+
+        ui.spacing_mut().slider_width = (ui.available_width() - 150.0).max(100.0);
+        ui.add(
+            egui::Slider::new(&mut self.start, t_min..=t_max)
+                .text("Epoch start (JD)")
+                .max_decimals(8),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.width, MIN_WIDTH..=(t_max - t_min).max(MIN_WIDTH))
+                .logarithmic(true)
+                .text("Epoch width (days)"),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("◀ Previous epoch").clicked() {
+                self.start = (self.start - self.width).max(t_min);
+            }
+            if ui.button("Next epoch ▶").clicked() {
+                self.start = (self.start + self.width).min(t_max);
+            }
+        });
+        let epoch = self.start..self.start + self.width;
+        ui.label(format!(
+            "Epoch: {} to {}",
+            jd_label(epoch.start),
+            jd_label(epoch.end)
+        ));
+
+        let epoch_usec = (epoch.start * JD_TO_USEC) as u64..(epoch.end * JD_TO_USEC) as u64;
+        let epoch_ranges = [epoch_usec];
+        let time_layers = [
+            TimeLayer {
+                name: "Observed",
+                ranges: coverage,
+                color: COVERAGE_COLOR,
+            },
+            TimeLayer {
+                name: "Epoch",
+                ranges: &epoch_ranges,
+                color: MOC_COLOR,
+            },
+        ];
+        timeline_ui(ui, "stmoc_timeline", &time_layers);
+
+        // The sky is computed again once the sliders are released.
+        let computed = self.moc.as_ref().map(|(e, _)| e.clone());
+        if computed != Some(epoch.clone()) && !ui.input(|i| i.pointer.any_down()) {
+            if let Some(id) = self.picked_file {
+                match space_coverage(id, epoch.clone()) {
+                    Ok(moc) => self.moc = Some((epoch, moc)),
+                    Err(e) => {
+                        err(&e);
+                        self.picked_file = None;
+                    }
+                }
+                self.view.invalidate();
+            }
+        }
+
+        let layers: Vec<Layer<'_>> = self
+            .moc
+            .iter()
+            .map(|(_, moc)| Layer {
+                moc,
+                color: MOC_COLOR,
+                opacity: 1.0,
+            })
+            .collect();
+        let hovered = self.view.ui(ui, &layers);
+        ui.label("Drag to pan, scroll to zoom, double click to reset.");
+        if let (Some(lonlat), Some((_, moc))) = (hovered, &self.moc) {
+            let (ra, dec) = lon_lat_deg(&lonlat);
+            let observed = if moc.is_in(lonlat.lon(), lonlat.lat()) {
+                "observed during the epoch"
+            } else {
+                "not observed during the epoch"
+            };
+            ui.label(format!("RA: {ra:.6}°, Dec: {dec:.6}° ({observed})"));
+        }
+    }
+}
+
+impl StMocExplorerUi {
+    // #Definition
+    //      A function that creates the combobox of the ST-MOCs that can be explored.
+    // #Args
+    //  *   `ui`: the ui from the app.
+    //  *   `registry`: the MOCs that can be picked
+    fn make_cbox(&mut self, ui: &mut Ui, registry: &Registry) {
+        let sel_text = self
+            .picked_file
+            .and_then(|id| registry.name(id).ok())
+            .unwrap_or("pick a ST-MOC");
+        ui.horizontal(|ui| {
+            ui.label("MOC :");
+            egui::ComboBox::from_id_source("stmoc_explorer_cbox")
+                .selected_text(sel_text)
+                .show_ui(ui, |ui| {
+                    for (id, entry) in registry.entries() {
+                        if matches!(entry.qty, MocQType::TimeSpace) {
+                            ui.selectable_value(&mut self.picked_file, Some(id), &entry.name);
+                        }
+                    }
+                });
+        });
+    }
+
+    // #Definition
+    //      update_coverage computes the time coverage of the picked ST-MOC when it changes, the
+    //      epoch then starts at the first observation.
+    // #Args
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if the time coverage of the picked MOC can't be computed.
+    fn update_coverage(&mut self, registry: &Registry) -> Result<(), Error> {
+        // The picked MOC may have been removed from the registry.
+        if self
+            .picked_file
+            .is_some_and(|id| registry.get(id).is_none())
+        {
+            self.picked_file = None;
+        }
+        let cached = self.coverage.as_ref().map(|(id, _)| *id);
+        if cached == self.picked_file {
+            return Ok(());
+        }
+        self.coverage = None;
+        self.moc = None;
+        self.view.invalidate();
+        if let Some(id) = self.picked_file {
+            let coverage = time_coverage(id)?;
+            if let (Some(first), Some(last)) = (coverage.first(), coverage.last()) {
+                self.start = first.start as f64 / JD_TO_USEC;
+                self.width = ((last.end - first.start) as f64 / JD_TO_USEC / 100.0).max(MIN_WIDTH);
+            }
+            self.coverage = Some((id, coverage));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::utils::commons::err;
+use crate::utils::registry::{MocEntry, Registry};
+use crate::windows::timeline::{timeline_ui, TimeLayer};
+
+use eframe::egui;
+use egui::{ScrollArea, Ui};
+use moc::storage::u64idx::common::MocQType;
+use moc::storage::u64idx::U64MocStore;
+
+use crate::windows::{View, Window};
+
+// A T-MOC of the store that can be drawn on the timeline.
+#[derive(Clone)]
+struct TMocLayer {
+    id: usize,
+    name: String,
+    visible: bool,
+    color: [u8; 3],
+    // The ranges of the T-MOC (in microseconds since JD=0), copied when it is first shown.
+    ranges: Option<Vec<Range<u64>>>,
+}
+
+#[derive(Default, Clone)]
+pub struct TimelineViewerUi {
+    // The layers, in load order.
+    layers: Vec<TMocLayer>,
+}
+
+impl Window for TimelineViewerUi {
+    fn name(&self) -> &'static str {
+        "Timeline viewer"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for TimelineViewerUi {
+    // #Definition
+    //      A function creating the UI of the timeline viewer: the T-MOCs to compare and their
+    //      timeline.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        if let Err(e) = self.update_layers(registry) {
+            err(&e);
+        }
+        if self.layers.is_empty() {
+            ui.label("Load a T-MOC to view it!");
+            return;
+        }
+
+        ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+            egui::Grid::new("timeline_viewer_layers")
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    for layer in &mut self.layers {
+                        ui.checkbox(&mut layer.visible, &layer.name);
+                        if ui.color_edit_button_srgb(&mut layer.color).changed() {
+                            registry.set_color(layer.id, layer.color);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        let layers: Vec<TimeLayer<'_>> = self
+            .layers
+            .iter()
+            .filter(|l| l.visible)
+            .filter_map(|l| {
+                Some(TimeLayer {
+                    name: &l.name,
+                    ranges: l.ranges.as_ref()?,
+                    color: l.color,
+                })
+            })
+            .collect();
+        timeline_ui(ui, "timeline_viewer", &layers);
+        ui.label("Drag to pan, ctrl + scroll to zoom, double click to see all the ranges.");
+    }
+}
+
+impl TimelineViewerUi {
+    // #Definition
+    //      update_layers keeps the layers up to date with the T-MOCs of the registry: removed
+    //      MOCs are forgotten, new ones are added (hidden) and shown ones are copied if needed.
+    // #Args
+    //  *   `registry`: the MOCs of the app
+    // #Errors
+    //      Error if the ranges of a MOC can't be copied from the store.
+    fn update_layers(&mut self, registry: &Registry) -> Result<(), Error> {
+        let files: Vec<(usize, &MocEntry)> = registry
+            .entries()
+            .into_iter()
+            .filter(|(_, entry)| matches!(entry.qty, MocQType::Time))
+            .collect();
+
+        let mut layers = Vec::with_capacity(files.len());
+        for (id, entry) in files {
+            let layer = match self.layers.iter().position(|l| l.id == id) {
+                Some(pos) => {
+                    let mut layer = self.layers.swap_remove(pos);
+                    // The MOC may have been renamed or recoloured.
+                    layer.name = entry.name.clone();
+                    layer.color = entry.color;
+                    layer
+                }
+                None => TMocLayer {
+                    id,
+                    name: entry.name.clone(),
+                    visible: false,
+                    color: entry.color,
+                    ranges: None,
+                },
+            };
+            layers.push(layer);
+        }
+
+        let mut res = Ok(());
+        for layer in layers
+            .iter_mut()
+            .filter(|l| l.visible && l.ranges.is_none())
+        {
+            match U64MocStore.to_ranges(layer.id) {
+                Ok(ranges) => layer.ranges = Some(ranges),
+                Err(e) => {
+                    layer.visible = false;
+                    res = Err(Error::from(e));
+                }
+            }
+        }
+        self.layers = layers;
+        res
+    }
+}
@@ -1,6 +1,6 @@
 use crate::controllers::op1::*;
 use crate::utils::commons::err;
-use crate::utils::namestore::{get_last, get_name, get_store, list_names};
+use crate::utils::registry::Registry;
 
 use eframe::egui;
 use egui::{TextEdit, Ui};
@@ -22,13 +22,13 @@ impl Window for UnitaryUi {
         "MOC unitary logical operation"
     }
 
-    fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
         egui::Window::new(self.name())
             .open(open)
             .resizable(false)
             .show(ctx, |ui| {
                 use crate::windows::View as _;
-                self.ui(ui);
+                self.ui(ui, registry);
             });
     }
 }
@@ -38,12 +38,12 @@ impl View for UnitaryUi {
     //      A function creating the UI for operations on a stored MOC.
     // #Args
     //  *   `ui`: The ui from the app.
-    //  *   `e`: an optional String in case of past errors to keep it visible until change
-    fn ui(&mut self, ui: &mut Ui) {
+    //  *   `registry`: the MOCs of the app
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
         let mut op: Op1 = self.operation;
 
         //If no file has been imported yet
-        if list_names().unwrap().is_empty() {
+        if registry.is_empty() {
             ui.label("Pick a file!");
         //If files have been imported and can be chosen from
         } else {
@@ -55,15 +55,15 @@ impl View for UnitaryUi {
                 .show(ui, |ui| {
                     //Combo box containing the different files that can be picked from
                     ui.label("MOC : ");
-                    if self.picked_file.is_some() && get_name(self.picked_file.unwrap()).is_ok() {
-                        self.make_cbox(ui, get_name(self.picked_file.unwrap()).unwrap());
+                    if let Some(Ok(name)) = self.picked_file.map(|id| registry.name(id)) {
+                        self.make_cbox(ui, name.to_owned(), registry);
                     } else {
-                        self.picked_file = Some(get_last().unwrap().0);
-                        self.make_cbox(ui, "pick a file".to_owned());
+                        self.picked_file = registry.ids().last().copied();
+                        self.make_cbox(ui, "pick a file".to_owned(), registry);
                     }
                     ui.end_row();
 
-                    self.op_one_ui(ui);
+                    self.op_one_ui(ui, registry);
                     ui.end_row();
 
                     //In case of degrade option ask for new depth
@@ -74,15 +74,15 @@ impl View for UnitaryUi {
                         ui.end_row();
                     }
 
-                    if self.picked_file.is_some() {
+                    if let Some(picked_file) = self.picked_file {
                         ui.label("New MOC name :");
                         ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
                         ui.end_row();
 
                         //Button launching the operation
                         if !matches!(
-                            U64MocStore.get_qty_type(self.picked_file.unwrap()),
-                            Ok(MocQType::TimeSpace)
+                            registry.get(picked_file).map(|e| e.qty),
+                            Some(MocQType::TimeSpace)
                         ) {
                             if ui.button("Launch").clicked() {
                                 if deg {
@@ -95,11 +95,11 @@ impl View for UnitaryUi {
                                     self.name = format!(
                                         "{}_{}",
                                         op,
-                                        get_name(self.picked_file.unwrap()).unwrap()
+                                        registry.name(picked_file).unwrap_or_default()
                                     );
                                 }
-                                let _ = op1(self.picked_file.unwrap(), op, &self.name)
-                                    .map_err(|e| err(&e));
+                                let _ =
+                                    op1(registry, picked_file, op, &self.name).map_err(|e| err(&e));
                                 self.name = String::default();
                             };
                         } else {
@@ -116,14 +116,14 @@ impl UnitaryUi {
     //      A function that creates comboboxes.
     // #Args
     //  *   ui: Ui, the ui from the app.
-    //  *   sel_text: &str, the text to show in the combo box.
-    //  *   id: &str, the combobox gui ID.
-    fn make_cbox(&mut self, ui: &mut Ui, text: String) {
+    //  *   text: String, the text to show in the combo box.
+    //  *   registry: &Registry, the MOCs that can be picked.
+    fn make_cbox(&mut self, ui: &mut Ui, text: String, registry: &Registry) {
         egui::ComboBox::from_id_source("file_cbox")
             .selected_text(text)
             .show_ui(ui, |ui| {
-                for file in get_store().read().unwrap().iter() {
-                    ui.selectable_value(&mut self.picked_file, Some(*file.0), &file.1 .0);
+                for (id, entry) in registry.entries() {
+                    ui.selectable_value(&mut self.picked_file, Some(id), &entry.name);
                 }
             });
     }
@@ -132,7 +132,8 @@ impl UnitaryUi {
     //      A function handling operations on a stored MOC.
     // #Args
     //  *   `ui`: The ui from the app.
-    fn op_one_ui(&mut self, ui: &mut Ui) {
+    //  *   `registry`: the MOCs of the app
+    fn op_one_ui(&mut self, ui: &mut Ui, registry: &Registry) {
         // An operation combo box including Intersection and Union.
         let sel_text = format!("{}", self.operation);
 
@@ -146,14 +147,15 @@ impl UnitaryUi {
                     Op1::Degrade { new_depth: 0 },
                     "Degrade",
                 );
-                if self.picked_file.is_some()
-                    && matches!(
-                        U64MocStore.get_qty_type(self.picked_file.unwrap()),
-                        Ok(MocQType::Space)
-                    )
-                {
+                let qty = self
+                    .picked_file
+                    .and_then(|id| registry.get(id))
+                    .map(|e| e.qty);
+                if matches!(qty, Some(MocQType::Space | MocQType::Frequency)) {
                     ui.selectable_value(&mut self.operation, Op1::Extend, "Extend");
                     ui.selectable_value(&mut self.operation, Op1::Contract, "Contract");
+                }
+                if matches!(qty, Some(MocQType::Space)) {
                     ui.selectable_value(&mut self.operation, Op1::ExtBorder, "ExtBorder");
                     ui.selectable_value(&mut self.operation, Op1::IntBorder, "IntBorder");
                     ui.selectable_value(&mut self.operation, Op1::Split, "Split");
@@ -168,29 +170,35 @@ impl UnitaryUi {
 // #Args
 //  *   `id`: the index of the MOC to be operated on
 //  *   `ui`: the ui used by the app
-pub(crate) fn lite_unit_ui(ui: &mut Ui, id: usize) {
+//  *   `registry`: the registry the result is added to
+pub(crate) fn lite_unit_ui(ui: &mut Ui, id: usize, registry: &mut Registry) {
     if !matches!(U64MocStore.get_qty_type(id), Ok(MocQType::TimeSpace)) {
         if ui.button("Complement").clicked() {
-            lite_op(id, Op1::Complement);
+            lite_op(id, Op1::Complement, registry);
         };
-        if matches!(U64MocStore.get_qty_type(id), Ok(MocQType::Space)) {
+        if matches!(
+            U64MocStore.get_qty_type(id),
+            Ok(MocQType::Space | MocQType::Frequency)
+        ) {
             if ui.button("Extend").clicked() {
-                lite_op(id, Op1::Extend);
+                lite_op(id, Op1::Extend, registry);
             };
             if ui.button("Contract").clicked() {
-                lite_op(id, Op1::Contract);
+                lite_op(id, Op1::Contract, registry);
             };
+        }
+        if matches!(U64MocStore.get_qty_type(id), Ok(MocQType::Space)) {
             if ui.button("ExtBorder").clicked() {
-                lite_op(id, Op1::ExtBorder);
+                lite_op(id, Op1::ExtBorder, registry);
             };
             if ui.button("IntBorder").clicked() {
-                lite_op(id, Op1::IntBorder);
+                lite_op(id, Op1::IntBorder, registry);
             };
             if ui.button("Split").clicked() {
-                lite_op(id, Op1::Split);
+                lite_op(id, Op1::Split, registry);
             };
             if ui.button("SplitIndirect").clicked() {
-                lite_op(id, Op1::SplitIndirect);
+                lite_op(id, Op1::SplitIndirect, registry);
             };
         }
     } else {
@@ -202,10 +210,14 @@ pub(crate) fn lite_unit_ui(ui: &mut Ui, id: usize) {
 // #Args
 //  *   `id`: the index of the MOC to be operated on
 //  *   `operation`: the operation that needs to be applied on the MOCs
+//  *   `registry`: the registry the result is added to
 // #Errors
 //      may show an error message coming from the op1 function
-fn lite_op(id: usize, operation: Op1) {
+fn lite_op(id: usize, operation: Op1, registry: &mut Registry) {
     //Button launching the operation
-    let name = format!("{}_{}", operation, get_name(id).unwrap());
-    let _ = op1(id, operation, &name).map_err(|e| err(&e));
+    let _ = registry
+        .name(id)
+        .map(|name| format!("{operation}_{name}"))
+        .and_then(|name| op1(registry, id, operation, &name))
+        .map_err(|e| err(&e));
 }
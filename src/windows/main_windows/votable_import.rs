@@ -0,0 +1,535 @@
+use std::collections::BTreeMap;
+
+use crate::controllers::creation::*;
+use crate::controllers::frame::Frame;
+use crate::error::Error;
+use crate::utils::commons::{pick_file, take_picked, PickedFile};
+use crate::utils::compression::decompress;
+use crate::utils::registry::Registry;
+use crate::utils::votable::{parse_votable, VoField, VoTable};
+
+use eframe::egui;
+use egui::{TextEdit, Ui};
+
+use crate::windows::{View, Window};
+
+// The extensions of the VOTable files, compressed or not.
+const VOTABLE_EXTENSIONS: [&str; 5] = ["xml", "vot", "votable", "gz", "bz2"];
+// The offset between Modified Julian Days and Julian Days.
+const MJD_TO_JD: f64 = 2_400_000.5;
+
+// A conversion of the values of a field to degrees or to JD.
+type Converter = Box<dyn Fn(f64) -> f64>;
+
+// What the values of a field are.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    // An angle, converted to degrees from the unit of the field.
+    Angle,
+    // A time, in JD or in MJD.
+    Time,
+}
+
+// A field the MOC creation needs, with the UCDs and the names it is usually found under.
+struct Role {
+    label: &'static str,
+    kind: Kind,
+    ucds: &'static [&'static str],
+    names: &'static [&'static str],
+}
+const RA: Role = Role {
+    label: "RA",
+    kind: Kind::Angle,
+    ucds: &["pos.eq.ra"],
+    names: &["ra", "raj2000", "_raj2000", "ra_icrs", "s_ra"],
+};
+const DEC: Role = Role {
+    label: "Dec",
+    kind: Kind::Angle,
+    ucds: &["pos.eq.dec"],
+    names: &["dec", "de", "dej2000", "_dej2000", "de_icrs", "s_dec"],
+};
+const RADIUS: Role = Role {
+    label: "Radius",
+    kind: Kind::Angle,
+    ucds: &["phys.angsize", "pos.angdistance", "phys.angsize.smajaxis"],
+    names: &["radius", "rad", "r", "s_fov"],
+};
+const TIME: Role = Role {
+    label: "Time",
+    kind: Kind::Time,
+    ucds: &["time.epoch"],
+    names: &["jd", "mjd", "time", "epoch", "obs_time", "t_mid"],
+};
+const TIME_MIN: Role = Role {
+    label: "Time start",
+    kind: Kind::Time,
+    ucds: &["time.start"],
+    names: &["t_min", "jd_min", "mjd_min", "start", "tmin"],
+};
+const TIME_MAX: Role = Role {
+    label: "Time end",
+    kind: Kind::Time,
+    ucds: &["time.end"],
+    names: &["t_max", "jd_max", "mjd_max", "end", "stop", "tmax"],
+};
+
+// The MOCs that can be created from a catalogue.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum VoMocType {
+    #[default]
+    Coo,
+    SmallCones,
+    LargeCones,
+    DecimalJd,
+    DecimalJdRange,
+    DecimalJdCoo,
+}
+impl VoMocType {
+    const ALL: [Self; 6] = [
+        Self::Coo,
+        Self::SmallCones,
+        Self::LargeCones,
+        Self::DecimalJd,
+        Self::DecimalJdRange,
+        Self::DecimalJdCoo,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Coo => "S-MOC: positions",
+            Self::SmallCones => "S-MOC: small cones",
+            Self::LargeCones => "S-MOC: large cones",
+            Self::DecimalJd => "T-MOC: times",
+            Self::DecimalJdRange => "T-MOC: time ranges",
+            Self::DecimalJdCoo => "ST-MOC: times and positions",
+        }
+    }
+
+    // The fields needed, in the order expected by the creation functions.
+    fn roles(self) -> &'static [Role] {
+        match self {
+            Self::Coo => &[RA, DEC],
+            Self::SmallCones | Self::LargeCones => &[RA, DEC, RADIUS],
+            Self::DecimalJd => &[TIME],
+            Self::DecimalJdRange => &[TIME_MIN, TIME_MAX],
+            Self::DecimalJdCoo => &[TIME, RA, DEC],
+        }
+    }
+
+    fn has_space(self) -> bool {
+        !matches!(self, Self::DecimalJd | Self::DecimalJdRange)
+    }
+
+    fn has_time(self) -> bool {
+        matches!(
+            self,
+            Self::DecimalJd | Self::DecimalJdRange | Self::DecimalJdCoo
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct VoTableImportUi {
+    picked: PickedFile,
+    // The loaded VOTable, with its file name.
+    table: Option<(String, VoTable)>,
+    typ: VoMocType,
+    // The field of each role of the MOC type.
+    columns: Vec<usize>,
+    // The times are Modified Julian Days.
+    mjd: bool,
+    space_depth: u8,
+    time_depth: u8,
+    name: String,
+    error: Option<String>,
+    info: Option<String>,
+}
+
+impl Default for VoTableImportUi {
+    fn default() -> Self {
+        Self {
+            picked: Default::default(),
+            table: Default::default(),
+            typ: Default::default(),
+            columns: Default::default(),
+            mjd: Default::default(),
+            space_depth: 10,
+            time_depth: 35,
+            name: Default::default(),
+            error: Default::default(),
+            info: Default::default(),
+        }
+    }
+}
+impl Window for VoTableImportUi {
+    fn name(&self) -> &'static str {
+        "VOTable import"
+    }
+
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry) {
+        egui::Window::new(self.name())
+            .open(open)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                use crate::windows::View as _;
+                self.ui(ui, registry);
+            });
+    }
+}
+
+impl View for VoTableImportUi {
+    // #Definition
+    //      A function creating the UI of the VOTable import: the fields of the catalogue are
+    //      picked for the MOC type (guessed from their UCD or their name) and the MOC is created.
+    // #Args
+    //  *   `ui`: The ui from the app.
+    //  *   `registry`: the registry the created MOC is added to
+    fn ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        if ui.button("Open VOTable").clicked() {
+            self.error = pick_file("VOTable", &VOTABLE_EXTENSIONS, &self.picked)
+                .err()
+                .map(|e| e.to_string());
+        }
+        if let Some((file_name, data)) = take_picked(&self.picked) {
+            match decompress(&data).and_then(|data| parse_votable(&data)) {
+                Ok(table) => {
+                    self.table = Some((file_name, table));
+                    self.error = None;
+                    self.info = None;
+                    self.guess_columns();
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+
+        if let Some((file_name, table)) = &self.table {
+            ui.label(format!(
+                "{file_name}: {} rows, {} fields",
+                table.rows.len(),
+                table.fields.len()
+            ));
+            egui::CollapsingHeader::new("Fields").show(ui, |ui| fields_grid(ui, &table.fields));
+            ui.add_space(10.0);
+            self.options_ui(ui, registry);
+        }
+
+        if let Some(info) = &self.info {
+            ui.label(info);
+        }
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
+}
+
+impl VoTableImportUi {
+    // #Definition
+    //      options_ui shows the MOC type, the field of each role, the depths and the name of
+    //      the MOC to create.
+    // #Args
+    //  *   `ui`: the ui from the app.
+    //  *   `registry`: the registry the created MOC is added to
+    fn options_ui(&mut self, ui: &mut Ui, registry: &mut Registry) {
+        let Some((_, table)) = &self.table else {
+            return;
+        };
+        let mut typ = self.typ;
+        egui::Grid::new("votable_import_grid")
+            .num_columns(2)
+            .spacing([5.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("MOC type:");
+                egui::ComboBox::from_id_source("votable_moc_type")
+                    .selected_text(typ.label())
+                    .show_ui(ui, |ui| {
+                        for t in VoMocType::ALL {
+                            ui.selectable_value(&mut typ, t, t.label());
+                        }
+                    });
+                ui.end_row();
+
+                for (role, column) in self.typ.roles().iter().zip(self.columns.iter_mut()) {
+                    ui.label(format!("{}:", role.label));
+                    egui::ComboBox::from_id_source(role.label)
+                        .width(250.0)
+                        .selected_text(field_label(&table.fields, *column))
+                        .show_ui(ui, |ui| {
+                            for i in 0..table.fields.len() {
+                                ui.selectable_value(column, i, field_label(&table.fields, i));
+                            }
+                        });
+                    ui.end_row();
+                }
+                if self.typ.has_time() {
+                    ui.label("Times in MJD:");
+                    ui.checkbox(&mut self.mjd, "");
+                    ui.end_row();
+                }
+
+                if self.typ.has_space() {
+                    ui.label("Space depth:");
+                    ui.add(egui::Slider::new(&mut self.space_depth, 1..=25));
+                    ui.end_row();
+                }
+                if self.typ.has_time() {
+                    ui.label("Time depth:");
+                    ui.add(egui::Slider::new(&mut self.time_depth, 1..=61));
+                    ui.end_row();
+                }
+                ui.label("New MOC name :");
+                ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
+                ui.end_row();
+            });
+        if typ != self.typ {
+            self.typ = typ;
+            self.guess_columns();
+        }
+
+        if ui.button("Create").clicked() {
+            match self.create(registry) {
+                Ok(info) => {
+                    self.info = Some(info);
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.info = None;
+                    self.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    // #Definition
+    //      guess_columns picks for each role the field with one of the role's UCDs (the main
+    //      one first), else the field with one of the role's usual names.
+    //      The times are taken as MJD if a time field says so or if its values are too small
+    //      to be JD.
+    fn guess_columns(&mut self) {
+        let Some((_, table)) = &self.table else {
+            return;
+        };
+        let fields = &table.fields;
+        self.columns = self
+            .typ
+            .roles()
+            .iter()
+            .enumerate()
+            .map(|(i, role)| {
+                let has_ucd = |f: &VoField| {
+                    f.ucd
+                        .to_lowercase()
+                        .split(';')
+                        .any(|word| role.ucds.contains(&word))
+                };
+                fields
+                    .iter()
+                    .position(|f| has_ucd(f) && f.ucd.contains("meta.main"))
+                    .or_else(|| fields.iter().position(has_ucd))
+                    .or_else(|| {
+                        fields
+                            .iter()
+                            .position(|f| role.names.contains(&f.name.to_lowercase().as_str()))
+                    })
+                    .unwrap_or(i.min(fields.len().saturating_sub(1)))
+            })
+            .collect();
+        if let Some(column) = self
+            .typ
+            .roles()
+            .iter()
+            .zip(&self.columns)
+            .find(|(role, _)| role.kind == Kind::Time)
+            .map(|(_, column)| *column)
+        {
+            let field = &fields[column];
+            let says_mjd = [&field.name, &field.xtype, &field.ucd, &field.unit]
+                .iter()
+                .any(|s| s.to_lowercase().contains("mjd"));
+            let first = table
+                .rows
+                .iter()
+                .find_map(|row| row.get(column).copied().flatten());
+            self.mjd = says_mjd || first.is_some_and(|v| v < 1e6);
+        }
+    }
+
+    // #Definition
+    //      create creates the MOC from the picked fields and adds it to the store and the registry.
+    // #Args
+    //  *   `registry`: the registry the created MOC is added to
+    // #Returns
+    //      A message giving the number of rows used and skipped.
+    // #Errors
+    //      Error if the unit of an angle is not supported or if the MOC creation fails.
+    fn create(&mut self, registry: &mut Registry) -> Result<String, Error> {
+        let Some((file_name, table)) = &self.table else {
+            return Err(Error::NotFound(String::from("VOTable")));
+        };
+        let mut converters: Vec<(usize, Converter)> = Vec::default();
+        for (role, column) in self.typ.roles().iter().zip(&self.columns) {
+            let field = &table.fields[*column];
+            let converter: Converter = match role.kind {
+                Kind::Angle => {
+                    let scale = deg_per_unit(&field.unit).ok_or_else(|| {
+                        Error::Parse(format!(
+                            "Unsupported unit '{}' for the {} field '{}'",
+                            field.unit, role.label, field.name
+                        ))
+                    })?;
+                    Box::new(move |v| v * scale)
+                }
+                Kind::Time if self.mjd => Box::new(|v| v + MJD_TO_JD),
+                Kind::Time => Box::new(|v| v),
+            };
+            converters.push((*column, converter));
+        }
+        let columns: Vec<(usize, &dyn Fn(f64) -> f64)> =
+            converters.iter().map(|(c, f)| (*c, f.as_ref())).collect();
+        let (values, skipped) = table.values(&columns);
+        let n_rows = table.rows.len() - skipped;
+
+        let (sd, td) = (self.space_depth, self.time_depth);
+        let id = match self.typ {
+            VoMocType::Coo => from_coo(sd, values, Frame::Icrs)?,
+            VoMocType::SmallCones => from_small_cones(sd, values, Frame::Icrs)?,
+            VoMocType::LargeCones => from_large_cones(sd, values, Frame::Icrs)?,
+            VoMocType::DecimalJd => from_decimal_jd(td, values)?,
+            VoMocType::DecimalJdRange => from_decimal_jd_range(td, values)?,
+            VoMocType::DecimalJdCoo => from_decimal_jd_coo(td, sd, values)?,
+        };
+        let name = if self.name.is_empty() {
+            let stem = file_name.split('.').next().unwrap_or(file_name);
+            match (self.typ.has_time(), self.typ.has_space()) {
+                (true, true) => format!("{stem}_ST_{td}_{sd}"),
+                (true, false) => format!("{stem}_T_{td}"),
+                _ => format!("{stem}_S_{sd}"),
+            }
+        } else {
+            std::mem::take(&mut self.name)
+        };
+        let depths = match (self.typ.has_time(), self.typ.has_space()) {
+            (true, true) => format!("time depth {td}, space depth {sd}"),
+            (true, false) => format!("depth {td}"),
+            _ => format!("depth {sd}"),
+        };
+        let name = registry.insert(&name, id, Some(file_name.clone()), BTreeMap::new())?;
+        registry.added("VOTable", &[], &format!("{file_name}, {depths}"), &[id])?;
+        let mut info = format!("'{name}' created from {n_rows} rows");
+        if skipped > 0 {
+            info.push_str(&format!(
+                ", {skipped} rows skipped (null or non numeric values)"
+            ));
+        }
+        Ok(info)
+    }
+}
+
+// #Definition
+//      fields_grid shows the name, UCD, unit and datatype of the fields of a VOTable.
+fn fields_grid(ui: &mut Ui, fields: &[VoField]) {
+    egui::ScrollArea::vertical()
+        .max_height(200.0)
+        .show(ui, |ui| {
+            egui::Grid::new("votable_fields_grid")
+                .striped(true)
+                .spacing([10.0, 2.0])
+                .show(ui, |ui| {
+                    for header in ["Name", "UCD", "Unit", "Datatype"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for field in fields {
+                        ui.label(&field.name);
+                        ui.label(&field.ucd);
+                        ui.label(&field.unit);
+                        ui.label(&field.datatype);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+// The label of a field in the combo boxes: its name, UCD and unit.
+fn field_label(fields: &[VoField], column: usize) -> String {
+    let Some(field) = fields.get(column) else {
+        return String::from("-");
+    };
+    let mut label = field.name.clone();
+    if !field.ucd.is_empty() {
+        label.push_str(&format!(" ({})", field.ucd));
+    }
+    if !field.unit.is_empty() {
+        label.push_str(&format!(" [{}]", field.unit));
+    }
+    label
+}
+
+// The number of degrees in an angle unit, None if the unit is not an angle unit.
+fn deg_per_unit(unit: &str) -> Option<f64> {
+    match unit.trim() {
+        "" | "deg" | "degree" | "degrees" => Some(1.0),
+        "h" | "hour" | "hours" => Some(15.0),
+        "arcmin" => Some(1.0 / 60.0),
+        "arcsec" => Some(1.0 / 3600.0),
+        "mas" => Some(1.0 / 3_600_000.0),
+        "rad" => Some(180.0 / std::f64::consts::PI),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moc::storage::u64idx::U64MocStore;
+
+    use super::*;
+
+    // The RA, Dec and time have to be told from their UCD, the names being misleading.
+    const CATALOGUE: &str = r#"<?xml version="1.0"?>
+<VOTABLE version="1.4">
+  <RESOURCE><TABLE>
+    <FIELD name="ra" ucd="pos.eq.ra" unit="deg" datatype="double"/>
+    <FIELD name="time" ucd="meta.id" datatype="int"/>
+    <FIELD name="alpha" ucd="pos.eq.ra;meta.main" unit="h" datatype="double"/>
+    <FIELD name="delta" ucd="pos.eq.dec;meta.main" unit="deg" datatype="double"/>
+    <FIELD name="obs" ucd="time.epoch" unit="d" datatype="double"/>
+    <DATA><TABLEDATA>
+      <TR><TD>15</TD><TD>1</TD><TD>1</TD><TD>20</TD><TD>51544.5</TD></TR>
+      <TR><TD>30</TD><TD>2</TD><TD>2</TD><TD></TD><TD>51545.5</TD></TR>
+    </TABLEDATA></DATA>
+  </TABLE></RESOURCE>
+</VOTABLE>"#;
+
+    fn import_ui(typ: VoMocType) -> VoTableImportUi {
+        let table = parse_votable(CATALOGUE.as_bytes()).unwrap();
+        let mut ui = VoTableImportUi {
+            table: Some((String::from("cat.xml"), table)),
+            typ,
+            ..Default::default()
+        };
+        ui.guess_columns();
+        ui
+    }
+
+    #[test]
+    fn columns_guessed_from_ucds() {
+        let ui = import_ui(VoMocType::DecimalJdCoo);
+        assert_eq!(ui.columns, vec![4, 2, 3]);
+        // The values are too small to be JD.
+        assert!(ui.mjd);
+        let ui = import_ui(VoMocType::Coo);
+        assert_eq!(ui.columns, vec![2, 3]);
+    }
+
+    #[test]
+    fn create_from_guessed_columns() {
+        let mut ui = import_ui(VoMocType::DecimalJdCoo);
+        let mut registry = Registry::default();
+        let info = ui.create(&mut registry).unwrap();
+        // The row with an empty Dec is skipped.
+        assert!(info.contains("from 1 rows, 1 rows skipped"), "{info}");
+        let (id, entry) = registry.entries()[0];
+        assert_eq!(entry.name, "cat_ST_35_10");
+        U64MocStore.drop(id).unwrap();
+    }
+}
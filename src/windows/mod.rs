@@ -1,8 +1,16 @@
+pub(crate) mod csv_import;
+pub(crate) mod dropped_files;
+pub(crate) mod expression_bar;
 pub(crate) mod list_window;
 pub(crate) mod main_windows;
+pub(crate) mod open_url;
+pub(crate) mod sky_view;
+pub(crate) mod timeline;
+
+use crate::utils::registry::Registry;
 
 pub trait View {
-    fn ui(&mut self, ui: &mut egui::Ui);
+    fn ui(&mut self, ui: &mut egui::Ui, registry: &mut Registry);
 }
 
 // Something to view
@@ -11,5 +19,5 @@ pub trait Window {
     fn name(&self) -> &'static str;
 
     // Show windows, etc
-    fn show(&mut self, ctx: &egui::Context, open: &mut bool);
+    fn show(&mut self, ctx: &egui::Context, open: &mut bool, registry: &mut Registry);
 }
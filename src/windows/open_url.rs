@@ -0,0 +1,88 @@
+use egui::Context;
+
+use crate::utils::commons::{fetch_url, Fetched};
+
+// The OpenUrl struct, a window downloading a MOC file from a URL.
+#[derive(Default)]
+pub(crate) struct OpenUrl {
+    open: bool,
+    url: String,
+    fetched: Fetched,
+    // The URL being downloaded, if any.
+    fetching: Option<String>,
+    error: Option<String>,
+}
+impl OpenUrl {
+    // #Definition
+    //      open opens the window.
+    pub(crate) fn open(&mut self) {
+        self.open = true;
+        self.error = None;
+    }
+
+    // #Definition
+    //      ui shows the window asking for the URL while it is open.
+    // #Args
+    //  *   `ctx`: the app's context
+    // #Returns
+    //      The URL, the name and the content of the downloaded file once the download is over.
+    pub(crate) fn ui(&mut self, ctx: &Context) -> Option<(String, String, Vec<u8>)> {
+        let mut downloaded = None;
+        if self.fetching.is_some() {
+            if let Some(result) = self.fetched.lock().ok().and_then(|mut f| f.take()) {
+                let url = self.fetching.take().unwrap_or_default();
+                match result {
+                    Ok((name, data)) => {
+                        self.open = false;
+                        downloaded = Some((url, name, data));
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+        }
+        if !self.open {
+            return downloaded;
+        }
+
+        let mut open = true;
+        egui::Window::new("Open URL")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("URL of a MOC file (FITS, JSON or ASCII, possibly compressed):");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.url)
+                        .hint_text("https://")
+                        .desired_width(400.0),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.horizontal(|ui| {
+                    let url = self.url.trim();
+                    let valid = url.starts_with("http://") || url.starts_with("https://");
+                    let fetching = self.fetching.is_some();
+                    let clicked = ui
+                        .add_enabled(valid && !fetching, egui::Button::new("Open"))
+                        .clicked();
+                    if (clicked || submitted) && valid && !fetching {
+                        self.fetching = Some(url.to_string());
+                        self.error = None;
+                        fetch_url(ctx, url, &self.fetched);
+                    }
+                    if fetching {
+                        ui.spinner();
+                    }
+                });
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+        // Closing the window while downloading discards the downloaded file.
+        if !open {
+            self.open = false;
+            self.fetching = None;
+            self.fetched = Fetched::default();
+        }
+        downloaded
+    }
+}
@@ -0,0 +1,209 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use egui::{Color32, ColorImage, Pos2, Rect, Sense, TextureHandle, Ui, Vec2};
+use mapproj::{pseudocyl::mol::Mol, CenteredProjection, LonLat, ProjXY, Projection};
+use moc::{moc::range::RangeMOC, qty::Hpx};
+
+pub(crate) type SMoc = RangeMOC<u64, Hpx<u64>>;
+
+// Half width of the Mollweide projection plane, the full sky fits in [-2V2, 2V2] x [-V2, V2].
+const MOL_HALF_WIDTH: f64 = 2.0 * SQRT_2;
+const MAX_ZOOM: f64 = 1e7;
+// The side, in pixels of the view, of the pixels of the image rendered while panning.
+const PAN_PIXEL: usize = 4;
+
+// A layer of the sky view, an S-MOC drawn with a colour and an opacity in [0, 1].
+#[derive(Clone, Copy)]
+pub(crate) struct Layer<'a> {
+    pub(crate) moc: &'a SMoc,
+    pub(crate) color: [u8; 3],
+    pub(crate) opacity: f32,
+}
+
+// The parameters a rendered image depends on, to only render again when they change.
+#[derive(Clone, PartialEq)]
+struct RenderKey {
+    center: (f64, f64),
+    zoom: f64,
+    size: [usize; 2],
+    // The side of a rendered pixel in pixels of the view, larger than 1 while panning.
+    step: usize,
+    background: Color32,
+}
+
+// The SkyView struct, a pannable and zoomable Mollweide view of the sky on which S-MOCs are drawn.
+// The view is centered on `center` (lon, lat in radians), at zoom 1 the whole sky is shown.
+// The image is rendered again from the MOCs themselves at each zoom level, so that the
+// cells of deep MOCs can be seen once zoomed in. While panning, it is rendered at a lower
+// resolution and at full resolution once the drag stops.
+#[derive(Clone)]
+pub(crate) struct SkyView {
+    center: (f64, f64),
+    zoom: f64,
+    texture: Option<TextureHandle>,
+    rendered: Option<RenderKey>,
+}
+impl Default for SkyView {
+    fn default() -> Self {
+        Self {
+            center: (0.0, 0.0),
+            zoom: 1.0,
+            texture: None,
+            rendered: None,
+        }
+    }
+}
+impl SkyView {
+    // #Definition
+    //      ui shows the view, handles pan (drag) and zoom (scroll) and renders the layers if needed.
+    //      It returns the sky position (lon, lat in radians) under the cursor, if any.
+    // #Args
+    //  *   `ui`: the ui in which the view is shown, it takes all the available width
+    //  *   `layers`: the MOCs to draw, the last one is drawn on top
+    pub(crate) fn ui(&mut self, ui: &mut Ui, layers: &[Layer<'_>]) -> Option<LonLat> {
+        let width = ui.available_width().max(100.0);
+        let (response, painter) =
+            ui.allocate_painter(Vec2::new(width, width / 2.0), Sense::click_and_drag());
+        let rect = response.rect;
+        let scale = self.scale(rect.width());
+
+        // Pan: the sky point dragged to the center becomes the new center.
+        let drag = response.drag_delta();
+        if drag != Vec2::ZERO {
+            let xy = self.to_proj(-drag, scale);
+            if let Some(lonlat) = self.projection().unproj_lonlat(&xy) {
+                self.center = (lonlat.lon(), lonlat.lat());
+            }
+        }
+        // Zoom: with the mouse wheel or a pinch.
+        if response.hovered() {
+            let (scroll, pinch) = ui.input(|i| (i.scroll_delta.y, i.zoom_delta()));
+            let factor = (scroll as f64 / 200.0).exp() * pinch as f64;
+            if factor != 1.0 {
+                self.zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+            }
+        }
+        if response.double_clicked() {
+            self.reset();
+        }
+
+        let size = [rect.width() as usize, rect.height() as usize];
+        let key = RenderKey {
+            center: self.center,
+            zoom: self.zoom,
+            size,
+            step: if response.dragged() { PAN_PIXEL } else { 1 },
+            background: ui.visuals().extreme_bg_color,
+        };
+        if self.texture.is_none() || self.rendered.as_ref() != Some(&key) {
+            let image = self.render(&key, layers);
+            self.texture = Some(ui.ctx().load_texture("sky_view", image, Default::default()));
+            self.rendered = Some(key);
+        }
+        if let (Some(texture), Some(key)) = (&self.texture, &self.rendered) {
+            // The last rendered pixels may go beyond the view.
+            let [w, h] = texture.size();
+            let uv = Rect::from_min_max(
+                Pos2::ZERO,
+                Pos2::new(
+                    key.size[0] as f32 / (w * key.step) as f32,
+                    key.size[1] as f32 / (h * key.step) as f32,
+                ),
+            );
+            painter.image(texture.id(), rect, uv, Color32::WHITE);
+        }
+
+        response.hover_pos().and_then(|pos| {
+            let xy = self.to_proj(pos - rect.center(), scale);
+            self.projection().unproj_lonlat(&xy)
+        })
+    }
+
+    // #Definition
+    //      invalidate forces the layers to be rendered again, e.g. when they have changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+
+    // #Definition
+    //      reset goes back to the whole sky view.
+    pub(crate) fn reset(&mut self) {
+        self.center = (0.0, 0.0);
+        self.zoom = 1.0;
+    }
+
+    pub(crate) fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    pub(crate) fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.clamp(1.0, MAX_ZOOM);
+    }
+
+    // #Definition
+    //      fov_deg gives the horizontal field of view, in degrees (exact at the view center).
+    pub(crate) fn fov_deg(&self) -> f64 {
+        360.0 / self.zoom
+    }
+
+    ///////////////
+    // UTILITIES //
+
+    fn projection(&self) -> CenteredProjection<Mol> {
+        let mut proj = CenteredProjection::new(Mol::new());
+        proj.set_proj_center_from_lonlat(&LonLat::new(self.center.0, self.center.1));
+        proj
+    }
+
+    // Number of pixels per unit of the projection plane.
+    fn scale(&self, width: f32) -> f64 {
+        self.zoom * width as f64 / (2.0 * MOL_HALF_WIDTH)
+    }
+
+    // From an offset to the view center (in pixels) to the projection plane.
+    // The longitude increases to the left, as on the sky.
+    fn to_proj(&self, offset: Vec2, scale: f64) -> ProjXY {
+        ProjXY::new(-offset.x as f64 / scale, -offset.y as f64 / scale)
+    }
+
+    // #Definition
+    //      render computes the image of the layers, pixel by pixel, a pixel of the image
+    //      covering key.step x key.step pixels of the view.
+    fn render(&self, key: &RenderKey, layers: &[Layer<'_>]) -> ColorImage {
+        let ([w, h], step) = (key.size, key.step);
+        let scale = self.scale(w as f32);
+        let proj = self.projection();
+        let bg = key.background;
+        let size = [(w + step - 1) / step, (h + step - 1) / step];
+        let mut image = ColorImage::new(size, Color32::TRANSPARENT);
+        for py in 0..size[1] {
+            for px in 0..size[0] {
+                let offset = Vec2::new(
+                    (px as f32 + 0.5) * step as f32 - w as f32 / 2.0,
+                    (py as f32 + 0.5) * step as f32 - h as f32 / 2.0,
+                );
+                let Some(lonlat) = proj.unproj_lonlat(&self.to_proj(offset, scale)) else {
+                    continue;
+                };
+                let mut rgb = [bg.r() as f32, bg.g() as f32, bg.b() as f32];
+                for layer in layers {
+                    if layer.moc.is_in(lonlat.lon(), lonlat.lat()) {
+                        for (c, l) in rgb.iter_mut().zip(layer.color) {
+                            *c = *c * (1.0 - layer.opacity) + l as f32 * layer.opacity;
+                        }
+                    }
+                }
+                image.pixels[py * size[0] + px] =
+                    Color32::from_rgb(rgb[0] as u8, rgb[1] as u8, rgb[2] as u8);
+            }
+        }
+        image
+    }
+}
+
+// #Definition
+//      lon_lat_deg converts a sky position to (RA, Dec) in degrees, RA in [0, 360[.
+pub(crate) fn lon_lat_deg(lonlat: &LonLat) -> (f64, f64) {
+    let lon = lonlat.lon().rem_euclid(2.0 * PI);
+    (lon.to_degrees(), lonlat.lat().to_degrees())
+}
@@ -0,0 +1,232 @@
+use std::ops::{Range, RangeInclusive};
+
+use egui::plot::{Plot, PlotBounds, PlotPoint, PlotPoints, Polygon};
+use egui::{Color32, Ui};
+use time::{Duration, OffsetDateTime};
+
+use crate::controllers::creation::JD_TO_USEC;
+
+// The JD of the Unix epoch, 1970-01-01T00:00:00.
+const JD_UNIX_EPOCH: f64 = 2_440_587.5;
+// Half the height of a T-MOC row, rows are 1 unit apart.
+const ROW_HALF_HEIGHT: f64 = 0.35;
+
+// A layer of the timeline, the ranges of a T-MOC (in microseconds since JD=0) drawn on a row.
+#[derive(Clone, Copy)]
+pub(crate) struct TimeLayer<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) ranges: &'a [Range<u64>],
+    pub(crate) color: [u8; 3],
+}
+
+// #Definition
+//      timeline_ui shows the layers on a horizontal timeline, one row per layer, the first one
+//      on top. The timeline can be dragged and zoomed (ctrl + scroll or boxed zoom with the right
+//      button), a double click shows all the ranges again.
+//      Hovering a range shows its start and end.
+// #Args
+//  *   `ui`: the ui in which the timeline is shown
+//  *   `id_source`: a source for the id of the timeline, unique in the ui
+//  *   `layers`: the T-MOCs to draw
+pub(crate) fn timeline_ui(ui: &mut Ui, id_source: &str, layers: &[TimeLayer<'_>]) {
+    // Whether the JD range has been set, the plot then keeps it (with the user's changes).
+    let view_id = ui.make_persistent_id(id_source).with("jd_range_set");
+    let view_set: bool = ui.data_mut(|d| d.get_temp(view_id)).unwrap_or(false);
+    let names: Vec<String> = layers.iter().map(|l| l.name.to_string()).collect();
+    let y_range = [-(layers.len() as f64) + 0.5, 0.5];
+
+    let inner = Plot::new(id_source)
+        .height(40.0 + 30.0 * layers.len() as f32)
+        .show_x(false)
+        .show_y(false)
+        .allow_double_click_reset(false)
+        .x_axis_formatter(jd_axis_label)
+        .y_axis_formatter(move |y, _| row_label(&names, y))
+        .show(ui, |plot_ui| {
+            // Only the JD range of the last frame is kept, the plot would otherwise also zoom
+            // on the rows.
+            let last = plot_ui.plot_bounds();
+            let [x_min, x_max] = if view_set && last.is_valid_x() {
+                [last.min()[0], last.max()[0]]
+            } else {
+                jd_span(layers)?
+            };
+            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                [x_min, y_range[0]],
+                [x_max, y_range[1]],
+            ));
+            // The width of a pixel, in days.
+            let width = plot_ui.screen_from_plot(PlotPoint::new(x_max, 0.0)).x
+                - plot_ui.screen_from_plot(PlotPoint::new(x_min, 0.0)).x;
+            let px = (x_max - x_min) / width.max(1.0) as f64;
+
+            for (row, layer) in layers.iter().enumerate() {
+                let y = -(row as f64);
+                let color = Color32::from_rgb(layer.color[0], layer.color[1], layer.color[2]);
+                for (start, end) in visible_spans(layer.ranges, x_min, x_max, px) {
+                    let rect = vec![
+                        [start, y - ROW_HALF_HEIGHT],
+                        [end, y - ROW_HALF_HEIGHT],
+                        [end, y + ROW_HALF_HEIGHT],
+                        [start, y + ROW_HALF_HEIGHT],
+                    ];
+                    plot_ui.polygon(
+                        Polygon::new(PlotPoints::new(rect))
+                            .color(color)
+                            .fill_alpha(0.6)
+                            .width(1.0),
+                    );
+                }
+            }
+            Some((plot_ui.pointer_coordinate(), px))
+        });
+
+    let response = inner.response;
+    if response.double_clicked() {
+        ui.data_mut(|d| d.remove::<bool>(view_id));
+        return;
+    }
+    let Some((pointer, px)) = inner.inner else {
+        return;
+    };
+    ui.data_mut(|d| d.insert_temp(view_id, true));
+
+    let Some((layer, range)) = pointer.and_then(|p| hovered_range(layers, &p, px)) else {
+        return;
+    };
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(layer.name);
+        ui.label(format!(
+            "Start: {}",
+            jd_label(range.start as f64 / JD_TO_USEC)
+        ));
+        ui.label(format!("End: {}", jd_label(range.end as f64 / JD_TO_USEC)));
+    });
+}
+
+// #Definition
+//      jd_to_iso converts a decimal JD to an ISO 8601 UTC date (with its time, to the
+//      millisecond, if `with_time`). None if the date is out of the supported years.
+pub(crate) fn jd_to_iso(jd: f64, with_time: bool) -> Option<String> {
+    let usec = ((jd - JD_UNIX_EPOCH) * JD_TO_USEC).round();
+    if !usec.is_finite() || usec.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    let date = OffsetDateTime::UNIX_EPOCH.checked_add(Duration::microseconds(usec as i64))?;
+    let day = format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        date.month() as u8,
+        date.day()
+    );
+    if !with_time {
+        return Some(day);
+    }
+    Some(format!(
+        "{day}T{:02}:{:02}:{:02}.{:03}",
+        date.hour(),
+        date.minute(),
+        date.second(),
+        date.millisecond()
+    ))
+}
+
+// #Definition
+//      jd_label formats a decimal JD as its ISO date and time followed by the JD itself.
+pub(crate) fn jd_label(jd: f64) -> String {
+    match jd_to_iso(jd, true) {
+        Some(iso) => format!("{iso} (JD {jd:.8})"),
+        None => format!("JD {jd:.8}"),
+    }
+}
+
+///////////////
+// UTILITIES //
+
+// The JD range of all the layers, with a margin.
+fn jd_span(layers: &[TimeLayer<'_>]) -> Option<[f64; 2]> {
+    let min = layers
+        .iter()
+        .filter_map(|l| l.ranges.first())
+        .map(|r| r.start)
+        .min()?;
+    let max = layers
+        .iter()
+        .filter_map(|l| l.ranges.last())
+        .map(|r| r.end)
+        .max()?;
+    let (min, max) = (min as f64 / JD_TO_USEC, max as f64 / JD_TO_USEC);
+    let margin = ((max - min) * 0.05).max(1.0 / 24.0);
+    Some([min - margin, max + margin])
+}
+
+// #Definition
+//      visible_spans gives the ranges (in JD) in [x_min, x_max]. Ranges closer than a pixel are
+//      merged, so that the number of drawn shapes does not depend on the number of ranges.
+fn visible_spans(ranges: &[Range<u64>], x_min: f64, x_max: f64, px: f64) -> Vec<(f64, f64)> {
+    let first = ranges.partition_point(|r| (r.end as f64 / JD_TO_USEC) < x_min);
+    let mut spans: Vec<(f64, f64)> = Vec::new();
+    for r in &ranges[first..] {
+        let start = r.start as f64 / JD_TO_USEC;
+        if start > x_max {
+            break;
+        }
+        let (start, end) = (
+            start.max(x_min - px),
+            (r.end as f64 / JD_TO_USEC).min(x_max + px),
+        );
+        match spans.last_mut() {
+            Some(last) if start - last.1 < px => last.1 = end,
+            _ => spans.push((start, end)),
+        }
+    }
+    spans
+}
+
+// #Definition
+//      hovered_range gives the layer and the range under the pointer, if any.
+//      A range is hovered if it is less than a pixel away from the pointer.
+fn hovered_range<'a>(
+    layers: &[TimeLayer<'a>],
+    pointer: &PlotPoint,
+    px: f64,
+) -> Option<(TimeLayer<'a>, Range<u64>)> {
+    let row = (-pointer.y).round();
+    if row < 0.0 || (-pointer.y - row).abs() > ROW_HALF_HEIGHT {
+        return None;
+    }
+    let layer = *layers.get(row as usize)?;
+    let (lo, hi) = ((pointer.x - px) * JD_TO_USEC, (pointer.x + px) * JD_TO_USEC);
+    let i = layer.ranges.partition_point(|r| (r.end as f64) < lo);
+    let range = layer.ranges.get(i).filter(|r| r.start as f64 <= hi)?;
+    Some((layer, range.clone()))
+}
+
+// #Definition
+//      jd_axis_label formats the JD axis: a few round values are labeled with their ISO date
+//      (and time when zoomed in) and their JD.
+fn jd_axis_label(jd: f64, range: &RangeInclusive<f64>) -> String {
+    let span = range.end() - range.start();
+    if span.is_nan() || span <= 0.0 {
+        return String::new();
+    }
+    // Labels only at multiples of a power of 10 giving between 1 and 4 labels.
+    let step = 10_f64.powf((span / 4.0).log10().ceil());
+    if ((jd / step).round() * step - jd).abs() > step * 1e-3 {
+        return String::new();
+    }
+    let decimals = (1.0 - step.log10().floor()).clamp(1.0, 9.0) as usize;
+    match jd_to_iso(jd, step < 1.0) {
+        Some(iso) => format!("{iso}\nJD {jd:.decimals$}"),
+        None => format!("JD {jd:.decimals$}"),
+    }
+}
+
+// The name of the layer on the row at `y`, the first row is at 0 and the next ones below.
+fn row_label(names: &[String], y: f64) -> String {
+    let row = (-y).round();
+    if row < 0.0 || (-y - row).abs() > 1e-6 {
+        return String::new();
+    }
+    names.get(row as usize).cloned().unwrap_or_default()
+}